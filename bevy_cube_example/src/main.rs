@@ -2,27 +2,21 @@
 
 use std::f32::consts::PI;
 
-use bevy::{
-    color::palettes::css::GOLD,
-    prelude::*,
-    render::{
-        camera::RenderTarget,
-        render_asset::RenderAssetUsages,
-        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
-    },
-};
+use bevy::prelude::*;
 use ratatui::{
     Frame,
-    prelude::{Stylize, Terminal},
+    prelude::Stylize,
     widgets::{Block, Borders, Paragraph, Wrap},
 };
-use soft_ratatui::SoftBackend;
+use soft_ratatui::{
+    InputEvent, KeyCode as TerminalKey, SoftBackend, SoftRatatuiPlugin, SoftTerminal,
+    TerminalFocus, TerminalImage, TerminalInput,
+};
 static FONT_DATA: &[u8] = include_bytes!("../../assets/iosevka.ttf");
 
 fn main() {
     App::new()
-        .add_plugins(DefaultPlugins)
-        .init_resource::<SoftTerminal>()
+        .add_plugins((DefaultPlugins, SoftRatatuiPlugin))
         .add_systems(Startup, setup)
         .add_systems(Update, rotator_system)
         .add_systems(Update, computer_test)
@@ -32,83 +26,21 @@ fn main() {
 // Marks the cube, to which the UI texture is applied.
 #[derive(Component)]
 struct Cube;
-#[derive(Resource)]
-struct MyProcGenMaterial(Handle<StandardMaterial>);
-
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    mut softatui: ResMut<SoftTerminal>,
     mut images: ResMut<Assets<Image>>,
 ) {
-    softatui
-        .draw(|frame| {
-            let area = frame.area();
-            let textik = format!("Hello bevy! The window area is {}", area);
-            frame.render_widget(
-                Paragraph::new(textik)
-                    .block(Block::new().title("Ratatui").borders(Borders::ALL))
-                    .white()
-                    .on_blue()
-                    .wrap(Wrap { trim: false }),
-                area,
-            );
-        })
-        .expect("epic fail");
-
-    let width = softatui.backend().get_pixmap_width() as u32;
-    let height = softatui.backend().get_pixmap_height() as u32;
-    let data = softatui.backend().get_pixmap_data_as_rgba();
-
-    let mut image = Image::new(
-        Extent3d {
-            width,
-            height,
-            depth_or_array_layers: 1,
-        },
-        TextureDimension::D2,
-        data,
-        TextureFormat::Rgba8UnormSrgb,
-        RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
-    );
-    // You need to set these texture usage flags in order to use the image as a render target
-    image.texture_descriptor.usage =
-        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
-
-    let image_handle = images.add(image);
+    // The plugin sizes the image like the pixmap and copies the redrawn cells into it.
+    let image_handle = images.add(Image::default());
+    let mut softatui = SoftTerminal::new(SoftBackend::new_with_font(15, 15, 16, FONT_DATA));
+    softatui.draw(draw_computer_screen).expect("epic fail");
+    commands.spawn((softatui, TerminalImage(image_handle.clone()), TerminalFocus));
 
     // Light
     commands.spawn(DirectionalLight::default());
 
-    let texture_camera = commands
-        .spawn((
-            Camera2d,
-            Camera {
-                target: RenderTarget::Image(image_handle.clone().into()),
-                ..default()
-            },
-        ))
-        .id();
-
-    commands
-        .spawn((
-            Node {
-                // Cover the whole image
-                width: Val::Percent(100.),
-                height: Val::Percent(100.),
-                flex_direction: FlexDirection::Column,
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                ..default()
-            },
-            BackgroundColor(GOLD.into()),
-            UiTargetCamera(texture_camera),
-        ))
-        .with_children(|parent| {
-            parent.spawn(ImageNode::new(image_handle.clone()));
-        });
-
     let cube_size = 4.0;
     let cube_handle = meshes.add(Cuboid::new(cube_size, cube_size, cube_size));
 
@@ -120,7 +52,6 @@ fn setup(
 
         ..default()
     });
-    commands.insert_resource(MyProcGenMaterial(material_handle.clone()));
 
     // Cube with material containing the rendered UI texture.
     commands.spawn((
@@ -146,17 +77,6 @@ fn rotator_system(time: Res<Time>, mut query: Query<&mut Transform, With<Cube>>)
     }
 }
 
-// Create resource to hold the ratatui terminal
-#[derive(Resource, Deref, DerefMut)]
-struct SoftTerminal(Terminal<SoftBackend>);
-impl Default for SoftTerminal {
-    fn default() -> Self {
-        let mut backend = SoftBackend::new_with_font(15, 15, 16, FONT_DATA);
-        //backend.set_font_size(12);
-        Self(Terminal::new(backend).unwrap())
-    }
-}
-
 pub fn draw_computer_screen(frame: &mut Frame) {
     let area = frame.area();
     let textik = format!("Hello bevy! The window area is {}", area);
@@ -183,43 +103,15 @@ pub fn new_computer_screen(frame: &mut Frame) {
     );
 }
 
-fn computer_test(
-    key: Res<ButtonInput<KeyCode>>,
-    mut softatui: ResMut<SoftTerminal>,
-    proc_material: Res<MyProcGenMaterial>,
-    mut images: ResMut<Assets<Image>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
+fn computer_test(mut input: MessageReader<TerminalInput>, mut terminals: Query<&mut SoftTerminal>) {
     trace!("SYSTEM: computer_test");
 
-    if key.just_pressed(KeyCode::KeyK) {
-        softatui.draw(new_computer_screen).expect("oops");
-
-        let width = softatui.backend().get_pixmap_width() as u32;
-        let height = softatui.backend().get_pixmap_height() as u32;
-        let data = softatui.backend().get_pixmap_data_as_rgba();
-        let material = materials
-            .get_mut(&proc_material.0)
-            .expect("material not found!");
-
-        let image = images
-            .get_mut(material.base_color_texture.as_ref().unwrap().id())
-            .expect("Image not found!");
-
-        let mut temp = Image::new(
-            Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            bevy::render::render_resource::TextureDimension::D2,
-            data,
-            TextureFormat::Rgba8UnormSrgb,
-            RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
-        );
-        temp.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
-            | TextureUsages::COPY_DST
-            | TextureUsages::RENDER_ATTACHMENT;
-        *image = temp;
+    for input in input.read() {
+        if let InputEvent::Key(key) = input.event
+            && key.code == TerminalKey::Char('k')
+            && let Ok(mut softatui) = terminals.get_mut(input.terminal)
+        {
+            softatui.draw(new_computer_screen).expect("oops");
+        }
     }
 }
@@ -1,8 +1,8 @@
-use eframe::egui::{self, TextureHandle};
+use eframe::egui;
 /// A minimal example of a Ratatui application.
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use ratatui::{Frame, Terminal};
-use soft_ratatui::SoftBackend;
+use soft_ratatui::{EguiTerminal, InputEvent, KeyCode, SoftBackend};
 use std::time::{Duration, Instant};
 
 use color_eyre::Result;
@@ -36,20 +36,18 @@ fn main() -> eframe::Result {
 }
 
 struct MyApp {
-    pub terminal: Terminal<SoftBackend>,
-    pub text_ref: Option<TextureHandle>,
+    pub terminal: EguiTerminal,
     pub appik: App,
 }
 
 impl MyApp {
     fn new() -> Self {
         let backend = SoftBackend::new_with_system_fonts(150, 100, 12);
-        let mut terminal = Terminal::new(backend).unwrap();
+        let terminal = EguiTerminal::new(backend);
         let appik = App::default();
 
         Self {
             terminal,
-            text_ref: None,
             appik,
         }
     }
@@ -63,26 +61,15 @@ impl eframe::App for MyApp {
 
         // self.terminal.backend_mut().set_font_size(sizeik);
 
-        let colorik = egui::ColorImage::from_rgb(
-            [
-                self.terminal.backend().get_pixmap_width(),
-                self.terminal.backend().get_pixmap_height(),
-            ],
-            self.terminal.backend().get_pixmap_data(),
-        );
-
-        let texture = ctx.load_texture(
-            "my-color-image", // texture ID (can be anything)
-            colorik.clone(),  // your ColorImage
-            Default::default(),
-        );
-        self.text_ref = Some(texture.clone());
-
-        egui::CentralPanel::default().show(ctx, |ui| {
-            egui::ScrollArea::both().show(ui, |ui| {
-                ui.image((texture.id(), texture.size_vec2()));
-            });
-        });
+        // The widget fits the terminal to the panel and uploads the redrawn regions.
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| ui.add(&mut self.terminal));
+        for event in self.terminal.take_events() {
+            if matches!(event, InputEvent::Key(key) if key.code == KeyCode::Char('q')) {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+        }
         ctx.request_repaint();
     }
 }
@@ -10,16 +10,16 @@
 /// [`latest`]: https://github.com/ratatui/ratatui/tree/latest
 use std::{error::Error, iter::once, result};
 
-use eframe::egui::{self, TextureHandle};
+use eframe::egui;
 use itertools::Itertools;
 use ratatui::layout::{Constraint, Layout};
 use ratatui::style::{Color, Modifier, Style, Stylize};
 use ratatui::text::Line;
 use ratatui::widgets::Paragraph;
 /// A minimal example of a Ratatui application.
-use ratatui::{Frame, Terminal};
+use ratatui::Frame;
 
-use soft_ratatui::SoftBackend;
+use soft_ratatui::{EguiTerminal, SoftBackend};
 static FONT_DATA: &[u8] = include_bytes!("../../assets/iosevka.ttf");
 fn main() -> eframe::Result {
     let options = eframe::NativeOptions {
@@ -41,18 +41,16 @@ fn main() -> eframe::Result {
 }
 
 struct MyApp {
-    pub terminal: Terminal<SoftBackend>,
-    pub text_ref: Option<TextureHandle>,
+    pub terminal: EguiTerminal,
 }
 
 impl MyApp {
     fn new() -> Self {
         let backend = SoftBackend::new_with_system_fonts(100, 50, 16);
-        let mut terminal = Terminal::new(backend).unwrap();
+        let terminal = EguiTerminal::new(backend);
 
         Self {
             terminal,
-            text_ref: None,
         }
     }
 }
@@ -62,26 +60,10 @@ impl eframe::App for MyApp {
         // terminal.draw(draw).expect("failed to draw frame");
         self.terminal.draw(draw).unwrap();
 
-        let colorik = egui::ColorImage::from_rgb(
-            [
-                self.terminal.backend().get_pixmap_width(),
-                self.terminal.backend().get_pixmap_height(),
-            ],
-            self.terminal.backend().get_pixmap_data(),
-        );
-
-        let texture = ctx.load_texture(
-            "my-color-image", // texture ID (can be anything)
-            colorik.clone(),  // your ColorImage
-            Default::default(),
-        );
-        self.text_ref = Some(texture.clone());
-
-        egui::CentralPanel::default().show(ctx, |ui| {
-            egui::ScrollArea::both().show(ui, |ui| {
-                ui.image((texture.id(), texture.size_vec2()));
-            });
-        });
+        // The widget fits the terminal to the panel and uploads the redrawn regions.
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| ui.add(&mut self.terminal));
         ctx.request_repaint();
     }
 }
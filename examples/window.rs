@@ -0,0 +1,36 @@
+//! Runs a ratatui app in a desktop window, showing the last input it received.
+//!
+//! `cargo run --example window --features window`, press Esc to quit.
+use std::ops::ControlFlow;
+
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use soft_ratatui::{AppEvent, BitmapFont, InputEvent, KeyCode, SoftBackend, run_app};
+
+fn main() {
+    let backend = SoftBackend::new_with_bitmap_font(80, 25, BitmapFont::FIXED_8X13);
+    let mut last_input = String::from("none");
+    run_app("soft_ratatui", backend, |terminal, event| {
+        if let AppEvent::Input(input) = event {
+            if let InputEvent::Key(key) = input
+                && key.code == KeyCode::Esc
+            {
+                return ControlFlow::Break(());
+            }
+            last_input = format!("{input:?}");
+        }
+        terminal
+            .draw(|frame| {
+                let area = frame.area();
+                let text = format!("The window area is {area}\nLast input: {last_input}");
+                frame.render_widget(
+                    Paragraph::new(text)
+                        .block(Block::new().title("Ratatui").borders(Borders::ALL))
+                        .wrap(Wrap { trim: false }),
+                    area,
+                );
+            })
+            .expect("failed to draw frame");
+        ControlFlow::Continue(())
+    })
+    .expect("failed to run the window");
+}
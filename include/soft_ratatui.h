@@ -0,0 +1,195 @@
+/*
+ * C API of soft_ratatui, built into the cdylib with the `capi` feature:
+ *
+ *     cargo build --release --features capi
+ *
+ * Functions report a failure, a panic caught in the library, through their return value, after
+ * which the handle should only be freed.
+ *
+ * Generated from src/capi.rs by cbindgen, see cbindgen.toml. tests/capi.rs compiles and runs
+ * tests/data/capi.c against this header.
+ */
+
+#ifndef SOFT_RATATUI_H
+#define SOFT_RATATUI_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+/* Colors of soft_ratatui_set_cell. */
+#define SOFT_RATATUI_RGB(r, g, b) \
+    ((((uint32_t)(r) & 0xff) << 16) | (((uint32_t)(g) & 0xff) << 8) | ((uint32_t)(b) & 0xff))
+/* A color of the 256 color palette, the first 16 being the ANSI colors. */
+#define SOFT_RATATUI_INDEXED(index) (0x01000000u | ((uint32_t)(index) & 0xff))
+/* The default foreground or background color. */
+#define SOFT_RATATUI_RESET 0xff000000u
+
+/* Modifiers of soft_ratatui_set_cell, combined with `|`. */
+#define SOFT_RATATUI_BOLD 0x0001
+#define SOFT_RATATUI_DIM 0x0002
+#define SOFT_RATATUI_ITALIC 0x0004
+#define SOFT_RATATUI_UNDERLINED 0x0008
+#define SOFT_RATATUI_SLOW_BLINK 0x0010
+#define SOFT_RATATUI_RAPID_BLINK 0x0020
+#define SOFT_RATATUI_REVERSED 0x0040
+#define SOFT_RATATUI_HIDDEN 0x0080
+#define SOFT_RATATUI_CROSSED_OUT 0x0100
+
+/**
+ * The pixel format of `SoftRatatuiPixmap`: 3 bytes per pixel, red first.
+ */
+#define SOFT_RATATUI_FORMAT_RGB888 0
+
+/**
+ * A backend with the cells to draw into it.
+ */
+typedef struct SoftRatatui SoftRatatui;
+
+/**
+ * The pixels of the pixmap, valid until the next call that takes the handle mutably.
+ */
+typedef struct SoftRatatuiPixmap {
+  const uint8_t *data;
+  size_t width;
+  size_t height;
+  /**
+   * The number of bytes from the start of a row to the next.
+   */
+  size_t stride;
+  /**
+   * `SOFT_RATATUI_FORMAT_RGB888`.
+   */
+  uint32_t format;
+} SoftRatatuiPixmap;
+
+/**
+ * A rectangle of the pixmap in pixels.
+ */
+typedef struct SoftRatatuiRect {
+  uint32_t x;
+  uint32_t y;
+  uint32_t width;
+  uint32_t height;
+} SoftRatatuiRect;
+
+
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a handle of `cols` by `rows` cells drawn with the built-in 8x13 bitmap font.
+ * Returns null if it fails.
+ */
+struct SoftRatatui *soft_ratatui_new_bitmap(uint16_t cols, uint16_t rows);
+
+/**
+ * Creates a handle of `cols` by `rows` cells drawn with a TTF/OTF font of `font_size` pixels.
+ * Returns null if the font can't be loaded.
+ *
+ * # Safety
+ * `font_data` must point to `font_len` readable bytes, which are copied.
+ */
+struct SoftRatatui *soft_ratatui_new_with_font(uint16_t cols,
+                                               uint16_t rows,
+                                               int32_t font_size,
+                                               const uint8_t *font_data,
+                                               size_t font_len);
+
+/**
+ * Destroys a handle. Null is ignored.
+ *
+ * # Safety
+ * `handle` must be null or come from a constructor, and not be used afterwards.
+ */
+void soft_ratatui_free(struct SoftRatatui *handle);
+
+/**
+ * Resizes the grid to `cols` by `rows` cells, keeping the top left of the cells. The pixmap is
+ * resized and fully redrawn. Returns false if it fails.
+ *
+ * # Safety
+ * `handle` must be a live handle.
+ */
+bool soft_ratatui_resize(struct SoftRatatui *handle, uint16_t cols, uint16_t rows);
+
+/**
+ * Sets a cell to a UTF-8 symbol with colors, see `SOFT_RATATUI_RGB`, and modifiers, see
+ * `SOFT_RATATUI_BOLD`. Returns false if the cell is outside the grid, the symbol isn't UTF-8 or
+ * it fails.
+ *
+ * # Safety
+ * `handle` must be a live handle and `symbol` a null terminated string.
+ */
+bool soft_ratatui_set_cell(struct SoftRatatui *handle,
+                           uint16_t x,
+                           uint16_t y,
+                           const char *symbol,
+                           uint32_t fg,
+                           uint32_t bg,
+                           uint16_t modifiers);
+
+/**
+ * Resets every cell to a blank with the default colors. Returns false if it fails.
+ *
+ * # Safety
+ * `handle` must be a live handle.
+ */
+bool soft_ratatui_clear(struct SoftRatatui *handle);
+
+/**
+ * Parses ANSI text, e.g. the output of a program, into a terminal screen that replaces the
+ * cells at the next render. Sequences may be split across calls. Returns false if `data` is
+ * null or it fails.
+ *
+ * # Safety
+ * `handle` must be a live handle and `data` point to `len` readable bytes.
+ */
+bool soft_ratatui_feed(struct SoftRatatui *handle, const uint8_t *data, size_t len);
+
+/**
+ * Draws the cells that changed since the last render into the pixmap, and marks their
+ * pixels dirty. Returns false if it fails.
+ *
+ * # Safety
+ * `handle` must be a live handle.
+ */
+bool soft_ratatui_render(struct SoftRatatui *handle);
+
+/**
+ * Returns the pixels of the pixmap, with null data if it fails.
+ *
+ * # Safety
+ * `handle` must be a live handle.
+ */
+struct SoftRatatuiPixmap soft_ratatui_pixmap(const struct SoftRatatui *handle);
+
+/**
+ * Writes up to `capacity` rectangles of the pixmap redrawn since the last
+ * `soft_ratatui_clear_dirty` to `rects`, and returns how many there are in total, or
+ * `SIZE_MAX` if it fails.
+ *
+ * # Safety
+ * `handle` must be a live handle and `rects` point to `capacity` writable rectangles, or be
+ * null with a capacity of 0.
+ */
+size_t soft_ratatui_dirty_rects(const struct SoftRatatui *handle,
+                                struct SoftRatatuiRect *rects,
+                                size_t capacity);
+
+/**
+ * Forgets the dirty rectangles, call after the pixmap has been presented. Returns false if it
+ * fails.
+ *
+ * # Safety
+ * `handle` must be a live handle.
+ */
+bool soft_ratatui_clear_dirty(struct SoftRatatui *handle);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SOFT_RATATUI_H */
@@ -0,0 +1,940 @@
+//! Reading and writing of asciinema v2 recordings (`.cast` files), to play sessions of a
+//! [`SoftBackend`] in a real terminal and to render terminal sessions with the rasterizer.
+//!
+//! A recording is a JSON header line followed by a JSON array per event, `[time, code, data]`.
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use core::fmt::Write;
+
+use ratatui::backend::Backend;
+#[cfg(feature = "std")]
+use ratatui::backend::{ClearType, WindowSize};
+use ratatui::buffer::Cell;
+#[cfg(feature = "std")]
+use ratatui::layout::{Position, Size};
+#[cfg(feature = "std")]
+use ratatui::style::{Color, Modifier};
+#[cfg(feature = "std")]
+use unicode_width::UnicodeWidthStr;
+
+#[cfg(feature = "std")]
+use crate::record::RecordError;
+use crate::soft_backend::SoftBackend;
+use crate::vt::VtScreen;
+
+/// An event of an asciicast recording.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AsciicastEvent {
+    /// Data written to the terminal, `o`.
+    Output(String),
+    /// Data typed by the user, `i`.
+    Input(String),
+    /// The terminal was resized to columns and rows, `r`.
+    Resize(u16, u16),
+    /// A marker with a label, `m`.
+    Marker(String),
+}
+
+/// An error found while reading an asciicast recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsciicastError {
+    /// The first line is not a header with the terminal size.
+    InvalidHeader,
+    /// The recording is not in the version 2 format.
+    UnsupportedVersion,
+    /// An event is malformed, on the given line (starting at 1).
+    InvalidEvent(usize),
+}
+
+impl fmt::Display for AsciicastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "invalid asciicast header"),
+            Self::UnsupportedVersion => write!(f, "only asciicast version 2 is supported"),
+            Self::InvalidEvent(line) => write!(f, "invalid asciicast event on line {line}"),
+        }
+    }
+}
+
+impl core::error::Error for AsciicastError {}
+
+/// An asciicast v2 recording: the terminal size and the timed events.
+///
+/// # Examples
+/// ```rust
+/// use soft_ratatui::{Asciicast, AsciicastPlayer, BitmapFont, SoftBackend};
+///
+/// let cast = Asciicast::parse(
+///     "{\"version\": 2, \"width\": 20, \"height\": 2}\n\
+///      [0.5, \"o\", \"\\u001b[1mHello\"]\n",
+/// )
+/// .unwrap();
+/// let mut player = AsciicastPlayer::new(&cast);
+/// let mut backend = SoftBackend::new_with_bitmap_font(1, 1, BitmapFont::FIXED_8X13);
+/// assert_eq!(player.next_time(), Some(0.5));
+/// assert!(player.advance(1.0));
+/// player.draw(&mut backend);
+/// assert_eq!(backend.buffer.area.width, 20);
+/// assert_eq!(backend.buffer[(0, 0)].symbol(), "H");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Asciicast {
+    pub width: u16,
+    pub height: u16,
+    /// The events with their time in seconds since the start of the recording.
+    pub events: Vec<(f64, AsciicastEvent)>,
+}
+
+impl Asciicast {
+    /// Parses a recording. Events with unknown codes are skipped.
+    pub fn parse(text: &str) -> Result<Self, AsciicastError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let header = lines
+            .next()
+            .and_then(|(_, line)| Json::parse(line))
+            .ok_or(AsciicastError::InvalidHeader)?;
+        if header.get("version").and_then(Json::as_f64) != Some(2.0) {
+            return Err(AsciicastError::UnsupportedVersion);
+        }
+        let size = |key| {
+            header
+                .get(key)
+                .and_then(Json::as_f64)
+                .filter(|size| (1.0..=u16::MAX as f64).contains(size))
+                .map(|size| size as u16)
+                .ok_or(AsciicastError::InvalidHeader)
+        };
+        let (width, height) = (size("width")?, size("height")?);
+        let mut events = Vec::new();
+        for (index, line) in lines {
+            let invalid = AsciicastError::InvalidEvent(index + 1);
+            let Some(Json::Array(event)) = Json::parse(line) else {
+                return Err(invalid);
+            };
+            let [time, code, data] = event.as_slice() else {
+                return Err(invalid);
+            };
+            let (Some(time), Some(code), Some(data)) =
+                (time.as_f64(), code.as_str(), data.as_str())
+            else {
+                return Err(invalid);
+            };
+            let event = match code {
+                "o" => AsciicastEvent::Output(data.to_owned()),
+                "i" => AsciicastEvent::Input(data.to_owned()),
+                "m" => AsciicastEvent::Marker(data.to_owned()),
+                "r" => {
+                    let size = data.split_once('x').and_then(|(width, height)| {
+                        Some((width.parse().ok()?, height.parse().ok()?))
+                    });
+                    let Some((width, height)) = size else {
+                        return Err(invalid);
+                    };
+                    AsciicastEvent::Resize(width, height)
+                }
+                _ => continue,
+            };
+            events.push((time, event));
+        }
+        Ok(Self {
+            width,
+            height,
+            events,
+        })
+    }
+}
+
+/// Plays an asciicast recording on a [`VtScreen`], to draw it into a [`SoftBackend`].
+pub struct AsciicastPlayer<'a> {
+    events: &'a [(f64, AsciicastEvent)],
+    next: usize,
+    screen: VtScreen,
+}
+
+impl<'a> AsciicastPlayer<'a> {
+    /// Starts playing a recording on a blank screen of its size.
+    pub fn new(cast: &'a Asciicast) -> Self {
+        Self {
+            events: &cast.events,
+            next: 0,
+            screen: VtScreen::new(cast.width, cast.height),
+        }
+    }
+
+    /// Returns the time of the next event, or `None` at the end of the recording.
+    pub fn next_time(&self) -> Option<f64> {
+        self.events.get(self.next).map(|(time, _)| *time)
+    }
+
+    /// Applies the output and resize events up to `time`, returning whether there were any.
+    pub fn advance(&mut self, time: f64) -> bool {
+        let mut changed = false;
+        while let Some((event_time, event)) = self.events.get(self.next) {
+            if *event_time > time {
+                break;
+            }
+            match event {
+                AsciicastEvent::Output(data) => self.screen.feed(data.as_bytes()),
+                AsciicastEvent::Resize(width, height) => self.screen.resize(*width, *height),
+                AsciicastEvent::Input(_) | AsciicastEvent::Marker(_) => {
+                    self.next += 1;
+                    continue;
+                }
+            }
+            changed = true;
+            self.next += 1;
+        }
+        changed
+    }
+
+    /// Returns the screen the recording is played on.
+    pub fn screen(&self) -> &VtScreen {
+        &self.screen
+    }
+
+    /// Draws the cells of the screen that changed since the last draw, and its cursor, resizing
+    /// the backend to the screen if needed.
+    pub fn draw(&self, backend: &mut SoftBackend) {
+        let buffer = self.screen.buffer();
+        if backend.buffer.area != buffer.area {
+            backend.resize(buffer.area.width, buffer.area.height);
+        }
+        let updates: Vec<(u16, u16, &Cell)> = backend.buffer.diff(buffer);
+        let _ = backend.draw(updates.into_iter());
+        let _ = backend.set_cursor_position(self.screen.cursor());
+        let _ = if self.screen.cursor_visible() {
+            backend.show_cursor()
+        } else {
+            backend.hide_cursor()
+        };
+    }
+}
+
+/// A minimal JSON value, enough to read asciicast files.
+enum Json {
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+    Other,
+}
+
+impl Json {
+    fn parse(text: &str) -> Option<Self> {
+        let mut parser = JsonParser {
+            chars: text.chars().peekable(),
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.whitespace();
+        parser.chars.peek().is_none().then_some(value)
+    }
+
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(string) => Some(string),
+            _ => None,
+        }
+    }
+}
+
+/// The deepest nesting of arrays and objects parsed, deeper values are rejected rather than
+/// overflowing the stack.
+const MAX_JSON_DEPTH: usize = 32;
+
+struct JsonParser<'a> {
+    chars: core::iter::Peekable<core::str::Chars<'a>>,
+    /// The number of arrays and objects the parser is in.
+    depth: usize,
+}
+
+impl JsonParser<'_> {
+    fn whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_ascii_whitespace()).is_some() {}
+    }
+
+    fn expect(&mut self, expected: char) -> Option<()> {
+        self.whitespace();
+        self.chars.next_if_eq(&expected).map(|_| ())
+    }
+
+    fn value(&mut self) -> Option<Json> {
+        self.whitespace();
+        match *self.chars.peek()? {
+            '"' => self.string().map(Json::String),
+            '[' | '{' if self.depth == MAX_JSON_DEPTH => None,
+            '[' => {
+                self.chars.next();
+                self.depth += 1;
+                let mut items = Vec::new();
+                if self.expect(']').is_none() {
+                    loop {
+                        items.push(self.value()?);
+                        if self.expect(']').is_some() {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                self.depth -= 1;
+                Some(Json::Array(items))
+            }
+            '{' => {
+                self.chars.next();
+                self.depth += 1;
+                let mut members = Vec::new();
+                if self.expect('}').is_none() {
+                    loop {
+                        self.whitespace();
+                        let key = self.string()?;
+                        self.expect(':')?;
+                        members.push((key, self.value()?));
+                        if self.expect('}').is_some() {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                self.depth -= 1;
+                Some(Json::Object(members))
+            }
+            '-' | '0'..='9' => {
+                let mut number = String::new();
+                while let Some(c) = self
+                    .chars
+                    .next_if(|c| matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9'))
+                {
+                    number.push(c);
+                }
+                number.parse().ok().map(Json::Number)
+            }
+            _ => {
+                while self.chars.next_if(|c| c.is_ascii_alphabetic()).is_some() {}
+                Some(Json::Other)
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..4 {
+            value = value * 16 + self.chars.next()?.to_digit(16)?;
+        }
+        Some(value)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.chars.next_if_eq(&'"')?;
+        let mut string = String::new();
+        loop {
+            match self.chars.next()? {
+                '"' => return Some(string),
+                '\\' => string.push(match self.chars.next()? {
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'b' => '\u{8}',
+                    'f' => '\u{c}',
+                    'u' => {
+                        let high = self.hex4()?;
+                        let code = if (0xd800..0xdc00).contains(&high) {
+                            self.chars.next_if_eq(&'\\')?;
+                            self.chars.next_if_eq(&'u')?;
+                            let low = self.hex4()?;
+                            0x10000 + ((high - 0xd800) << 10) + (low.checked_sub(0xdc00)? & 0x3ff)
+                        } else {
+                            high
+                        };
+                        char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                    }
+                    c => c,
+                }),
+                c => string.push(c),
+            }
+        }
+    }
+}
+
+/// Encodes backend calls as the escape sequences a terminal needs to show them, keeping track of
+/// the terminal's pen and cursor to only emit the changes.
+#[cfg(feature = "std")]
+struct AnsiEncoder {
+    out: String,
+    /// The colors and modifiers of the terminal, `None` until they are reset.
+    pen: Option<(Color, Color, Modifier)>,
+    /// The cursor position of the terminal, `None` when unknown.
+    cursor: Option<(u16, u16)>,
+}
+
+#[cfg(feature = "std")]
+impl AnsiEncoder {
+    fn move_to(&mut self, x: u16, y: u16) {
+        if self.cursor != Some((x, y)) {
+            let _ = write!(self.out, "\x1b[{};{}H", y + 1, x + 1);
+            self.cursor = Some((x, y));
+        }
+    }
+
+    fn reset_pen(&mut self) {
+        self.out.push_str("\x1b[0m");
+        self.pen = Some((Color::Reset, Color::Reset, Modifier::empty()));
+    }
+
+    fn set_pen(&mut self, fg: Color, bg: Color, modifier: Modifier) {
+        if self.pen.is_none() {
+            self.reset_pen();
+        }
+        let Some((old_fg, old_bg, old_modifier)) = self.pen else {
+            return;
+        };
+        let mut params = String::new();
+        let mut param = |code: &dyn fmt::Display| {
+            if !params.is_empty() {
+                params.push(';');
+            }
+            let _ = write!(params, "{code}");
+        };
+        let mut added = modifier - old_modifier;
+        let removed = old_modifier - modifier;
+        // SGR 22 and 25 turn off two attributes each, the one that stays is turned on again.
+        for (attributes, off) in [
+            (Modifier::BOLD | Modifier::DIM, 22),
+            (Modifier::ITALIC, 23),
+            (Modifier::UNDERLINED, 24),
+            (Modifier::SLOW_BLINK | Modifier::RAPID_BLINK, 25),
+            (Modifier::REVERSED, 27),
+            (Modifier::HIDDEN, 28),
+            (Modifier::CROSSED_OUT, 29),
+        ] {
+            if removed.intersects(attributes) {
+                param(&off);
+                added |= modifier & attributes;
+            }
+        }
+        for (attribute, on) in [
+            (Modifier::BOLD, 1),
+            (Modifier::DIM, 2),
+            (Modifier::ITALIC, 3),
+            (Modifier::UNDERLINED, 4),
+            (Modifier::SLOW_BLINK, 5),
+            (Modifier::RAPID_BLINK, 6),
+            (Modifier::REVERSED, 7),
+            (Modifier::HIDDEN, 8),
+            (Modifier::CROSSED_OUT, 9),
+        ] {
+            if added.contains(attribute) {
+                param(&on);
+            }
+        }
+        if fg != old_fg {
+            param(&SgrColor(fg, false));
+        }
+        if bg != old_bg {
+            param(&SgrColor(bg, true));
+        }
+        if !params.is_empty() {
+            let _ = write!(self.out, "\x1b[{params}m");
+        }
+        self.pen = Some((fg, bg, modifier));
+    }
+
+    fn draw<'a>(&mut self, content: impl Iterator<Item = (u16, u16, &'a Cell)>, width: u16) {
+        for (x, y, cell) in content {
+            self.move_to(x, y);
+            self.set_pen(cell.fg, cell.bg, cell.modifier);
+            let symbol = cell.symbol();
+            // Control chars would be interpreted by the terminal.
+            let symbol = if symbol.chars().any(char::is_control) {
+                " "
+            } else {
+                symbol
+            };
+            self.out.push_str(symbol);
+            let end = x.saturating_add(symbol.width() as u16);
+            // After the last column, the terminal waits to wrap.
+            self.cursor = (end < width).then_some((end, y));
+        }
+    }
+}
+
+/// Writes the SGR parameters of a foreground or background color.
+#[cfg(feature = "std")]
+struct SgrColor(Color, bool);
+
+#[cfg(feature = "std")]
+impl fmt::Display for SgrColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let base = if self.1 { 40 } else { 30 };
+        let named = |index: u8| match index {
+            0..=7 => base + index,
+            _ => base + 60 + index - 8,
+        };
+        match self.0 {
+            Color::Reset => write!(f, "{}", base + 9),
+            Color::Black => write!(f, "{}", named(0)),
+            Color::Red => write!(f, "{}", named(1)),
+            Color::Green => write!(f, "{}", named(2)),
+            Color::Yellow => write!(f, "{}", named(3)),
+            Color::Blue => write!(f, "{}", named(4)),
+            Color::Magenta => write!(f, "{}", named(5)),
+            Color::Cyan => write!(f, "{}", named(6)),
+            Color::Gray => write!(f, "{}", named(7)),
+            Color::DarkGray => write!(f, "{}", named(8)),
+            Color::LightRed => write!(f, "{}", named(9)),
+            Color::LightGreen => write!(f, "{}", named(10)),
+            Color::LightYellow => write!(f, "{}", named(11)),
+            Color::LightBlue => write!(f, "{}", named(12)),
+            Color::LightMagenta => write!(f, "{}", named(13)),
+            Color::LightCyan => write!(f, "{}", named(14)),
+            Color::White => write!(f, "{}", named(15)),
+            Color::Indexed(index) => write!(f, "{};5;{index}", base + 8),
+            Color::Rgb(r, g, b) => write!(f, "{};2;{r};{g};{b}", base + 8),
+        }
+    }
+}
+
+/// Writes a string as a JSON string literal.
+#[cfg(feature = "std")]
+fn write_json_string(out: &mut String, string: &str) {
+    out.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// A backend that forwards every call to another backend and writes what a terminal would have
+/// received as an asciicast v2 recording, to play sessions of a graphical host with `asciinema`.
+///
+/// The escape sequences of a frame are written as a single output event when it is flushed, and
+/// size changes of the wrapped backend as resize events.
+///
+/// # Examples
+/// ```rust
+/// use ratatui::Terminal;
+/// use ratatui::widgets::Paragraph;
+/// use soft_ratatui::{Asciicast, AsciicastEvent, AsciicastWriter, BitmapFont, SoftBackend};
+///
+/// let backend = SoftBackend::new_with_bitmap_font(20, 2, BitmapFont::FIXED_8X13);
+/// let mut terminal = Terminal::new(AsciicastWriter::new(backend, Vec::new()).unwrap()).unwrap();
+/// terminal
+///     .draw(|frame| frame.render_widget(Paragraph::new("Hello"), frame.area()))
+///     .unwrap();
+///
+/// let cast = Asciicast::parse(std::str::from_utf8(terminal.backend().writer()).unwrap()).unwrap();
+/// assert_eq!((cast.width, cast.height), (20, 2));
+/// let AsciicastEvent::Output(output) = &cast.events[0].1 else {
+///     panic!()
+/// };
+/// assert!(output.contains("Hello"));
+/// ```
+#[cfg(feature = "std")]
+pub struct AsciicastWriter<B, W> {
+    inner: B,
+    writer: W,
+    encoder: AnsiEncoder,
+    start: std::time::Instant,
+    size: Size,
+}
+
+#[cfg(feature = "std")]
+impl<B: Backend, W: std::io::Write> AsciicastWriter<B, W> {
+    /// Wraps a backend, writing the header of the recording with its size.
+    pub fn new(inner: B, mut writer: W) -> Result<Self, RecordError<B::Error>> {
+        let size = inner.size().map_err(RecordError::Backend)?;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        writeln!(
+            writer,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {timestamp}}}",
+            size.width, size.height
+        )
+        .map_err(RecordError::Io)?;
+        Ok(Self {
+            inner,
+            writer,
+            encoder: AnsiEncoder {
+                out: String::new(),
+                pen: None,
+                cursor: None,
+            },
+            start: std::time::Instant::now(),
+            size,
+        })
+    }
+
+    /// The wrapped backend.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// The wrapped backend, mutably. Calls made through it are not recorded.
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    /// The writer of the recording.
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns the wrapped backend and the writer of the recording. Output that wasn't flushed
+    /// is lost.
+    pub fn into_parts(self) -> (B, W) {
+        (self.inner, self.writer)
+    }
+
+    fn write_event(&mut self, code: &str, data: &str) -> Result<(), RecordError<B::Error>> {
+        let mut line = String::new();
+        let _ = write!(
+            line,
+            "[{:.6}, \"{code}\", ",
+            self.start.elapsed().as_secs_f64()
+        );
+        write_json_string(&mut line, data);
+        line.push_str("]\n");
+        self.writer
+            .write_all(line.as_bytes())
+            .map_err(RecordError::Io)
+    }
+
+    /// Writes the pending output as an event.
+    fn write_output(&mut self) -> Result<(), RecordError<B::Error>> {
+        if self.encoder.out.is_empty() {
+            return Ok(());
+        }
+        let out = core::mem::take(&mut self.encoder.out);
+        self.write_event("o", &out)
+    }
+
+    /// Writes a resize event if the wrapped backend changed size since the last call.
+    fn check_size(&mut self) -> Result<(), RecordError<B::Error>> {
+        let size = self.inner.size().map_err(RecordError::Backend)?;
+        if size != self.size {
+            self.write_output()?;
+            self.size = size;
+            let mut data = String::new();
+            let _ = write!(data, "{}x{}", size.width, size.height);
+            self.write_event("r", &data)?;
+            // The terminal may move the cursor when resized.
+            self.encoder.cursor = None;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B: Backend, W: std::io::Write> Backend for AsciicastWriter<B, W>
+where
+    B::Error: 'static,
+{
+    type Error = RecordError<B::Error>;
+
+    fn draw<'a, I>(&mut self, content: I) -> Result<(), Self::Error>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        self.check_size()?;
+        let cells: Vec<(u16, u16, &Cell)> = content.collect();
+        self.encoder.draw(cells.iter().copied(), self.size.width);
+        self.inner
+            .draw(cells.into_iter())
+            .map_err(RecordError::Backend)
+    }
+
+    fn append_lines(&mut self, n: u16) -> Result<(), Self::Error> {
+        self.check_size()?;
+        for _ in 0..n {
+            self.encoder.out.push('\n');
+        }
+        self.encoder.cursor = None;
+        self.inner.append_lines(n).map_err(RecordError::Backend)
+    }
+
+    fn hide_cursor(&mut self) -> Result<(), Self::Error> {
+        self.encoder.out.push_str("\x1b[?25l");
+        self.inner.hide_cursor().map_err(RecordError::Backend)
+    }
+
+    fn show_cursor(&mut self) -> Result<(), Self::Error> {
+        self.encoder.out.push_str("\x1b[?25h");
+        self.inner.show_cursor().map_err(RecordError::Backend)
+    }
+
+    fn get_cursor_position(&mut self) -> Result<Position, Self::Error> {
+        self.inner
+            .get_cursor_position()
+            .map_err(RecordError::Backend)
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> Result<(), Self::Error> {
+        let position = position.into();
+        self.check_size()?;
+        self.encoder.move_to(position.x, position.y);
+        self.inner
+            .set_cursor_position(position)
+            .map_err(RecordError::Backend)
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        self.clear_region(ClearType::All)
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> Result<(), Self::Error> {
+        self.check_size()?;
+        // Erased cells take the background of the pen.
+        self.encoder.reset_pen();
+        self.encoder.out.push_str(match clear_type {
+            ClearType::All => "\x1b[2J",
+            ClearType::AfterCursor => "\x1b[J",
+            ClearType::BeforeCursor => "\x1b[1J",
+            ClearType::CurrentLine => "\x1b[2K",
+            ClearType::UntilNewLine => "\x1b[K",
+        });
+        self.inner
+            .clear_region(clear_type)
+            .map_err(RecordError::Backend)
+    }
+
+    fn size(&self) -> Result<Size, Self::Error> {
+        self.inner.size().map_err(RecordError::Backend)
+    }
+
+    fn window_size(&mut self) -> Result<WindowSize, Self::Error> {
+        self.inner.window_size().map_err(RecordError::Backend)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.check_size()?;
+        self.write_output()?;
+        self.writer.flush().map_err(RecordError::Io)?;
+        self.inner.flush().map_err(RecordError::Backend)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::BitmapFont;
+    use alloc::format;
+    use alloc::vec;
+    use ratatui::Terminal;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    const HEADER: &str = "{\"version\": 2, \"width\": 4, \"height\": 2}\n";
+
+    fn parse(events: &str) -> Result<Asciicast, AsciicastError> {
+        Asciicast::parse(&[HEADER, events].concat())
+    }
+
+    #[test]
+    fn parses_the_header() {
+        let cast = Asciicast::parse(
+            "{\"version\": 2, \"width\": 80, \"height\": 24, \"timestamp\": 1, \
+             \"env\": {\"TERM\": \"xterm\"}, \"title\": null}",
+        )
+        .unwrap();
+        assert_eq!((cast.width, cast.height, cast.events.len()), (80, 24, 0));
+        let error = |text| Asciicast::parse(text).unwrap_err();
+        assert_eq!(error(""), AsciicastError::InvalidHeader);
+        assert_eq!(error("{\"version\": 2"), AsciicastError::InvalidHeader);
+        assert_eq!(
+            error("[1, \"o\", \"\"]"),
+            AsciicastError::UnsupportedVersion
+        );
+        assert_eq!(
+            error("{\"version\": 1, \"width\": 80, \"height\": 24}"),
+            AsciicastError::UnsupportedVersion
+        );
+        assert_eq!(
+            error("{\"version\": 2, \"width\": 0, \"height\": 24}"),
+            AsciicastError::InvalidHeader
+        );
+        assert_eq!(
+            error("{\"version\": 2, \"width\": 80, \"height\": 70000}"),
+            AsciicastError::InvalidHeader
+        );
+    }
+
+    #[test]
+    fn rejects_deep_nesting() {
+        let nested = |depth| {
+            let value = ["[".repeat(depth), "]".repeat(depth)].concat();
+            format!("{{\"version\": 2, \"width\": 4, \"height\": 2, \"nested\": {value}}}")
+        };
+        // The header object takes one level.
+        assert!(Asciicast::parse(&nested(MAX_JSON_DEPTH - 1)).is_ok());
+        assert_eq!(
+            Asciicast::parse(&nested(MAX_JSON_DEPTH)).unwrap_err(),
+            AsciicastError::InvalidHeader
+        );
+        // Deep enough to overflow the stack without the limit.
+        assert_eq!(
+            Asciicast::parse(&"[".repeat(200_000)).unwrap_err(),
+            AsciicastError::InvalidHeader
+        );
+        assert_eq!(
+            parse(&["[0, \"o\", ", &"{".repeat(200_000)].concat()).unwrap_err(),
+            AsciicastError::InvalidEvent(2)
+        );
+    }
+
+    #[test]
+    fn parses_events() {
+        let cast = parse(
+            "[0.25, \"o\", \"a\\\"\\\\\\n\\u001b\\ud83d\\ude00\"]\n\
+             \n\
+             [ 1 , \"i\" , \"q\" ]\n\
+             [1.5e0, \"r\", \"100x30\"]\n\
+             [2, \"m\", \"chapter\"]\n\
+             [3, \"x\", \"unknown codes are skipped\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            cast.events,
+            vec![
+                (0.25, AsciicastEvent::Output("a\"\\\n\x1b😀".into())),
+                (1.0, AsciicastEvent::Input("q".into())),
+                (1.5, AsciicastEvent::Resize(100, 30)),
+                (2.0, AsciicastEvent::Marker("chapter".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_events() {
+        for (events, line) in [
+            ("[1, \"o\"]", 2),
+            ("[1, \"o\", \"a\", 2]", 2),
+            ("[\"1\", \"o\", \"a\"]", 2),
+            ("[1, \"o\", 2]", 2),
+            ("[1, \"o\", \"a\"", 2),
+            ("[1, \"o\", \"a\"] x", 2),
+            ("[1, \"r\", \"80\"]", 2),
+            ("[1, \"o\", \"a\"]\n\n[1, \"r\", \"80x-1\"]", 4),
+        ] {
+            assert_eq!(parse(events), Err(AsciicastError::InvalidEvent(line)));
+        }
+    }
+
+    #[test]
+    fn player_applies_events_up_to_the_time() {
+        let cast = parse(
+            "[1, \"o\", \"ab\"]\n\
+             [2, \"i\", \"x\"]\n\
+             [3, \"r\", \"6x3\"]\n\
+             [4, \"o\", \"\\u001b[?25l\"]\n",
+        )
+        .unwrap();
+        let mut player = AsciicastPlayer::new(&cast);
+        assert!(!player.advance(0.5));
+        assert_eq!(player.next_time(), Some(1.0));
+        assert!(player.advance(2.0));
+        assert_eq!(player.screen().buffer()[(1, 0)].symbol(), "b");
+        assert_eq!(player.next_time(), Some(3.0));
+        let mut backend = SoftBackend::new_with_bitmap_font(1, 1, BitmapFont::FIXED_8X13);
+        player.draw(&mut backend);
+        assert_eq!(backend.buffer.area, Rect::new(0, 0, 4, 2));
+        assert_eq!(backend.get_cursor_position().unwrap(), Position::new(2, 0));
+
+        assert!(player.advance(f64::INFINITY));
+        assert_eq!(player.next_time(), None);
+        player.draw(&mut backend);
+        assert_eq!(backend.buffer.area, Rect::new(0, 0, 6, 3));
+        assert_eq!(backend.buffer[(0, 0)].symbol(), "a");
+        assert!(!player.screen().cursor_visible());
+    }
+
+    #[test]
+    fn encoder_output_plays_back_as_the_same_buffer() {
+        let styles = [
+            Style::new().fg(Color::Red).bg(Color::Indexed(236)),
+            Style::new()
+                .fg(Color::Rgb(10, 20, 30))
+                .add_modifier(Modifier::BOLD | Modifier::DIM | Modifier::UNDERLINED),
+            // Only bold is removed, dim stays on after SGR 22.
+            Style::new()
+                .fg(Color::LightCyan)
+                .add_modifier(Modifier::DIM),
+            Style::new().add_modifier(Modifier::ITALIC | Modifier::REVERSED),
+            Style::new()
+                .bg(Color::White)
+                .add_modifier(Modifier::CROSSED_OUT),
+        ];
+        let backend = SoftBackend::new_with_bitmap_font(12, 3, BitmapFont::FIXED_8X13);
+        let mut terminal =
+            Terminal::new(AsciicastWriter::new(backend, Vec::new()).unwrap()).unwrap();
+        let mut expected = Buffer::empty(Rect::new(0, 0, 12, 3));
+        for (frame, text) in ["first", "second frame", "wide 日本"].iter().enumerate() {
+            terminal
+                .draw(|f| {
+                    let buffer = f.buffer_mut();
+                    for (i, style) in styles.iter().enumerate() {
+                        buffer.set_string(i as u16 * 2, frame as u16 % 3, text, *style);
+                    }
+                    buffer.set_string(0, 2, "\x07 bell", Style::new());
+                    expected.clone_from(buffer);
+                })
+                .unwrap();
+        }
+        // The control char is written as a space.
+        expected[(0, 2)].set_symbol(" ");
+
+        let cast =
+            Asciicast::parse(core::str::from_utf8(terminal.backend().writer()).unwrap()).unwrap();
+        assert_eq!(cast.events.len(), 3);
+        let mut player = AsciicastPlayer::new(&cast);
+        player.advance(f64::INFINITY);
+        assert_eq!(player.screen().buffer(), &expected);
+    }
+
+    #[test]
+    fn writer_records_resizes_and_clears() {
+        let backend = SoftBackend::new_with_bitmap_font(4, 2, BitmapFont::FIXED_8X13);
+        let mut writer = AsciicastWriter::new(backend, Vec::new()).unwrap();
+        writer.inner_mut().resize(5, 3);
+        writer.clear().unwrap();
+        writer.flush().unwrap();
+        let cast = Asciicast::parse(core::str::from_utf8(writer.writer()).unwrap()).unwrap();
+        assert_eq!((cast.width, cast.height), (4, 2));
+        assert_eq!(cast.events[0].1, AsciicastEvent::Resize(5, 3));
+        assert_eq!(
+            cast.events[1].1,
+            AsciicastEvent::Output("\x1b[0m\x1b[2J".into())
+        );
+    }
+}
@@ -0,0 +1,505 @@
+//! A Bevy plugin showing terminals as image assets, e.g. on sprites or the materials of meshes,
+//! and forwarding keyboard and mouse input to them.
+
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+
+use bevy::app::{App, Plugin, PostUpdate, PreUpdate};
+use bevy::asset::{Assets, Handle};
+use bevy::camera::Camera;
+use bevy::ecs::prelude::*;
+use bevy::image::{Image, ImageSampler, ToExtents};
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::mouse::{MouseButtonInput, MouseWheel};
+use bevy::input::{ButtonInput, ButtonState, InputSystems};
+use bevy::math::{UVec2, Vec2};
+use bevy::sprite::{Anchor, Sprite};
+use bevy::transform::components::GlobalTransform;
+use bevy::window::{CursorMoved, PrimaryWindow, Window};
+use ratatui::Terminal;
+
+use crate::input::{
+    InputEvent, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use crate::soft_backend::SoftBackend;
+
+/// Keeps the image of every [`SoftTerminal`] in sync with its pixmap, resizes terminals as
+/// asked by their [`TerminalFit`] and sends their input as [`TerminalInput`] messages.
+///
+/// Terminals are drawn by the systems of the app in `Update`. The image of a terminal is
+/// created in `PostUpdate` after it's spawned, then only the regions redrawn since the last
+/// frame are copied into it, and frames that redraw nothing leave the asset untouched so that
+/// it isn't uploaded again.
+///
+/// # Examples
+/// ```rust,no_run
+/// use bevy::prelude::*;
+/// use ratatui::widgets::Paragraph;
+/// use soft_ratatui::{
+///     BitmapFont, SoftBackend, SoftRatatuiPlugin, SoftTerminal, TerminalFit, TerminalFocus,
+/// };
+///
+/// App::new()
+///     .add_plugins((DefaultPlugins, SoftRatatuiPlugin))
+///     .add_systems(Startup, |mut commands: Commands| {
+///         commands.spawn(Camera2d);
+///         let backend = SoftBackend::new_with_bitmap_font(40, 10, BitmapFont::FIXED_8X13);
+///         commands.spawn((
+///             SoftTerminal::new(backend),
+///             Sprite::default(),
+///             TerminalFit::Window,
+///             TerminalFocus,
+///         ));
+///     })
+///     .add_systems(Update, |mut terminals: Query<&mut SoftTerminal>| {
+///         for mut terminal in &mut terminals {
+///             terminal
+///                 .draw(|frame| frame.render_widget(Paragraph::new("Hello"), frame.area()))
+///                 .unwrap();
+///         }
+///     })
+///     .run();
+/// ```
+pub struct SoftRatatuiPlugin;
+
+impl Plugin for SoftRatatuiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<TerminalInput>()
+            .add_systems(
+                PreUpdate,
+                (fit_terminals, forward_input).chain().after(InputSystems),
+            )
+            .add_systems(PostUpdate, (add_images, sync_images).chain());
+    }
+}
+
+/// A terminal drawn into by the app. Its pixmap is shown by the image of its [`TerminalImage`].
+#[derive(Component)]
+pub struct SoftTerminal(pub Terminal<SoftBackend>);
+
+impl SoftTerminal {
+    /// Creates a terminal drawing into a backend.
+    pub fn new(backend: SoftBackend) -> Self {
+        Self(Terminal::new(backend).unwrap_or_else(|error| match error {}))
+    }
+}
+
+impl Deref for SoftTerminal {
+    type Target = Terminal<SoftBackend>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for SoftTerminal {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// The image the pixmap of a [`SoftTerminal`] is copied to, in the `Rgba8UnormSrgb` format with
+/// nearest sampling.
+///
+/// Inserted with a new image for terminals spawned without one, which then also becomes the
+/// image of their `Sprite`. Insert it at spawn to use an image of the app instead, e.g. the
+/// texture of a material.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct TerminalImage(pub Handle<Image>);
+
+/// How the grid of a [`SoftTerminal`] follows the space it's shown in.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TerminalFit {
+    /// Keeps its size, unless resized by the app.
+    #[default]
+    Fixed,
+    /// Fills the primary window with as many cells as fit, at a pixel of the pixmap per physical
+    /// pixel. The `custom_size` of the terminal's `Sprite` is set to show it at that scale, which
+    /// is right for a sprite at the origin of a `Camera2d` with the default projection.
+    Window,
+    /// Fills the `custom_size` of the terminal's `Sprite` with as many cells as fit, taking a
+    /// unit of the size as a pixel.
+    Sprite,
+}
+
+/// Marks the terminals receiving keyboard input.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct TerminalFocus;
+
+/// A key press for a focused terminal, or a mouse event over a terminal.
+///
+/// Mouse events are sent for terminals with [`TerminalFit::Window`], and for terminals shown by
+/// a `Sprite` seen by a 2D camera.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalInput {
+    pub terminal: Entity,
+    pub event: InputEvent,
+}
+
+fn fit_terminals(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut terminals: Query<(&mut SoftTerminal, &TerminalFit, Option<&mut Sprite>)>,
+) {
+    let window = windows.single().ok();
+    for (mut terminal, fit, sprite) in &mut terminals {
+        let backend = terminal.backend_mut();
+        match (fit, window, sprite) {
+            (TerminalFit::Window, Some(window), sprite) => {
+                let size = window.physical_size();
+                backend.fit_to_pixels(size.x as usize, size.y as usize);
+                if let Some(mut sprite) = sprite {
+                    let size = pixmap_size(backend).as_vec2() / window.scale_factor();
+                    if sprite.custom_size != Some(size) {
+                        sprite.custom_size = Some(size);
+                    }
+                }
+            }
+            (TerminalFit::Sprite, _, Some(sprite)) => {
+                if let Some(size) = sprite.custom_size {
+                    backend.fit_to_pixels(size.x.max(0.0) as usize, size.y.max(0.0) as usize);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn add_images(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut terminals: Query<(Entity, &SoftTerminal, Option<&mut Sprite>), Without<TerminalImage>>,
+) {
+    for (entity, terminal, sprite) in &mut terminals {
+        let mut image = Image {
+            sampler: ImageSampler::nearest(),
+            ..Image::default()
+        };
+        resize_image(&mut image, terminal.backend());
+        let handle = images.add(image);
+        if let Some(mut sprite) = sprite {
+            sprite.image = handle.clone();
+        }
+        commands.entity(entity).insert(TerminalImage(handle));
+    }
+}
+
+fn sync_images(
+    mut images: ResMut<Assets<Image>>,
+    mut terminals: Query<(&mut SoftTerminal, &TerminalImage)>,
+) {
+    for (mut terminal, image) in &mut terminals {
+        let backend = terminal.backend_mut();
+        // Getting the image mutably uploads it again, even if nothing changed.
+        if backend.dirty_regions().is_empty() {
+            continue;
+        }
+        let Some(image) = images.get_mut(&image.0) else {
+            continue;
+        };
+        let same_size = image.size() == pixmap_size(backend);
+        match image.data.as_mut() {
+            Some(data) if same_size => {
+                backend.update_rgba(data);
+            }
+            _ => {
+                resize_image(image, backend);
+                backend.clear_dirty();
+            }
+        }
+    }
+}
+
+/// Sizes an image like the pixmap of a backend and copies the pixmap into it.
+fn resize_image(image: &mut Image, backend: &SoftBackend) {
+    image.resize(pixmap_size(backend).to_extents());
+    image.data = Some(backend.get_pixmap_data_as_rgba());
+}
+
+fn pixmap_size(backend: &SoftBackend) -> UVec2 {
+    UVec2::new(
+        backend.get_pixmap_width() as u32,
+        backend.get_pixmap_height() as u32,
+    )
+}
+
+/// Where a terminal is shown, to find the cell under the cursor.
+#[derive(Clone, Copy)]
+enum Placement {
+    /// Centered in the window, a pixel of the pixmap per physical pixel.
+    Window,
+    /// On a sprite, with its transform, its size and its anchor.
+    Sprite(GlobalTransform, Vec2, Vec2),
+}
+
+/// The state of the mouse, shared by the conversion of all of its events.
+struct Pointer<'a> {
+    window: Option<&'a Window>,
+    camera: Option<(&'a Camera, &'a GlobalTransform)>,
+    buttons: &'a ButtonInput<bevy::input::mouse::MouseButton>,
+    modifiers: KeyModifiers,
+}
+
+impl Pointer<'_> {
+    /// Returns the cell of a terminal under the cursor of the window.
+    fn cell(&self, backend: &SoftBackend, placement: Placement) -> Option<(u16, u16)> {
+        let window = self.window?;
+        let hit = match placement {
+            Placement::Window => {
+                let cursor = window.physical_cursor_position()?;
+                let offset =
+                    (window.physical_size().as_vec2() - pixmap_size(backend).as_vec2()) / 2.0;
+                let pixel = cursor - offset;
+                backend.cell_at_pixel(pixel.x, pixel.y)?
+            }
+            Placement::Sprite(transform, size, anchor) => {
+                let (camera, camera_transform) = self.camera?;
+                let world = camera
+                    .viewport_to_world_2d(camera_transform, window.cursor_position()?)
+                    .ok()?;
+                let local = transform
+                    .affine()
+                    .inverse()
+                    .transform_point3(world.extend(0.0))
+                    .truncate()
+                    / size;
+                backend.cell_at_uv(local.x + 0.5 + anchor.x, 0.5 - anchor.y - local.y)?
+            }
+        };
+        Some((hit.position.x, hit.position.y))
+    }
+
+    fn event(&self, kind: MouseEventKind, (column, row): (u16, u16)) -> InputEvent {
+        InputEvent::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: self.modifiers,
+        })
+    }
+
+    /// The kind of a cursor move, a drag of the first held button.
+    fn moved(&self) -> MouseEventKind {
+        [MouseButton::Left, MouseButton::Middle, MouseButton::Right]
+            .into_iter()
+            .find(|button| self.buttons.pressed(bevy_button(*button)))
+            .map_or(MouseEventKind::Moved, MouseEventKind::Drag)
+    }
+}
+
+fn bevy_button(button: MouseButton) -> bevy::input::mouse::MouseButton {
+    match button {
+        MouseButton::Left => bevy::input::mouse::MouseButton::Left,
+        MouseButton::Right => bevy::input::mouse::MouseButton::Right,
+        MouseButton::Middle => bevy::input::mouse::MouseButton::Middle,
+    }
+}
+
+/// Converts a mouse button, `None` for the buttons a terminal doesn't have.
+fn mouse_button(button: bevy::input::mouse::MouseButton) -> Option<MouseButton> {
+    match button {
+        bevy::input::mouse::MouseButton::Left => Some(MouseButton::Left),
+        bevy::input::mouse::MouseButton::Right => Some(MouseButton::Right),
+        bevy::input::mouse::MouseButton::Middle => Some(MouseButton::Middle),
+        _ => None,
+    }
+}
+
+/// Converts a key press, `None` for keys with no terminal equivalent, such as modifier keys on
+/// their own, and for key releases.
+fn key_event(input: &KeyboardInput, modifiers: KeyModifiers) -> Option<KeyEvent> {
+    if input.state != ButtonState::Pressed {
+        return None;
+    }
+    let code = match &input.logical_key {
+        Key::Character(text) => {
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return None,
+            }
+        }
+        Key::Space => KeyCode::Char(' '),
+        Key::Enter => KeyCode::Enter,
+        Key::Backspace => KeyCode::Backspace,
+        Key::Tab if modifiers.shift => KeyCode::BackTab,
+        Key::Tab => KeyCode::Tab,
+        Key::Escape => KeyCode::Esc,
+        Key::ArrowLeft => KeyCode::Left,
+        Key::ArrowRight => KeyCode::Right,
+        Key::ArrowUp => KeyCode::Up,
+        Key::ArrowDown => KeyCode::Down,
+        Key::Home => KeyCode::Home,
+        Key::End => KeyCode::End,
+        Key::PageUp => KeyCode::PageUp,
+        Key::PageDown => KeyCode::PageDown,
+        Key::Insert => KeyCode::Insert,
+        Key::Delete => KeyCode::Delete,
+        Key::F1 => KeyCode::F(1),
+        Key::F2 => KeyCode::F(2),
+        Key::F3 => KeyCode::F(3),
+        Key::F4 => KeyCode::F(4),
+        Key::F5 => KeyCode::F(5),
+        Key::F6 => KeyCode::F(6),
+        Key::F7 => KeyCode::F(7),
+        Key::F8 => KeyCode::F(8),
+        Key::F9 => KeyCode::F(9),
+        Key::F10 => KeyCode::F(10),
+        Key::F11 => KeyCode::F(11),
+        Key::F12 => KeyCode::F(12),
+        _ => return None,
+    };
+    Some(KeyEvent::new(code, modifiers))
+}
+
+/// A terminal with what's needed to send it input.
+type TerminalPlacement = (
+    Entity,
+    &'static SoftTerminal,
+    Option<&'static TerminalFit>,
+    Option<&'static TerminalFocus>,
+    Option<(
+        &'static Sprite,
+        &'static GlobalTransform,
+        Option<&'static Anchor>,
+    )>,
+);
+
+#[allow(clippy::too_many_arguments)]
+fn forward_input(
+    mut keyboard: MessageReader<KeyboardInput>,
+    mut buttons: MessageReader<MouseButtonInput>,
+    mut cursor: MessageReader<CursorMoved>,
+    mut wheel: MessageReader<MouseWheel>,
+    keys: Res<ButtonInput<bevy::input::keyboard::KeyCode>>,
+    mouse: Res<ButtonInput<bevy::input::mouse::MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    terminals: Query<TerminalPlacement>,
+    mut input: MessageWriter<TerminalInput>,
+) {
+    use bevy::input::keyboard::KeyCode as Bevy;
+    let modifiers = KeyModifiers {
+        shift: keys.any_pressed([Bevy::ShiftLeft, Bevy::ShiftRight]),
+        ctrl: keys.any_pressed([Bevy::ControlLeft, Bevy::ControlRight]),
+        alt: keys.any_pressed([Bevy::AltLeft, Bevy::AltRight]),
+    };
+    for event in keyboard.read() {
+        let Some(key) = key_event(event, modifiers) else {
+            continue;
+        };
+        for (terminal, ..) in terminals.iter().filter(|terminal| terminal.3.is_some()) {
+            input.write(TerminalInput {
+                terminal,
+                event: InputEvent::Key(key),
+            });
+        }
+    }
+
+    let mut kinds: Vec<MouseEventKind> = Vec::new();
+    if cursor.read().count() > 0 {
+        kinds.push(MouseEventKind::Moved);
+    }
+    for event in buttons.read() {
+        if let Some(button) = mouse_button(event.button) {
+            kinds.push(match event.state {
+                ButtonState::Pressed => MouseEventKind::Down(button),
+                ButtonState::Released => MouseEventKind::Up(button),
+            });
+        }
+    }
+    for event in wheel.read() {
+        if event.y != 0.0 {
+            kinds.push(if event.y > 0.0 {
+                MouseEventKind::ScrollUp
+            } else {
+                MouseEventKind::ScrollDown
+            });
+        }
+        if event.x != 0.0 {
+            kinds.push(if event.x > 0.0 {
+                MouseEventKind::ScrollRight
+            } else {
+                MouseEventKind::ScrollLeft
+            });
+        }
+    }
+    if kinds.is_empty() {
+        return;
+    }
+    let pointer = Pointer {
+        window: windows.single().ok(),
+        camera: cameras.iter().find(|(camera, _)| camera.is_active),
+        buttons: &mouse,
+        modifiers,
+    };
+    for (terminal, soft_terminal, fit, _, sprite) in &terminals {
+        let backend = soft_terminal.backend();
+        let placement = match (fit, sprite) {
+            (Some(TerminalFit::Window), _) => Placement::Window,
+            (_, Some((sprite, transform, anchor))) => Placement::Sprite(
+                *transform,
+                sprite
+                    .custom_size
+                    .unwrap_or_else(|| pixmap_size(backend).as_vec2()),
+                anchor.map_or(Vec2::ZERO, Anchor::as_vec),
+            ),
+            _ => continue,
+        };
+        let Some(cell) = pointer.cell(backend, placement) else {
+            continue;
+        };
+        for kind in &kinds {
+            let kind = match kind {
+                MouseEventKind::Moved => pointer.moved(),
+                kind => *kind,
+            };
+            input.write(TerminalInput {
+                terminal,
+                event: pointer.event(kind, cell),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(logical_key: Key, state: ButtonState) -> KeyboardInput {
+        KeyboardInput {
+            key_code: bevy::input::keyboard::KeyCode::Unidentified(
+                bevy::input::keyboard::NativeKeyCode::Unidentified,
+            ),
+            logical_key,
+            state,
+            text: None,
+            repeat: false,
+            window: Entity::PLACEHOLDER,
+        }
+    }
+
+    #[test]
+    fn converts_keys() {
+        let code = |key| key_event(&press(key, ButtonState::Pressed), KeyModifiers::NONE);
+        assert_eq!(
+            code(Key::Character("é".into())).unwrap().code,
+            KeyCode::Char('é')
+        );
+        assert_eq!(code(Key::Space).unwrap().code, KeyCode::Char(' '));
+        assert_eq!(code(Key::ArrowDown).unwrap().code, KeyCode::Down);
+        assert_eq!(code(Key::F12).unwrap().code, KeyCode::F(12));
+        assert_eq!(code(Key::Character("ab".into())), None);
+        assert_eq!(code(Key::Shift), None);
+        let ctrl = KeyModifiers {
+            ctrl: true,
+            ..KeyModifiers::NONE
+        };
+        let event = key_event(
+            &press(Key::Character("c".into()), ButtonState::Pressed),
+            ctrl,
+        );
+        assert_eq!(event, Some(KeyEvent::new(KeyCode::Char('c'), ctrl)));
+        let release = press(Key::Enter, ButtonState::Released);
+        assert_eq!(key_event(&release, KeyModifiers::NONE), None);
+    }
+}
@@ -0,0 +1,441 @@
+//! Renders ANSI colored text to PNG and asciinema v2 recordings to animated GIF or APNG, e.g.
+//! to make screenshots and demos for a README without a terminal.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::process::ExitCode;
+
+use ratatui::backend::Backend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use soft_ratatui::{Asciicast, AsciicastEvent, BitmapFont, PixelRect, SoftBackend, VtScreen};
+
+const USAGE: &str = "\
+Usage: soft_ratatui [OPTIONS] -o OUTPUT [INPUT]
+
+Renders ANSI colored text to an image, or an asciinema v2 recording (.cast) to an animation.
+Reads stdin when INPUT is missing or `-`.
+
+Options:
+  -o, --output FILE     Output file: .png, .apng or .gif. A recording written to .png is an APNG
+      --cols N          Number of columns [default: 80, or the width of the recording]
+      --rows N          Number of rows [default: the rows of text, or the height of the recording]
+      --font FILE       TTF/OTF font file [default: the built-in 8x13 bitmap font]
+      --font-size N     Font size in pixels when a font file is given [default: 16]
+      --theme NAME      Color theme: xterm, solarized-dark, dracula or backend, the
+                        colors of SoftBackend [default: xterm]
+      --fps N           Maximum frames per second of animations [default: 30]
+      --idle-limit SECS Longest pause between frames of animations [default: 2]
+  -h, --help            Prints this help
+";
+
+struct Options {
+    input: Option<String>,
+    output: String,
+    cols: Option<u16>,
+    rows: Option<u16>,
+    font: Option<String>,
+    font_size: i32,
+    theme: Theme,
+    fps: f64,
+    idle_limit: f64,
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{flag} needs a value"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {flag}: {value}"))
+}
+
+fn parse_args() -> Result<Option<Options>, String> {
+    let mut args = std::env::args().skip(1);
+    let mut input = None;
+    let mut output = None;
+    let mut options = Options {
+        input: None,
+        output: String::new(),
+        cols: None,
+        rows: None,
+        font: None,
+        font_size: 16,
+        theme: Theme {
+            colors: Some(Theme::XTERM),
+        },
+        fps: 30.0,
+        idle_limit: 2.0,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-o" | "--output" => output = Some(args.next().ok_or("--output needs a value")?),
+            "--cols" => options.cols = Some(parse_number(&arg, args.next())?),
+            "--rows" => options.rows = Some(parse_number(&arg, args.next())?),
+            "--font" => options.font = Some(args.next().ok_or("--font needs a value")?),
+            "--font-size" => options.font_size = parse_number(&arg, args.next())?,
+            "--theme" => {
+                let name = args.next().ok_or("--theme needs a value")?;
+                options.theme = Theme::by_name(&name).ok_or(format!("unknown theme: {name}"))?;
+            }
+            "--fps" => options.fps = parse_number(&arg, args.next())?,
+            "--idle-limit" => options.idle_limit = parse_number(&arg, args.next())?,
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option: {arg}"));
+            }
+            _ if input.is_none() => input = Some(arg),
+            _ => return Err(format!("unexpected argument: {arg}")),
+        }
+    }
+    if options.cols == Some(0) || options.rows == Some(0) {
+        return Err("--cols and --rows must be at least 1".into());
+    }
+    if options.fps <= 0.0 || options.idle_limit <= 0.0 {
+        return Err("--fps and --idle-limit must be positive".into());
+    }
+    options.input = input.filter(|input| input != "-");
+    options.output = output.ok_or("missing --output")?;
+    Ok(Some(options))
+}
+
+/// The colors that replace the reset colors and the 16 named colors.
+#[derive(Clone, Copy)]
+struct Theme {
+    /// `None` keeps the colors of the backend.
+    colors: Option<ThemeColors>,
+}
+
+#[derive(Clone, Copy)]
+struct ThemeColors {
+    foreground: u32,
+    background: u32,
+    palette: [u32; 16],
+}
+
+impl Theme {
+    const XTERM: ThemeColors = ThemeColors {
+        foreground: 0xe5e5e5,
+        background: 0x000000,
+        palette: [
+            0x000000, 0xcd0000, 0x00cd00, 0xcdcd00, 0x0000ee, 0xcd00cd, 0x00cdcd, 0xe5e5e5,
+            0x7f7f7f, 0xff0000, 0x00ff00, 0xffff00, 0x5c5cff, 0xff00ff, 0x00ffff, 0xffffff,
+        ],
+    };
+    const SOLARIZED_DARK: ThemeColors = ThemeColors {
+        foreground: 0x839496,
+        background: 0x002b36,
+        palette: [
+            0x073642, 0xdc322f, 0x859900, 0xb58900, 0x268bd2, 0xd33682, 0x2aa198, 0xeee8d5,
+            0x002b36, 0xcb4b16, 0x586e75, 0x657b83, 0x839496, 0x6c71c4, 0x93a1a1, 0xfdf6e3,
+        ],
+    };
+    const DRACULA: ThemeColors = ThemeColors {
+        foreground: 0xf8f8f2,
+        background: 0x282a36,
+        palette: [
+            0x21222c, 0xff5555, 0x50fa7b, 0xf1fa8c, 0xbd93f9, 0xff79c6, 0x8be9fd, 0xf8f8f2,
+            0x6272a4, 0xff6e6e, 0x69ff94, 0xffffa5, 0xd6acff, 0xff92df, 0xa4ffff, 0xffffff,
+        ],
+    };
+
+    fn by_name(name: &str) -> Option<Self> {
+        let colors = match name {
+            "backend" => None,
+            "xterm" => Some(Self::XTERM),
+            "solarized-dark" => Some(Self::SOLARIZED_DARK),
+            "dracula" => Some(Self::DRACULA),
+            _ => return None,
+        };
+        Some(Self { colors })
+    }
+
+    /// Returns a copy of the buffer with the themed colors replaced by rgb colors.
+    fn apply(&self, buffer: &Buffer) -> Buffer {
+        let mut buffer = buffer.clone();
+        let Some(colors) = self.colors else {
+            return buffer;
+        };
+        let rgb = |color: u32| Color::from_u32(color);
+        let map = |color: Color, reset: u32| match color {
+            Color::Reset => rgb(reset),
+            Color::Indexed(index @ 0..16) => rgb(colors.palette[index as usize]),
+            Color::Black => rgb(colors.palette[0]),
+            Color::Red => rgb(colors.palette[1]),
+            Color::Green => rgb(colors.palette[2]),
+            Color::Yellow => rgb(colors.palette[3]),
+            Color::Blue => rgb(colors.palette[4]),
+            Color::Magenta => rgb(colors.palette[5]),
+            Color::Cyan => rgb(colors.palette[6]),
+            Color::Gray => rgb(colors.palette[7]),
+            Color::DarkGray => rgb(colors.palette[8]),
+            Color::LightRed => rgb(colors.palette[9]),
+            Color::LightGreen => rgb(colors.palette[10]),
+            Color::LightYellow => rgb(colors.palette[11]),
+            Color::LightBlue => rgb(colors.palette[12]),
+            Color::LightMagenta => rgb(colors.palette[13]),
+            Color::LightCyan => rgb(colors.palette[14]),
+            Color::White => rgb(colors.palette[15]),
+            color => color,
+        };
+        for cell in &mut buffer.content {
+            cell.fg = map(cell.fg, colors.foreground);
+            cell.bg = map(cell.bg, colors.background);
+        }
+        buffer
+    }
+}
+
+/// A frame of an animation: the part of the image that changed and how long it is shown.
+struct Frame {
+    region: PixelRect,
+    pixels: Vec<u8>,
+    delay: f64,
+}
+
+/// Renders themed screens into a backend and keeps the changed regions as frames.
+struct Renderer {
+    backend: SoftBackend,
+    theme: Theme,
+    previous: Option<Buffer>,
+    frames: Vec<Frame>,
+}
+
+impl Renderer {
+    fn new(options: &Options, cols: u16, rows: u16) -> Result<Self, String> {
+        let backend = match &options.font {
+            Some(path) => {
+                let data = std::fs::read(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+                SoftBackend::new_with_font(cols, rows, options.font_size, &data)
+            }
+            None => SoftBackend::new_with_bitmap_font(cols, rows, BitmapFont::FIXED_8X13),
+        };
+        Ok(Self {
+            backend,
+            theme: options.theme,
+            previous: None,
+            frames: Vec::new(),
+        })
+    }
+
+    /// Draws the screen, adding a frame shown for `delay` seconds, or extending the last frame
+    /// if nothing changed.
+    fn render(&mut self, buffer: &Buffer, delay: f64) {
+        let buffer = self.theme.apply(buffer);
+        let _ = match &self.previous {
+            Some(previous) => self.backend.draw(previous.diff(&buffer).into_iter()),
+            None => self
+                .backend
+                .draw(buffer.content.iter().enumerate().map(|(i, cell)| {
+                    let (x, y) = buffer.pos_of(i);
+                    (x, y, cell)
+                })),
+        };
+        self.previous = Some(buffer);
+
+        let dirty = self.backend.dirty_regions();
+        self.backend.clear_dirty();
+        let Some(region) = dirty.into_iter().reduce(|a, b| {
+            let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+            PixelRect {
+                x,
+                y,
+                width: (a.x + a.width).max(b.x + b.width) - x,
+                height: (a.y + a.height).max(b.y + b.height) - y,
+            }
+        }) else {
+            if let Some(last) = self.frames.last_mut() {
+                last.delay += delay;
+            }
+            return;
+        };
+        let pixmap = &self.backend.rgb_pixmap;
+        let mut pixels = Vec::with_capacity(region.width * region.height * 3);
+        for y in region.y..region.y + region.height {
+            let start = (y * pixmap.width() + region.x) * 3;
+            pixels.extend_from_slice(&pixmap.data()[start..start + region.width * 3]);
+        }
+        self.frames.push(Frame {
+            region,
+            pixels,
+            delay,
+        });
+    }
+}
+
+/// Returns the number of rows used by the text, ignoring trailing blank rows.
+fn used_rows(screen: &VtScreen) -> u16 {
+    let buffer = screen.buffer();
+    let blank = |y| {
+        (0..buffer.area.width).all(|x| {
+            let cell = &buffer[(x, y)];
+            cell.symbol() == " " && cell.bg == Color::Reset
+        })
+    };
+    (0..buffer.area.height)
+        .rev()
+        .find(|y| !blank(*y))
+        .map_or(1, |y| y + 1)
+}
+
+fn render_text(options: &Options, input: &[u8]) -> Result<Renderer, String> {
+    let cols = options.cols.unwrap_or(80);
+    // Room for the row a final newline moves the cursor to, cropped below.
+    let lines = input.split(|byte| *byte == b'\n').count();
+    let rows = options
+        .rows
+        .unwrap_or(lines.clamp(1, u16::MAX as usize) as u16);
+    let mut screen = VtScreen::new(cols, rows);
+    screen.set_newline_mode(true);
+    screen.feed(input);
+    let mut buffer = screen.buffer().clone();
+    if options.rows.is_none() {
+        // Cropped rather than resized, which would scroll the rows above the cursor away.
+        buffer.resize(Rect::new(0, 0, cols, used_rows(&screen)));
+    }
+    let area = buffer.area;
+    let mut renderer = Renderer::new(options, area.width, area.height)?;
+    renderer.render(&buffer, options.idle_limit);
+    Ok(renderer)
+}
+
+fn render_cast(options: &Options, cast: &Asciicast) -> Result<Renderer, String> {
+    let cols = options.cols.unwrap_or(cast.width);
+    let rows = options.rows.unwrap_or(cast.height);
+    let mut screen = VtScreen::new(cols, rows);
+    let mut renderer = Renderer::new(options, cols, rows)?;
+    let frame_time = 1.0 / options.fps;
+    let mut events = cast
+        .events
+        .iter()
+        .filter_map(|(time, event)| match event {
+            AsciicastEvent::Output(data) => Some((*time, data)),
+            _ => None,
+        })
+        .peekable();
+    let mut time = 0.0;
+    loop {
+        // Output coming faster than the frame rate is drawn in a single frame.
+        while let Some((_, data)) = events.next_if(|(t, _)| *t < time + frame_time) {
+            screen.feed(data.as_bytes());
+        }
+        let next = events.peek().map(|(t, _)| *t);
+        let delay = next.map_or(options.idle_limit, |next| next - time);
+        // The idle limit wins over the frame time when it is shorter.
+        renderer.render(
+            screen.buffer(),
+            delay.max(frame_time).min(options.idle_limit),
+        );
+        let Some(next) = next else {
+            break;
+        };
+        time = next;
+    }
+    Ok(renderer)
+}
+
+fn write_png(renderer: &Renderer, writer: impl Write) -> Result<(), png::EncodingError> {
+    let pixmap = &renderer.backend.rgb_pixmap;
+    let mut encoder = png::Encoder::new(writer, pixmap.width() as u32, pixmap.height() as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    if renderer.frames.len() <= 1 {
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(pixmap.data())?;
+        return writer.finish();
+    }
+    encoder.set_animated(renderer.frames.len() as u32, 0)?;
+    let mut writer = encoder.write_header()?;
+    for frame in &renderer.frames {
+        let region = frame.region;
+        writer.set_frame_position(0, 0)?;
+        writer.set_frame_dimension(region.width as u32, region.height as u32)?;
+        writer.set_frame_position(region.x as u32, region.y as u32)?;
+        writer.set_frame_delay((frame.delay * 1000.0).round().min(65535.0) as u16, 1000)?;
+        writer.set_dispose_op(png::DisposeOp::None)?;
+        writer.set_blend_op(png::BlendOp::Source)?;
+        writer.write_image_data(&frame.pixels)?;
+    }
+    writer.finish()
+}
+
+fn write_gif(renderer: &Renderer, writer: impl Write) -> Result<(), gif::EncodingError> {
+    let pixmap = &renderer.backend.rgb_pixmap;
+    let (width, height) = (pixmap.width() as u16, pixmap.height() as u16);
+    let mut encoder = gif::Encoder::new(writer, width, height, &[])?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+    for frame in &renderer.frames {
+        let region = frame.region;
+        let mut gif_frame = gif::Frame::from_rgb_speed(
+            region.width as u16,
+            region.height as u16,
+            &frame.pixels,
+            10,
+        );
+        gif_frame.left = region.x as u16;
+        gif_frame.top = region.y as u16;
+        gif_frame.delay = (frame.delay * 100.0).round().min(65535.0) as u16;
+        gif_frame.dispose = gif::DisposalMethod::Keep;
+        encoder.write_frame(&gif_frame)?;
+    }
+    Ok(())
+}
+
+fn run(options: Options) -> Result<(), String> {
+    let path = &options.output;
+    let extension = path
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase());
+    let gif = match extension.as_deref() {
+        Some("png" | "apng") => false,
+        Some("gif") => true,
+        _ => return Err(format!("unknown output format: {path}")),
+    };
+    let mut input = Vec::new();
+    match &options.input {
+        Some(path) => File::open(path).and_then(|mut file| file.read_to_end(&mut input)),
+        None => io::stdin().read_to_end(&mut input),
+    }
+    .map_err(|e| format!("cannot read input: {e}"))?;
+
+    // Recordings start with a JSON header, text never starts with `{`.
+    let renderer = if input.trim_ascii_start().starts_with(b"{") {
+        let text = String::from_utf8_lossy(&input);
+        render_cast(
+            &options,
+            &Asciicast::parse(&text).map_err(|e| e.to_string())?,
+        )?
+    } else {
+        render_text(&options, &input)?
+    };
+
+    let file = File::create(path).map_err(|e| format!("cannot create {path}: {e}"))?;
+    let mut writer = BufWriter::new(file);
+    if gif {
+        write_gif(&renderer, &mut writer).map_err(|e| e.to_string())?;
+    } else {
+        write_png(&renderer, &mut writer).map_err(|e| e.to_string())?;
+    }
+    writer
+        .flush()
+        .map_err(|e| format!("cannot write {path}: {e}"))
+}
+
+fn main() -> ExitCode {
+    match parse_args() {
+        Ok(Some(options)) => match run(options) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => {
+                eprintln!("soft_ratatui: {error}");
+                ExitCode::FAILURE
+            }
+        },
+        Ok(None) => {
+            print!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("soft_ratatui: {error}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
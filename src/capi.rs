@@ -0,0 +1,323 @@
+//! A C API for hosts written in other languages, such as C++ game engines, declared in
+//! `include/soft_ratatui.h`, which cbindgen generates from this file.
+//!
+//! A `SoftRatatui` handle owns a backend and a screen. Cells are set one by one or by feeding
+//! ANSI text, and drawn into the pixmap by `soft_ratatui_render`.
+//!
+//! Unwinding into C is undefined, so every function catches panics and reports them through its
+//! return value. A handle should only be freed after a function failed on it.
+
+use core::ffi::c_char;
+use std::boxed::Box;
+use std::ffi::CStr;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::slice;
+use std::vec::Vec;
+
+use ratatui::backend::Backend;
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier};
+use ratatui::widgets::Widget;
+
+use crate::bitmap_font::BitmapFont;
+use crate::soft_backend::SoftBackend;
+use crate::vt::VtScreen;
+
+/// The pixel format of `SoftRatatuiPixmap`: 3 bytes per pixel, red first.
+pub const FORMAT_RGB888: u32 = 0;
+
+/// A backend with the cells to draw into it.
+pub struct SoftRatatui {
+    backend: SoftBackend,
+    /// The cells drawn by the next render.
+    buffer: Buffer,
+    screen: VtScreen,
+    /// Whether text was fed to the screen since the last render.
+    screen_changed: bool,
+}
+
+/// The pixels of the pixmap, valid until the next call that takes the handle mutably.
+#[repr(C)]
+pub struct SoftRatatuiPixmap {
+    data: *const u8,
+    width: usize,
+    height: usize,
+    /// The number of bytes from the start of a row to the next.
+    stride: usize,
+    /// `SOFT_RATATUI_FORMAT_RGB888`.
+    format: u32,
+}
+
+/// A rectangle of the pixmap in pixels.
+#[repr(C)]
+pub struct SoftRatatuiRect {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl SoftRatatui {
+    fn new(backend: SoftBackend) -> *mut Self {
+        let area = backend.buffer.area;
+        Box::into_raw(Box::new(Self {
+            buffer: Buffer::empty(area),
+            screen: VtScreen::new(area.width, area.height),
+            screen_changed: false,
+            backend,
+        }))
+    }
+}
+
+/// Runs `f`, returning `failed` instead if it panics.
+fn catch<T>(failed: T, f: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(failed)
+}
+
+/// Decodes a color: `0x00RRGGBB`, `0x01000000` plus a palette index, or anything else for the
+/// default color.
+fn color(value: u32) -> Color {
+    match value >> 24 {
+        0 => Color::Rgb((value >> 16) as u8, (value >> 8) as u8, value as u8),
+        1 => Color::Indexed(value as u8),
+        _ => Color::Reset,
+    }
+}
+
+/// Creates a handle of `cols` by `rows` cells drawn with the built-in 8x13 bitmap font.
+/// Returns null if it fails.
+#[unsafe(no_mangle)]
+pub extern "C" fn soft_ratatui_new_bitmap(cols: u16, rows: u16) -> *mut SoftRatatui {
+    catch(core::ptr::null_mut(), || {
+        SoftRatatui::new(SoftBackend::new_with_bitmap_font(
+            cols,
+            rows,
+            BitmapFont::FIXED_8X13,
+        ))
+    })
+}
+
+/// Creates a handle of `cols` by `rows` cells drawn with a TTF/OTF font of `font_size` pixels.
+/// Returns null if the font can't be loaded.
+///
+/// # Safety
+/// `font_data` must point to `font_len` readable bytes, which are copied.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn soft_ratatui_new_with_font(
+    cols: u16,
+    rows: u16,
+    font_size: i32,
+    font_data: *const u8,
+    font_len: usize,
+) -> *mut SoftRatatui {
+    if font_data.is_null() {
+        return core::ptr::null_mut();
+    }
+    // SAFETY: Guaranteed by the caller.
+    let font_data = unsafe { slice::from_raw_parts(font_data, font_len) };
+    catch(None, || {
+        SoftBackend::try_new_with_font(cols, rows, font_size, font_data)
+    })
+    .map_or(core::ptr::null_mut(), SoftRatatui::new)
+}
+
+/// Destroys a handle. Null is ignored.
+///
+/// # Safety
+/// `handle` must be null or come from a constructor, and not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn soft_ratatui_free(handle: *mut SoftRatatui) {
+    if !handle.is_null() {
+        // SAFETY: Guaranteed by the caller.
+        let handle = unsafe { Box::from_raw(handle) };
+        catch((), || drop(handle));
+    }
+}
+
+/// Resizes the grid to `cols` by `rows` cells, keeping the top left of the cells. The pixmap is
+/// resized and fully redrawn. Returns false if it fails.
+///
+/// # Safety
+/// `handle` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn soft_ratatui_resize(
+    handle: *mut SoftRatatui,
+    cols: u16,
+    rows: u16,
+) -> bool {
+    // SAFETY: Guaranteed by the caller.
+    let handle = unsafe { &mut *handle };
+    catch(false, || {
+        handle.buffer.resize(Rect::new(0, 0, cols, rows));
+        handle.screen.resize(cols, rows);
+        handle.backend.resize(cols, rows);
+        true
+    })
+}
+
+/// Sets a cell to a UTF-8 symbol with colors, see `SOFT_RATATUI_RGB`, and modifiers, see
+/// `SOFT_RATATUI_BOLD`. Returns false if the cell is outside the grid, the symbol isn't UTF-8 or
+/// it fails.
+///
+/// # Safety
+/// `handle` must be a live handle and `symbol` a null terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn soft_ratatui_set_cell(
+    handle: *mut SoftRatatui,
+    x: u16,
+    y: u16,
+    symbol: *const c_char,
+    fg: u32,
+    bg: u32,
+    modifiers: u16,
+) -> bool {
+    // SAFETY: Guaranteed by the caller.
+    let (handle, symbol) = unsafe { (&mut *handle, CStr::from_ptr(symbol)) };
+    catch(false, || {
+        let (Ok(symbol), Some(cell)) = (symbol.to_str(), handle.buffer.cell_mut((x, y))) else {
+            return false;
+        };
+        cell.set_symbol(symbol)
+            .set_fg(color(fg))
+            .set_bg(color(bg))
+            .modifier = Modifier::from_bits_truncate(modifiers);
+        true
+    })
+}
+
+/// Resets every cell to a blank with the default colors. Returns false if it fails.
+///
+/// # Safety
+/// `handle` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn soft_ratatui_clear(handle: *mut SoftRatatui) -> bool {
+    // SAFETY: Guaranteed by the caller.
+    let handle = unsafe { &mut *handle };
+    catch(false, || {
+        handle.buffer.reset();
+        true
+    })
+}
+
+/// Parses ANSI text, e.g. the output of a program, into a terminal screen that replaces the
+/// cells at the next render. Sequences may be split across calls. Returns false if `data` is
+/// null or it fails.
+///
+/// # Safety
+/// `handle` must be a live handle and `data` point to `len` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn soft_ratatui_feed(
+    handle: *mut SoftRatatui,
+    data: *const u8,
+    len: usize,
+) -> bool {
+    if data.is_null() {
+        return false;
+    }
+    // SAFETY: Guaranteed by the caller.
+    let (handle, data) = unsafe { (&mut *handle, slice::from_raw_parts(data, len)) };
+    catch(false, || {
+        handle.screen.feed(data);
+        handle.screen_changed = true;
+        true
+    })
+}
+
+/// Draws the cells that changed since the last render into the pixmap, and marks their
+/// pixels dirty. Returns false if it fails.
+///
+/// # Safety
+/// `handle` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn soft_ratatui_render(handle: *mut SoftRatatui) -> bool {
+    // SAFETY: Guaranteed by the caller.
+    let handle = unsafe { &mut *handle };
+    catch(false, || {
+        if handle.screen_changed {
+            let area = handle.buffer.area;
+            (&handle.screen).render(area, &mut handle.buffer);
+            let _ = handle.backend.set_cursor_position(handle.screen.cursor());
+            let _ = if handle.screen.cursor_visible() {
+                handle.backend.show_cursor()
+            } else {
+                handle.backend.hide_cursor()
+            };
+            handle.screen_changed = false;
+        }
+        let updates: Vec<(u16, u16, &Cell)> = handle.backend.buffer.diff(&handle.buffer);
+        let _ = handle.backend.draw(updates.into_iter());
+        true
+    })
+}
+
+/// Returns the pixels of the pixmap, with null data if it fails.
+///
+/// # Safety
+/// `handle` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn soft_ratatui_pixmap(handle: *const SoftRatatui) -> SoftRatatuiPixmap {
+    // SAFETY: Guaranteed by the caller.
+    let pixmap = unsafe { &(*handle).backend.rgb_pixmap };
+    let failed = SoftRatatuiPixmap {
+        data: core::ptr::null(),
+        width: 0,
+        height: 0,
+        stride: 0,
+        format: FORMAT_RGB888,
+    };
+    catch(failed, || SoftRatatuiPixmap {
+        data: pixmap.data().as_ptr(),
+        width: pixmap.width(),
+        height: pixmap.height(),
+        stride: pixmap.width() * 3,
+        format: FORMAT_RGB888,
+    })
+}
+
+/// Writes up to `capacity` rectangles of the pixmap redrawn since the last
+/// `soft_ratatui_clear_dirty` to `rects`, and returns how many there are in total, or
+/// `SIZE_MAX` if it fails.
+///
+/// # Safety
+/// `handle` must be a live handle and `rects` point to `capacity` writable rectangles, or be
+/// null with a capacity of 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn soft_ratatui_dirty_rects(
+    handle: *const SoftRatatui,
+    rects: *mut SoftRatatuiRect,
+    capacity: usize,
+) -> usize {
+    // SAFETY: Guaranteed by the caller.
+    let handle = unsafe { &*handle };
+    catch(usize::MAX, || {
+        let regions = handle.backend.dirty_regions();
+        for (i, region) in regions.iter().take(capacity).enumerate() {
+            // SAFETY: `i` is below the capacity guaranteed by the caller.
+            unsafe {
+                rects.add(i).write(SoftRatatuiRect {
+                    x: region.x as u32,
+                    y: region.y as u32,
+                    width: region.width as u32,
+                    height: region.height as u32,
+                });
+            }
+        }
+        regions.len()
+    })
+}
+
+/// Forgets the dirty rectangles, call after the pixmap has been presented. Returns false if it
+/// fails.
+///
+/// # Safety
+/// `handle` must be a live handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn soft_ratatui_clear_dirty(handle: *mut SoftRatatui) -> bool {
+    // SAFETY: Guaranteed by the caller.
+    let handle = unsafe { &mut *handle };
+    catch(false, || {
+        handle.backend.clear_dirty();
+        true
+    })
+}
@@ -26,14 +26,48 @@ pub fn rat_to_rgb(rat_col: &RatColor, is_a_fg: bool) -> [u8; 3] {
         RatColor::LightMagenta => [139, 0, 139],
         RatColor::LightCyan => [224, 255, 255],
         RatColor::White => [255, 255, 255],
-        RatColor::Indexed(i) => {
-            let i = *i as u8;
-            [i.wrapping_mul(i), i.wrapping_add(i), i]
-        }
+        RatColor::Indexed(i) => indexed_to_rgb(*i, is_a_fg),
         RatColor::Rgb(r, g, b) => [*r, *g, *b],
     }
 }
 
+/// Converts an index of the xterm 256 color palette into a rgb [u8;3]
+///
+/// Indexes 0 to 15 are the named colors, 16 to 231 a 6x6x6 color cube and 232 to 255 a
+/// grayscale ramp.
+fn indexed_to_rgb(index: u8, is_a_fg: bool) -> [u8; 3] {
+    const NAMED: [RatColor; 16] = [
+        RatColor::Black,
+        RatColor::Red,
+        RatColor::Green,
+        RatColor::Yellow,
+        RatColor::Blue,
+        RatColor::Magenta,
+        RatColor::Cyan,
+        RatColor::Gray,
+        RatColor::DarkGray,
+        RatColor::LightRed,
+        RatColor::LightGreen,
+        RatColor::LightYellow,
+        RatColor::LightBlue,
+        RatColor::LightMagenta,
+        RatColor::LightCyan,
+        RatColor::White,
+    ];
+    match index {
+        0..=15 => rat_to_rgb(&NAMED[index as usize], is_a_fg),
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let cube = index - 16;
+            [level(cube / 36), level(cube / 6 % 6), level(cube % 6)]
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            [gray, gray, gray]
+        }
+    }
+}
+
 /// Blend two RGBA colors using alpha compositing.
 ///
 /// (fg over bg) -> resulting RGB
@@ -47,8 +81,9 @@ pub fn blend_rgba(fg: [u8; 4], bg: [u8; 4]) -> [u8; 3] {
     let bg_a = bg[3] as f32 / 255.0;
     let out_a = fg_a + bg_a * (1.0 - fg_a);
 
+    // Channels are never negative, so adding 0.5 before truncating rounds without `std`.
     let blend_channel =
-        |f: u8, b: u8| ((f as f32 * fg_a + b as f32 * bg_a * (1.0 - fg_a)) / out_a).round() as u8;
+        |f: u8, b: u8| ((f as f32 * fg_a + b as f32 * bg_a * (1.0 - fg_a)) / out_a + 0.5) as u8;
 
     if out_a == 0.0 {
         [0, 0, 0]
@@ -0,0 +1,190 @@
+//! Glyph rasterization for regular TTF/OTF fonts, powered by cosmic-text.
+
+use alloc::string::{String, ToString};
+
+use crate::colors::*;
+use crate::pixmap::RgbPixmap;
+
+use cosmic_text::fontdb::Database;
+use cosmic_text::{
+    Attrs, AttrsList, CacheKeyFlags, Family, LineEnding, Metrics, Shaping, Weight, Wrap,
+};
+use cosmic_text::{Buffer as CosmicBuffer, FontSystem, SwashCache};
+use ratatui::style::Modifier;
+
+/// Holds the cosmic-text state needed to shape and rasterize a single cell.
+pub(crate) struct CosmicFont {
+    font_system: FontSystem,
+    cosmic_buffer: CosmicBuffer,
+    swash_cache: SwashCache,
+    pub(crate) char_width: usize,
+    pub(crate) char_height: usize,
+    pub(crate) font_size: f32,
+    /// The distance from the top of a cell to the baseline of its text.
+    pub(crate) baseline: f32,
+}
+
+fn add_strikeout(text: &str) -> String {
+    let strike = '\u{0336}';
+    text.chars().flat_map(|c| [c, strike]).collect()
+}
+
+fn add_underline(text: &str) -> String {
+    let strike = '\u{0332}';
+    text.chars().flat_map(|c| [c, strike]).collect()
+}
+
+impl CosmicFont {
+    /// Creates a font from the given font file data, `None` if it holds no font that can draw
+    /// `█` at a positive size.
+    pub(crate) fn from_font_data(font_size: i32, font_data: &[u8]) -> Option<Self> {
+        let mut db = Database::new();
+        db.load_font_data(font_data.to_vec());
+        // cosmic-text panics without a face to fall back on.
+        if db.is_empty() || font_size <= 0 {
+            return None;
+        }
+        let font_system = FontSystem::new_with_locale_and_db("English".to_string(), db);
+        Self::new(font_system, font_size)
+    }
+
+    /// Creates a font that uses the fonts installed on the system.
+    pub(crate) fn from_system_fonts(font_size: i32) -> Self {
+        Self::new(FontSystem::new(), font_size).expect("no usable system font")
+    }
+
+    fn new(mut font_system: FontSystem, font_size: i32) -> Option<Self> {
+        let metrics = Metrics::new(font_size as f32, font_size as f32);
+        let cosmic_buffer = CosmicBuffer::new(&mut font_system, metrics);
+        let mut font = Self {
+            font_system,
+            cosmic_buffer,
+            swash_cache: SwashCache::new(),
+            char_width: 0,
+            char_height: 0,
+            font_size: 0.0,
+            baseline: 0.0,
+        };
+        font.measure(font_size)?;
+        Some(font)
+    }
+
+    /// Sets a new font size.
+    pub(crate) fn set_font_size(&mut self, font_size: i32) {
+        self.measure(font_size)
+            .expect("the font can't draw █ at this size");
+    }
+
+    /// Sets a font size and measures the resulting cell size from the bounding box of `█`.
+    fn measure(&mut self, font_size: i32) -> Option<()> {
+        let metrics = Metrics::new(font_size as f32, font_size as f32);
+        self.cosmic_buffer
+            .set_metrics(&mut self.font_system, metrics);
+        let mut buffer = CosmicBuffer::new(&mut self.font_system, metrics);
+        let mut buffer = buffer.borrow_with(&mut self.font_system);
+        buffer.set_text(
+            "█\n█",
+            &Attrs::new().family(Family::Monospace),
+            Shaping::Advanced,
+        );
+        buffer.shape_until_scroll(true);
+        let boop = buffer.layout_runs().next()?;
+        let physical_glyph = boop.glyphs.first()?.physical((0., 0.), 1.0);
+        let baseline = boop.line_y;
+
+        let wa = self
+            .swash_cache
+            .get_image(&mut self.font_system, physical_glyph.cache_key)
+            .clone()?
+            .placement;
+        if wa.width == 0 || wa.height == 0 {
+            return None;
+        }
+
+        self.char_width = wa.width as usize;
+        self.char_height = wa.height as usize;
+        self.font_size = font_size as f32;
+        self.baseline = baseline;
+        self.cosmic_buffer.set_size(
+            &mut self.font_system,
+            Some(self.char_width as f32),
+            Some(self.char_height as f32),
+        );
+        Some(())
+    }
+
+    /// Draws `symbol` with its top left corner at (begin_x, begin_y), blending the glyph coverage
+    /// of `fg_color` over `bg_color`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn draw_symbol(
+        &mut self,
+        pixmap: &mut RgbPixmap,
+        begin_x: usize,
+        begin_y: usize,
+        symbol: &str,
+        modifier: Modifier,
+        fg_color: [u8; 3],
+        bg_color: [u8; 3],
+    ) {
+        let mut text_symbol: String = symbol.to_string();
+
+        if modifier.contains(Modifier::CROSSED_OUT) {
+            text_symbol = add_strikeout(&text_symbol);
+        }
+        if modifier.contains(Modifier::UNDERLINED) {
+            text_symbol = add_underline(&text_symbol);
+        }
+
+        let mut attrs = Attrs::new().family(Family::Monospace);
+        if modifier.contains(Modifier::BOLD) {
+            attrs = attrs.weight(Weight::BOLD);
+        }
+        if modifier.contains(Modifier::ITALIC) {
+            attrs = attrs.cache_key_flags(CacheKeyFlags::FAKE_ITALIC);
+        }
+        let mets = self.cosmic_buffer.metrics().font_size;
+        let line = self.cosmic_buffer.lines.get_mut(0).unwrap();
+        line.set_text(&text_symbol, LineEnding::None, AttrsList::new(&attrs));
+
+        line.layout(&mut self.font_system, mets, None, Wrap::None, None, 1);
+
+        for run in self.cosmic_buffer.layout_runs() {
+            for glyph in run.glyphs.iter() {
+                let physical_glyph = glyph.physical((0., 0.), 1.0);
+
+                //TODO : Handle Content::Color (emojis?)
+
+                if let Some(image) = self
+                    .swash_cache
+                    .get_image(&mut self.font_system, physical_glyph.cache_key)
+                {
+                    let x = image.placement.left;
+
+                    let y = -image.placement.top;
+                    let mut i = 0;
+
+                    for off_y in 0..image.placement.height {
+                        for off_x in 0..image.placement.width {
+                            let real_x = physical_glyph.x + x + off_x as i32;
+
+                            let real_y = run.line_y as i32 + physical_glyph.y + y + off_y as i32;
+
+                            if real_x >= 0 && real_y >= 0 {
+                                let get_x = begin_x + real_x as usize;
+                                let get_y = begin_y + real_y as usize;
+
+                                let put_color = blend_rgba(
+                                    [fg_color[0], fg_color[1], fg_color[2], image.data[i]],
+                                    [bg_color[0], bg_color[1], bg_color[2], 255],
+                                );
+                                pixmap.put_pixel(get_x, get_y, put_color);
+                            }
+
+                            i += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
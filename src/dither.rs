@@ -0,0 +1,379 @@
+//! Grayscale and 1-bit output with dithering, for e-paper and monochrome displays.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::image;
+use crate::pixmap::RgbPixmap;
+use crate::soft_backend::SoftBackend;
+
+/// How colors are reduced to the available gray levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dither {
+    /// Every pixel snaps to the nearest level, giving hard edges and flat areas.
+    #[default]
+    Threshold,
+    /// Ordered dithering with an 8x8 Bayer matrix. Stable between frames, so it suits partial
+    /// e-paper refreshes.
+    Bayer,
+    /// Floyd–Steinberg error diffusion.
+    FloydSteinberg,
+    /// Atkinson error diffusion, which only spreads 3/4 of the error and keeps more contrast.
+    Atkinson,
+}
+
+/// How 1-bit pixels are packed into bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PackLayout {
+    /// Rows of bytes with the leftmost pixel in the most significant bit, each row padded to a
+    /// whole byte. The layout of most e-paper controllers (SSD1680, UC8151, IL0373...).
+    #[default]
+    RowMsbFirst,
+    /// Rows of bytes with the leftmost pixel in the least significant bit.
+    RowLsbFirst,
+    /// Pages of 8 rows, one byte per column with the top pixel in the least significant bit.
+    /// The layout of SSD1306 and SH1106 style controllers.
+    VerticalPages,
+}
+
+const BAYER_8X8: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// A pixmap of gray values, each snapped to one of `levels` evenly spaced shades.
+#[derive(Debug, Clone)]
+pub struct GrayPixmap {
+    width: usize,
+    height: usize,
+    levels: u8,
+    data: Vec<u8>,
+}
+
+impl GrayPixmap {
+    /// Returns the width of the pixmap in pixels
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    /// Returns the height of the pixmap in pixels
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    /// Returns the number of gray levels
+    pub fn levels(&self) -> u8 {
+        self.levels
+    }
+    /// Returns the gray values of the pixmap as a flat array, 0 is black and 255 is white
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the gray value of a pixel at (x, y).
+    pub fn get_pixel(&self, x: usize, y: usize) -> u8 {
+        debug_assert!(
+            x < self.width && y < self.height,
+            "Pixel coordinates out of bounds"
+        );
+        self.data[y * self.width + x]
+    }
+
+    /// Returns the level index (0 = black, levels - 1 = white) of a pixel at (x, y).
+    pub fn get_level(&self, x: usize, y: usize) -> u8 {
+        let max = self.levels as u32 - 1;
+        ((self.get_pixel(x, y) as u32 * max + 127) / 255) as u8
+    }
+
+    /// Packs the pixmap to 1 bit per pixel, a set bit being white.
+    ///
+    /// Pixmaps with more than two levels are split at mid gray. Drivers that expect a set bit to
+    /// be black can invert the bytes.
+    pub fn pack_1bpp(&self, layout: PackLayout) -> Vec<u8> {
+        let white = |x: usize, y: usize| self.get_pixel(x, y) >= 128;
+        match layout {
+            PackLayout::RowMsbFirst | PackLayout::RowLsbFirst => {
+                let stride = self.width.div_ceil(8);
+                let mut packed = vec![0; stride * self.height];
+                for y in 0..self.height {
+                    for x in 0..self.width {
+                        if white(x, y) {
+                            let bit = match layout {
+                                PackLayout::RowMsbFirst => 0x80 >> (x % 8),
+                                _ => 1 << (x % 8),
+                            };
+                            packed[y * stride + x / 8] |= bit;
+                        }
+                    }
+                }
+                packed
+            }
+            PackLayout::VerticalPages => {
+                let pages = self.height.div_ceil(8);
+                let mut packed = vec![0; pages * self.width];
+                for y in 0..self.height {
+                    for x in 0..self.width {
+                        if white(x, y) {
+                            packed[(y / 8) * self.width + x] |= 1 << (y % 8);
+                        }
+                    }
+                }
+                packed
+            }
+        }
+    }
+}
+
+fn luma(color: [u8; 3]) -> i32 {
+    (color[0] as i32 * 299 + color[1] as i32 * 587 + color[2] as i32 * 114 + 500) / 1000
+}
+
+fn quantize(value: i32, levels: u8) -> u8 {
+    let max = levels as i32 - 1;
+    let level = ((value.clamp(0, 255) * max + 127) / 255).clamp(0, max);
+    (level * 255 / max) as u8
+}
+
+impl RgbPixmap {
+    /// Converts the pixmap to `levels` shades of gray, dithering every pixel.
+    ///
+    /// (to-gray levels dither) -> GrayPixmap
+    ///
+    /// * levels     : u8     - Number of gray shades, 2 for 1-bit displays
+    /// * dither     : Dither - Dithering algorithm
+    pub fn to_gray(&self, levels: u8, dither: Dither) -> GrayPixmap {
+        self.to_gray_masked(levels, dither, |_, _| true)
+    }
+
+    /// Converts the pixmap to `levels` shades of gray, dithering only the pixels for which
+    /// `dither_mask` returns true and thresholding the rest.
+    ///
+    /// Thresholded pixels neither receive nor spread diffusion error, so they keep hard edges.
+    pub fn to_gray_masked<F>(&self, levels: u8, dither: Dither, dither_mask: F) -> GrayPixmap
+    where
+        F: Fn(usize, usize) -> bool,
+    {
+        assert!(levels >= 2, "At least two gray levels are needed");
+        let (width, height) = (self.width(), self.height());
+        let mut values: Vec<i32> = self
+            .data()
+            .chunks_exact(3)
+            .map(|px| luma([px[0], px[1], px[2]]))
+            .collect();
+        let mut data = vec![0; width * height];
+        let step = 255 / (levels as i32 - 1);
+
+        for y in 0..height {
+            for x in 0..width {
+                let index = y * width + x;
+                let value = values[index];
+                if dither == Dither::Threshold || !dither_mask(x, y) {
+                    data[index] = quantize(value, levels);
+                    continue;
+                }
+
+                if dither == Dither::Bayer {
+                    let bayer = BAYER_8X8[y % 8][x % 8] as i32;
+                    let offset = (2 * bayer + 1) * step / 128 - step / 2;
+                    data[index] = quantize(value + offset, levels);
+                    continue;
+                }
+
+                let out = quantize(value, levels);
+                data[index] = out;
+                let error = value - out as i32;
+                // (dx, dy, weight) with the weights over a common divisor.
+                let (spread, divisor): (&[(isize, usize, i32)], i32) = match dither {
+                    Dither::FloydSteinberg => (&[(1, 0, 7), (-1, 1, 3), (0, 1, 5), (1, 1, 1)], 16),
+                    _ => (
+                        &[
+                            (1, 0, 1),
+                            (2, 0, 1),
+                            (-1, 1, 1),
+                            (0, 1, 1),
+                            (1, 1, 1),
+                            (0, 2, 1),
+                        ],
+                        8,
+                    ),
+                };
+                for &(dx, dy, weight) in spread {
+                    let nx = x as isize + dx;
+                    let ny = y + dy;
+                    if nx < 0 || nx as usize >= width || ny >= height {
+                        continue;
+                    }
+                    if dither_mask(nx as usize, ny) {
+                        values[ny * width + nx as usize] += error * weight / divisor;
+                    }
+                }
+            }
+        }
+
+        GrayPixmap {
+            width,
+            height,
+            levels,
+            data,
+        }
+    }
+}
+
+impl SoftBackend {
+    /// Converts the rendered frame to `levels` shades of gray for e-paper displays.
+    ///
+    /// Cells with text are thresholded so glyphs keep crisp edges, while blank and image cells,
+    /// which carry gradients and color fills, are dithered.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use soft_ratatui::{BitmapFont, Dither, PackLayout, SoftBackend};
+    /// let backend = SoftBackend::new_with_bitmap_font(10, 2, BitmapFont::FIXED_8X13);
+    /// let gray = backend.to_gray(2, Dither::Atkinson);
+    /// let packed = gray.pack_1bpp(PackLayout::RowMsbFirst);
+    /// assert_eq!(packed.len(), 10 * 26);
+    /// ```
+    pub fn to_gray(&self, levels: u8, dither: Dither) -> GrayPixmap {
+        let area = self.buffer.area;
+        let blank: Vec<bool> = self
+            .buffer
+            .content
+            .iter()
+            .map(|cell| {
+                let symbol = cell.symbol();
+                symbol.chars().all(char::is_whitespace) || image::is_placeholder(symbol)
+            })
+            .collect();
+        self.rgb_pixmap.to_gray_masked(levels, dither, |x, y| {
+            let column = x / self.char_width;
+            let row = y / self.char_height;
+            blank[row * area.width as usize + column]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pixmap of 2 levels with the white pixels of a `width` by `height` grid.
+    fn two_levels(width: usize, height: usize, white: &[(usize, usize)]) -> GrayPixmap {
+        let mut data = vec![0; width * height];
+        for &(x, y) in white {
+            data[y * width + x] = 255;
+        }
+        GrayPixmap {
+            width,
+            height,
+            levels: 2,
+            data,
+        }
+    }
+
+    fn flat(width: usize, height: usize, color: [u8; 3]) -> RgbPixmap {
+        let mut pixmap = RgbPixmap::new(width, height);
+        pixmap.fill(color);
+        pixmap
+    }
+
+    fn white_count(gray: &GrayPixmap) -> usize {
+        gray.data().iter().filter(|&&value| value == 255).count()
+    }
+
+    #[test]
+    fn packs_rows_msb_first() {
+        // 10 pixels wide, so each row is padded to 2 bytes.
+        let gray = two_levels(10, 2, &[(0, 0), (9, 0), (1, 1)]);
+        assert_eq!(
+            gray.pack_1bpp(PackLayout::RowMsbFirst),
+            [0x80, 0x40, 0x40, 0x00]
+        );
+    }
+
+    #[test]
+    fn packs_rows_lsb_first() {
+        let gray = two_levels(10, 2, &[(0, 0), (9, 0), (1, 1)]);
+        assert_eq!(
+            gray.pack_1bpp(PackLayout::RowLsbFirst),
+            [0x01, 0x02, 0x02, 0x00]
+        );
+    }
+
+    #[test]
+    fn packs_vertical_pages() {
+        // 9 rows, so the second page only has its top pixel.
+        let gray = two_levels(3, 9, &[(0, 0), (1, 7), (2, 1), (2, 8)]);
+        assert_eq!(
+            gray.pack_1bpp(PackLayout::VerticalPages),
+            [0x01, 0x80, 0x02, 0x00, 0x00, 0x01]
+        );
+    }
+
+    #[test]
+    fn packs_more_levels_split_at_mid_gray() {
+        let gray = GrayPixmap {
+            width: 4,
+            height: 1,
+            levels: 4,
+            data: vec![0, 85, 170, 255],
+        };
+        assert_eq!(gray.pack_1bpp(PackLayout::RowMsbFirst), [0b0011_0000]);
+    }
+
+    #[test]
+    fn thresholds_to_the_nearest_level() {
+        let mut pixmap = RgbPixmap::new(4, 1);
+        pixmap.put_pixel(1, 0, [255, 255, 255]);
+        // Luma 76.
+        pixmap.put_pixel(2, 0, [255, 0, 0]);
+        pixmap.put_pixel(3, 0, [200, 200, 200]);
+
+        let gray = pixmap.to_gray(2, Dither::Threshold);
+        assert_eq!(gray.data(), [0, 255, 0, 255]);
+        let gray = pixmap.to_gray(4, Dither::Threshold);
+        assert_eq!(gray.data(), [0, 255, 85, 170]);
+        assert_eq!(
+            (0..4).map(|x| gray.get_level(x, 0)).collect::<Vec<_>>(),
+            [0, 3, 1, 2]
+        );
+    }
+
+    #[test]
+    fn bayer_dithers_mid_gray_to_half_white() {
+        let gray = flat(8, 8, [128, 128, 128]).to_gray(2, Dither::Bayer);
+        assert_eq!(white_count(&gray), 32);
+        // Stable between frames.
+        assert_eq!(
+            gray.data(),
+            flat(8, 8, [128, 128, 128]).to_gray(2, Dither::Bayer).data()
+        );
+    }
+
+    #[test]
+    fn error_diffusion_keeps_the_average() {
+        let pixmap = flat(8, 8, [64, 64, 64]);
+        // A quarter white, within the error left at the edges.
+        let floyd_steinberg = pixmap.to_gray(2, Dither::FloydSteinberg);
+        assert!((14..=18).contains(&white_count(&floyd_steinberg)));
+        // Atkinson drops a quarter of the error, keeping dark areas darker.
+        let atkinson = pixmap.to_gray(2, Dither::Atkinson);
+        assert!(white_count(&atkinson) <= white_count(&floyd_steinberg));
+        assert!(white_count(&atkinson) > 0);
+    }
+
+    #[test]
+    fn masked_pixels_are_thresholded_without_error() {
+        let pixmap = flat(8, 8, [96, 96, 96]);
+        let gray = pixmap.to_gray_masked(2, Dither::FloydSteinberg, |x, _| x < 4);
+        // The unmasked right half is all black, taking no error from the left half.
+        for y in 0..8 {
+            assert!((4..8).all(|x| gray.get_pixel(x, y) == 0));
+        }
+        assert!(white_count(&gray) > 0);
+        assert_eq!(gray.levels(), 2);
+    }
+}
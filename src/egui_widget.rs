@@ -0,0 +1,424 @@
+//! An egui widget showing a terminal in the space it's given, and turning the egui input it
+//! receives into terminal input.
+
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+
+use egui::{
+    Color32, ColorImage, Event, EventFilter, Modifiers, PointerButton, Pos2, Rect, Response, Sense,
+    TextureHandle, TextureOptions, Ui, Widget,
+};
+use ratatui::Terminal;
+
+use crate::input::{
+    InputEvent, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use crate::pixmap::PixelRect;
+use crate::soft_backend::SoftBackend;
+
+/// A terminal shown as an egui widget, filling the space available in the `Ui` with as many
+/// cells as fit, at a pixel of the pixmap per physical pixel.
+///
+/// The widget keeps a single texture, created on the first frame and after resizes, and uploads
+/// only the regions redrawn since the last frame into it. Once clicked, it takes the keyboard
+/// focus, including Tab, arrows and Escape. Key presses while it has the focus, and mouse events
+/// over it, are collected for [`Self::take_events`].
+///
+/// # Examples
+/// ```rust
+/// use ratatui::widgets::Paragraph;
+/// use soft_ratatui::{BitmapFont, EguiTerminal, SoftBackend};
+///
+/// let mut terminal =
+///     EguiTerminal::new(SoftBackend::new_with_bitmap_font(1, 1, BitmapFont::FIXED_8X13));
+/// let ctx = egui::Context::default();
+/// let input = egui::RawInput {
+///     screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(160.0, 52.0))),
+///     ..Default::default()
+/// };
+/// let _ = ctx.run(input, |ctx| {
+///     egui::CentralPanel::default()
+///         .frame(egui::Frame::NONE)
+///         .show(ctx, |ui| ui.add(&mut terminal));
+/// });
+/// assert_eq!(terminal.size().unwrap(), ratatui::layout::Size::new(20, 4));
+/// terminal
+///     .draw(|frame| frame.render_widget(Paragraph::new("Hello"), frame.area()))
+///     .unwrap();
+/// ```
+pub struct EguiTerminal {
+    terminal: Terminal<SoftBackend>,
+    texture: Option<TextureHandle>,
+    events: Vec<InputEvent>,
+}
+
+impl EguiTerminal {
+    /// Creates a widget showing a terminal drawing into a backend.
+    pub fn new(backend: SoftBackend) -> Self {
+        Self {
+            terminal: Terminal::new(backend).unwrap_or_else(|error| match error {}),
+            texture: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns the input received since the last call, in order.
+    pub fn take_events(&mut self) -> Vec<InputEvent> {
+        core::mem::take(&mut self.events)
+    }
+
+    /// Uploads the pixmap, whole into a new texture if there is none of its size, else its dirty
+    /// regions.
+    fn upload(&mut self, ui: &Ui) -> &TextureHandle {
+        let backend = self.terminal.backend_mut();
+        let size = [backend.get_pixmap_width(), backend.get_pixmap_height()];
+        match &mut self.texture {
+            Some(texture) if texture.size() == size => {
+                for region in backend.dirty_regions() {
+                    texture.set_partial(
+                        [region.x, region.y],
+                        region_image(backend, region),
+                        TextureOptions::NEAREST,
+                    );
+                }
+            }
+            texture => {
+                *texture = Some(ui.ctx().load_texture(
+                    "soft_ratatui",
+                    ColorImage::from_rgb(size, backend.get_pixmap_data()),
+                    TextureOptions::NEAREST,
+                ));
+            }
+        }
+        backend.clear_dirty();
+        self.texture.as_ref().expect("texture was just loaded")
+    }
+}
+
+impl Deref for EguiTerminal {
+    type Target = Terminal<SoftBackend>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl DerefMut for EguiTerminal {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+impl Widget for &mut EguiTerminal {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let backend = self.terminal.backend_mut();
+        let available = ui.available_size() * pixels_per_point;
+        // Unbounded, e.g. in a scroll area: keep the size.
+        if available.is_finite() {
+            backend.fit_to_pixels(available.x as usize, available.y as usize);
+        }
+        let pixmap = egui::vec2(
+            backend.get_pixmap_width() as f32,
+            backend.get_pixmap_height() as f32,
+        ) / pixels_per_point;
+        let desired = if available.is_finite() {
+            ui.available_size()
+        } else {
+            pixmap
+        };
+        let (rect, response) = ui.allocate_exact_size(desired, Sense::click_and_drag());
+
+        let texture = self.upload(ui).id();
+        let image = Rect::from_min_size(rect.min, pixmap);
+        let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+        ui.painter().image(texture, image, uv, Color32::WHITE);
+
+        if response.clicked() || response.drag_started() {
+            response.request_focus();
+        }
+        let focused = response.has_focus();
+        if focused {
+            ui.memory_mut(|memory| {
+                memory.set_focus_lock_filter(
+                    response.id,
+                    EventFilter {
+                        tab: true,
+                        horizontal_arrows: true,
+                        vertical_arrows: true,
+                        escape: true,
+                    },
+                );
+            });
+        }
+        let over = response.hovered() || response.is_pointer_button_down_on();
+        let backend = self.terminal.backend();
+        ui.input(|input| {
+            // The buttons held before the events of this frame, updated as they are read.
+            let mut held: Vec<MouseButton> = [
+                (PointerButton::Primary, MouseButton::Left),
+                (PointerButton::Middle, MouseButton::Middle),
+                (PointerButton::Secondary, MouseButton::Right),
+            ]
+            .into_iter()
+            .filter(|(button, _)| input.pointer.button_down(*button))
+            .map(|(_, button)| button)
+            .collect();
+            for event in input.events.iter().rev() {
+                if let Event::PointerButton {
+                    button, pressed, ..
+                } = event
+                    && let Some(button) = mouse_button(*button)
+                {
+                    held.retain(|held| *held != button);
+                    if !pressed {
+                        held.push(button);
+                    }
+                }
+            }
+            let cell = |pos: Pos2| {
+                let pixel = (pos - image.min) * pixels_per_point;
+                backend.cell_at_pixel(pixel.x, pixel.y)
+            };
+            let hover = input.pointer.hover_pos();
+            for event in &input.events {
+                if let Event::PointerButton {
+                    button, pressed, ..
+                } = event
+                    && let Some(button) = mouse_button(*button)
+                {
+                    held.retain(|held| *held != button);
+                    if *pressed {
+                        held.push(button);
+                    }
+                }
+                let (kind, pos, modifiers) = match event {
+                    Event::PointerMoved(pos) if over => (
+                        held.first().map_or(MouseEventKind::Moved, |button| {
+                            MouseEventKind::Drag(*button)
+                        }),
+                        *pos,
+                        input.modifiers,
+                    ),
+                    Event::PointerButton {
+                        pos,
+                        button,
+                        pressed,
+                        modifiers,
+                    } if over => {
+                        let Some(button) = mouse_button(*button) else {
+                            continue;
+                        };
+                        let kind = if *pressed {
+                            MouseEventKind::Down(button)
+                        } else {
+                            MouseEventKind::Up(button)
+                        };
+                        (kind, *pos, *modifiers)
+                    }
+                    Event::MouseWheel {
+                        delta, modifiers, ..
+                    } if over && delta.y != 0.0 => {
+                        let Some(pos) = hover else {
+                            continue;
+                        };
+                        let kind = if delta.y > 0.0 {
+                            MouseEventKind::ScrollUp
+                        } else {
+                            MouseEventKind::ScrollDown
+                        };
+                        (kind, pos, *modifiers)
+                    }
+                    _ if focused => {
+                        self.events.extend(key_events(event).map(InputEvent::Key));
+                        continue;
+                    }
+                    _ => continue,
+                };
+                if let Some(hit) = cell(pos) {
+                    self.events.push(InputEvent::Mouse(MouseEvent {
+                        kind,
+                        column: hit.position.x,
+                        row: hit.position.y,
+                        modifiers: key_modifiers(modifiers),
+                    }));
+                }
+            }
+        });
+        response
+    }
+}
+
+/// Copies a region of the pixmap into an image.
+fn region_image(backend: &SoftBackend, region: PixelRect) -> ColorImage {
+    let width = backend.get_pixmap_width();
+    let data = backend.get_pixmap_data();
+    let mut pixels = Vec::with_capacity(region.width * region.height * 3);
+    for y in region.y..region.y + region.height {
+        let start = (y * width + region.x) * 3;
+        pixels.extend_from_slice(&data[start..start + region.width * 3]);
+    }
+    ColorImage::from_rgb([region.width, region.height], &pixels)
+}
+
+fn key_modifiers(modifiers: Modifiers) -> KeyModifiers {
+    KeyModifiers {
+        shift: modifiers.shift,
+        ctrl: modifiers.ctrl,
+        alt: modifiers.alt,
+    }
+}
+
+fn mouse_button(button: PointerButton) -> Option<MouseButton> {
+    match button {
+        PointerButton::Primary => Some(MouseButton::Left),
+        PointerButton::Secondary => Some(MouseButton::Right),
+        PointerButton::Middle => Some(MouseButton::Middle),
+        _ => None,
+    }
+}
+
+/// Converts an egui event to key presses: typed and pasted text, keys without text such as
+/// arrows, and letters and digits pressed with Ctrl or Alt, for which egui sends no text.
+///
+/// egui-winit sends Ctrl+C and Ctrl+X, and their Ctrl+Insert and Shift+Delete aliases, only as
+/// copy and cut commands, which are turned back into Ctrl+C and Ctrl+X for the terminal.
+fn key_events(event: &Event) -> impl Iterator<Item = KeyEvent> + '_ {
+    let (text, key) = match event {
+        Event::Text(text) | Event::Paste(text) => (Some(text.as_str()), None),
+        Event::Copy => (None, Some(ctrl_key('c'))),
+        Event::Cut => (None, Some(ctrl_key('x'))),
+        Event::Key {
+            key,
+            pressed: true,
+            modifiers,
+            ..
+        } => (None, key_event(*key, *modifiers)),
+        _ => (None, None),
+    };
+    let text = text.into_iter().flat_map(|text| {
+        text.chars().map(|c| match c {
+            '\n' | '\r' => KeyEvent::from(KeyCode::Enter),
+            '\t' => KeyEvent::from(KeyCode::Tab),
+            c => KeyEvent::from(KeyCode::Char(c)),
+        })
+    });
+    text.chain(key)
+}
+
+fn ctrl_key(c: char) -> KeyEvent {
+    let ctrl = KeyModifiers {
+        ctrl: true,
+        ..KeyModifiers::NONE
+    };
+    KeyEvent::new(KeyCode::Char(c), ctrl)
+}
+
+fn key_event(key: egui::Key, modifiers: Modifiers) -> Option<KeyEvent> {
+    use egui::Key;
+    let code = match key {
+        Key::Enter => KeyCode::Enter,
+        Key::Backspace => KeyCode::Backspace,
+        Key::Tab if modifiers.shift => KeyCode::BackTab,
+        Key::Tab => KeyCode::Tab,
+        Key::Escape => KeyCode::Esc,
+        Key::ArrowLeft => KeyCode::Left,
+        Key::ArrowRight => KeyCode::Right,
+        Key::ArrowUp => KeyCode::Up,
+        Key::ArrowDown => KeyCode::Down,
+        Key::Home => KeyCode::Home,
+        Key::End => KeyCode::End,
+        Key::PageUp => KeyCode::PageUp,
+        Key::PageDown => KeyCode::PageDown,
+        Key::Insert => KeyCode::Insert,
+        Key::Delete => KeyCode::Delete,
+        Key::F1 => KeyCode::F(1),
+        Key::F2 => KeyCode::F(2),
+        Key::F3 => KeyCode::F(3),
+        Key::F4 => KeyCode::F(4),
+        Key::F5 => KeyCode::F(5),
+        Key::F6 => KeyCode::F(6),
+        Key::F7 => KeyCode::F(7),
+        Key::F8 => KeyCode::F(8),
+        Key::F9 => KeyCode::F(9),
+        Key::F10 => KeyCode::F(10),
+        Key::F11 => KeyCode::F(11),
+        Key::F12 => KeyCode::F(12),
+        // Text is sent for the other keys, unless Ctrl or Alt is held.
+        key if modifiers.ctrl || modifiers.alt => {
+            let mut chars = key.symbol_or_name().chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c.to_ascii_lowercase()),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some(KeyEvent::new(code, key_modifiers(modifiers)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn keys(event: Event) -> Vec<KeyEvent> {
+        key_events(&event).collect()
+    }
+
+    fn key(key: egui::Key, modifiers: Modifiers) -> Event {
+        Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        }
+    }
+
+    #[test]
+    fn converts_key_events() {
+        assert_eq!(
+            keys(Event::Text("é!".to_string())),
+            [KeyCode::Char('é').into(), KeyCode::Char('!').into()]
+        );
+        assert_eq!(
+            keys(Event::Paste("a\nb".to_string())),
+            [
+                KeyCode::Char('a').into(),
+                KeyCode::Enter.into(),
+                KeyCode::Char('b').into()
+            ]
+        );
+        assert_eq!(
+            keys(key(egui::Key::ArrowUp, Modifiers::NONE)),
+            [KeyCode::Up.into()]
+        );
+        assert_eq!(
+            keys(key(egui::Key::Tab, Modifiers::SHIFT))[0].code,
+            KeyCode::BackTab
+        );
+        // Typed letters come as text.
+        assert_eq!(keys(key(egui::Key::A, Modifiers::SHIFT)), []);
+        let ctrl_c = keys(key(egui::Key::C, Modifiers::CTRL));
+        assert_eq!(ctrl_c[0].code, KeyCode::Char('c'));
+        assert!(ctrl_c[0].modifiers.ctrl);
+        // What egui-winit sends instead of Ctrl+C and Ctrl+X key events.
+        assert_eq!(keys(Event::Copy), [ctrl_c[0]]);
+        let ctrl_x = keys(Event::Cut);
+        assert_eq!(ctrl_x[0].code, KeyCode::Char('x'));
+        assert!(ctrl_x[0].modifiers.ctrl);
+        assert_eq!(
+            keys(key(egui::Key::Num1, Modifiers::ALT))[0].code,
+            KeyCode::Char('1')
+        );
+        let release = Event::Key {
+            key: egui::Key::Enter,
+            physical_key: None,
+            pressed: false,
+            repeat: false,
+            modifiers: Modifiers::NONE,
+        };
+        assert_eq!(keys(release), []);
+    }
+}
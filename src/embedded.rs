@@ -0,0 +1,178 @@
+//! [`embedded-graphics`](https://github.com/embedded-graphics/embedded-graphics) integration,
+//! for driving small LCD and OLED displays with ratatui.
+
+use core::convert::Infallible;
+
+use embedded_graphics_core::Pixel;
+use embedded_graphics_core::draw_target::DrawTarget;
+use embedded_graphics_core::geometry::{Dimensions, OriginDimensions, Point, Size};
+use embedded_graphics_core::pixelcolor::{Rgb888, RgbColor};
+use embedded_graphics_core::primitives::Rectangle;
+
+use crate::pixmap::{PixelRect, RgbPixmap};
+use crate::soft_backend::SoftBackend;
+
+impl OriginDimensions for RgbPixmap {
+    fn size(&self) -> Size {
+        Size::new(self.width() as u32, self.height() as u32)
+    }
+}
+
+/// Lets embedded-graphics primitives be drawn over the rendered terminal.
+///
+/// Pixels outside of the pixmap are ignored. Drawing here doesn't mark anything dirty, draw on
+/// the [`SoftBackend`] instead for [`SoftBackend::flush_dirty_to`] to pick the pixels up.
+impl DrawTarget for RgbPixmap {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y))
+                && x < self.width()
+                && y < self.height()
+            {
+                self.put_pixel(x, y, [color.r(), color.g(), color.b()]);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Dimensions for SoftBackend {
+    fn bounding_box(&self) -> Rectangle {
+        self.rgb_pixmap.bounding_box()
+    }
+}
+
+/// Draws embedded-graphics primitives over the rendered terminal, marking the cells they touch
+/// dirty so [`SoftBackend::flush_dirty_to`] sends them.
+///
+/// Pixels outside of the pixmap are ignored. Cells redrawn by the next frame cover the pixels.
+///
+/// # Examples
+/// ```rust
+/// use embedded_graphics::mock_display::MockDisplay;
+/// use embedded_graphics::pixelcolor::{BinaryColor, Rgb888};
+/// use embedded_graphics::prelude::*;
+/// use embedded_graphics::primitives::{Line, PrimitiveStyle};
+/// use soft_ratatui::{BitmapFont, SoftBackend};
+///
+/// let mut backend = SoftBackend::new_with_bitmap_font(4, 2, BitmapFont::FIXED_8X13);
+/// backend.clear_dirty();
+/// Line::new(Point::new(0, 20), Point::new(9, 20))
+///     .into_styled(PrimitiveStyle::with_stroke(Rgb888::WHITE, 1))
+///     .draw(&mut backend)
+///     .unwrap();
+///
+/// // The two cells under the line.
+/// let mut display = MockDisplay::<BinaryColor>::new();
+/// backend.flush_dirty_to(&mut display).unwrap();
+/// assert_eq!(display.affected_area().size, Size::new(16, 13));
+/// ```
+impl DrawTarget for SoftBackend {
+    type Color = Rgb888;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let (Ok(x), Ok(y)) = (usize::try_from(point.x), usize::try_from(point.y))
+                && x < self.rgb_pixmap.width()
+                && y < self.rgb_pixmap.height()
+            {
+                self.rgb_pixmap
+                    .put_pixel(x, y, [color.r(), color.g(), color.b()]);
+                self.mark_dirty((x / self.char_width) as u16, (y / self.char_height) as u16);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RgbPixmap {
+    /// Copies a region of the pixmap, clipped to it, to an embedded-graphics [`DrawTarget`] at
+    /// the same position, converting to the color type of the target (e.g. `Rgb565`, `Gray8` or
+    /// `BinaryColor`).
+    pub fn draw_region_to<D>(&self, region: PixelRect, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget,
+        D::Color: From<Rgb888>,
+    {
+        // Clipped to the pixmap.
+        let x_end = (region.x + region.width).min(self.width());
+        let y_end = (region.y + region.height).min(self.height());
+        if region.x >= x_end || region.y >= y_end {
+            return Ok(());
+        }
+        let region = PixelRect {
+            x: region.x,
+            y: region.y,
+            width: x_end - region.x,
+            height: y_end - region.y,
+        };
+        let area = Rectangle::new(
+            Point::new(region.x as i32, region.y as i32),
+            Size::new(region.width as u32, region.height as u32),
+        );
+        let colors = (region.y..region.y + region.height).flat_map(move |y| {
+            (region.x..region.x + region.width).map(move |x| {
+                let [r, g, b] = self.get_pixel(x, y);
+                D::Color::from(Rgb888::new(r, g, b))
+            })
+        });
+        target.fill_contiguous(&area, colors)
+    }
+}
+
+impl SoftBackend {
+    /// Copies the whole rendered frame to an embedded-graphics [`DrawTarget`].
+    pub fn draw_to<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget,
+        D::Color: From<Rgb888>,
+    {
+        self.rgb_pixmap
+            .draw_region_to(self.rgb_pixmap.bounds(), target)?;
+        self.clear_dirty();
+        Ok(())
+    }
+
+    /// Copies only the regions redrawn since the last flush to an embedded-graphics
+    /// [`DrawTarget`], which keeps the transfer to SPI displays small.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use embedded_graphics::mock_display::MockDisplay;
+    /// use embedded_graphics::pixelcolor::BinaryColor;
+    /// use ratatui::Terminal;
+    /// use ratatui::style::Style;
+    /// use soft_ratatui::{BitmapFont, SoftBackend};
+    ///
+    /// let backend = SoftBackend::new_with_bitmap_font(4, 2, BitmapFont::FIXED_8X13);
+    /// let mut terminal = Terminal::new(backend).unwrap();
+    /// terminal
+    ///     .draw(|frame| frame.buffer_mut().set_string(0, 0, "Hi", Style::new()))
+    ///     .unwrap();
+    ///
+    /// let mut display = MockDisplay::<BinaryColor>::new();
+    /// terminal.backend_mut().flush_dirty_to(&mut display).unwrap();
+    /// assert_eq!(display.affected_area().size.width, 32);
+    /// ```
+    pub fn flush_dirty_to<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget,
+        D::Color: From<Rgb888>,
+    {
+        for region in self.dirty_regions() {
+            self.rgb_pixmap.draw_region_to(region, target)?;
+        }
+        self.clear_dirty();
+        Ok(())
+    }
+}
@@ -0,0 +1,374 @@
+//! Output to a Linux framebuffer device such as `/dev/fb0`, for kiosks and single board
+//! computers running without X or Wayland.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, ErrorKind};
+use std::os::fd::AsRawFd;
+use std::path::Path;
+
+use crate::pixmap::{PixelRect, RgbPixmap};
+use crate::soft_backend::SoftBackend;
+
+const FBIOGET_VSCREENINFO: u32 = 0x4600;
+const FBIOGET_FSCREENINFO: u32 = 0x4602;
+
+/// `struct fb_bitfield` of `linux/fb.h`.
+#[repr(C)]
+#[derive(Default)]
+struct FbBitfield {
+    offset: u32,
+    length: u32,
+    msb_right: u32,
+}
+
+/// `struct fb_var_screeninfo` of `linux/fb.h`.
+#[repr(C)]
+#[derive(Default)]
+struct FbVarScreeninfo {
+    xres: u32,
+    yres: u32,
+    xres_virtual: u32,
+    yres_virtual: u32,
+    xoffset: u32,
+    yoffset: u32,
+    bits_per_pixel: u32,
+    grayscale: u32,
+    red: FbBitfield,
+    green: FbBitfield,
+    blue: FbBitfield,
+    transp: FbBitfield,
+    nonstd: u32,
+    activate: u32,
+    height: u32,
+    width: u32,
+    accel_flags: u32,
+    timings: [u32; 13],
+    colorspace: u32,
+    reserved: [u32; 4],
+}
+
+/// `struct fb_fix_screeninfo` of `linux/fb.h`.
+#[repr(C)]
+#[derive(Default)]
+struct FbFixScreeninfo {
+    id: [u8; 16],
+    smem_start: libc::c_ulong,
+    smem_len: u32,
+    kind: u32,
+    type_aux: u32,
+    visual: u32,
+    xpanstep: u16,
+    ypanstep: u16,
+    ywrapstep: u16,
+    line_length: u32,
+    mmio_start: libc::c_ulong,
+    mmio_len: u32,
+    accel: u32,
+    capabilities: u16,
+    reserved: [u16; 2],
+}
+
+/// The bit offset and bit length of a color channel in a pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Channel {
+    pub offset: u32,
+    pub length: u32,
+}
+
+impl Channel {
+    /// Places an 8 bit value in the channel, which must fit in 32 bits.
+    fn pack(&self, value: u8) -> u32 {
+        let value = value as u32;
+        let scaled = match self.length {
+            0 => 0,
+            1..8 => value >> (8 - self.length),
+            _ => value << (self.length - 8),
+        };
+        scaled << self.offset
+    }
+}
+
+/// The geometry and pixel format of a framebuffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FramebufferLayout {
+    /// The visible width in pixels.
+    pub width: u32,
+    /// The visible height in pixels.
+    pub height: u32,
+    /// 16, 24 or 32.
+    pub bits_per_pixel: u32,
+    /// The length of a row in bytes, including padding.
+    pub line_length: u32,
+    pub red: Channel,
+    pub green: Channel,
+    pub blue: Channel,
+}
+
+impl FramebufferLayout {
+    /// The 32 bits per pixel `0x00RRGGBB` layout of most framebuffers.
+    pub const fn xrgb8888(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            bits_per_pixel: 32,
+            line_length: width * 4,
+            red: Channel {
+                offset: 16,
+                length: 8,
+            },
+            green: Channel {
+                offset: 8,
+                length: 8,
+            },
+            blue: Channel {
+                offset: 0,
+                length: 8,
+            },
+        }
+    }
+
+    /// The 16 bits per pixel layout of small displays.
+    pub const fn rgb565(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            bits_per_pixel: 16,
+            line_length: width * 2,
+            red: Channel {
+                offset: 11,
+                length: 5,
+            },
+            green: Channel {
+                offset: 5,
+                length: 6,
+            },
+            blue: Channel {
+                offset: 0,
+                length: 5,
+            },
+        }
+    }
+
+    fn bytes_per_pixel(&self) -> usize {
+        self.bits_per_pixel as usize / 8
+    }
+
+    /// The size of the visible part of the framebuffer in bytes.
+    fn len(&self) -> usize {
+        self.line_length as usize * self.height as usize
+    }
+}
+
+/// A framebuffer mapped into memory, showing the pixmap of a [`SoftBackend`] at its top left.
+///
+/// # Examples
+/// ```rust
+/// use ratatui::Terminal;
+/// use ratatui::widgets::Paragraph;
+/// use soft_ratatui::{BitmapFont, Framebuffer, FramebufferLayout, SoftBackend};
+///
+/// // A plain file stands in for /dev/fb0.
+/// let path = std::env::temp_dir().join(format!("soft_ratatui_fb_{}", std::process::id()));
+/// # struct RemoveOnDrop<'a>(&'a std::path::Path);
+/// # impl Drop for RemoveOnDrop<'_> {
+/// #     fn drop(&mut self) {
+/// #         let _ = std::fs::remove_file(self.0);
+/// #     }
+/// # }
+/// # let _remove = RemoveOnDrop(&path);
+/// let layout = FramebufferLayout::xrgb8888(320, 240);
+/// let mut framebuffer = Framebuffer::open_with_layout(&path, layout).unwrap();
+///
+/// let backend = SoftBackend::new_with_bitmap_font(40, 18, BitmapFont::FIXED_8X13);
+/// let mut terminal = Terminal::new(backend).unwrap();
+/// terminal
+///     .draw(|frame| frame.render_widget(Paragraph::new("Hello"), frame.area()))
+///     .unwrap();
+/// framebuffer.present(terminal.backend_mut());
+/// drop(framebuffer);
+///
+/// let data = std::fs::read(&path).unwrap();
+/// assert_eq!(data.len(), 320 * 240 * 4);
+/// let [r, g, b] = terminal.backend().rgb_pixmap.get_pixel(0, 0);
+/// assert_eq!(data[..4], [b, g, r, 0]);
+/// ```
+pub struct Framebuffer {
+    // Kept open while mapped.
+    _file: File,
+    map: *mut u8,
+    /// The length of the mapping, from the start of the framebuffer memory.
+    map_len: usize,
+    /// The position of the visible area in the mapping, in bytes.
+    offset: usize,
+    layout: FramebufferLayout,
+}
+
+impl Framebuffer {
+    /// Opens a framebuffer device, such as `/dev/fb0`, querying its geometry and pixel format.
+    /// The pixmap is shown at the top left of the visible area, which panning may have moved
+    /// within the framebuffer memory.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let mut var = FbVarScreeninfo::default();
+        let mut fix = FbFixScreeninfo::default();
+        // SAFETY: The ioctls fill in structs with the layout of the kernel headers.
+        unsafe {
+            if libc::ioctl(file.as_raw_fd(), FBIOGET_VSCREENINFO as _, &mut var) != 0
+                || libc::ioctl(file.as_raw_fd(), FBIOGET_FSCREENINFO as _, &mut fix) != 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        let channel = |bitfield: &FbBitfield| Channel {
+            offset: bitfield.offset,
+            length: bitfield.length,
+        };
+        let layout = FramebufferLayout {
+            width: var.xres,
+            height: var.yres,
+            bits_per_pixel: var.bits_per_pixel,
+            line_length: fix.line_length,
+            red: channel(&var.red),
+            green: channel(&var.green),
+            blue: channel(&var.blue),
+        };
+        let offset = var.yoffset as usize * fix.line_length as usize
+            + var.xoffset as usize * layout.bytes_per_pixel();
+        if offset + layout.len() > fix.smem_len as usize {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "the visible area extends past the framebuffer memory",
+            ));
+        }
+        Self::map(file, layout, offset)
+    }
+
+    /// Opens any file as a framebuffer of the given layout, e.g. a plain file to test without a
+    /// display. Regular files are extended to the size of the framebuffer.
+    pub fn open_with_layout(path: impl AsRef<Path>, layout: FramebufferLayout) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let metadata = file.metadata()?;
+        if metadata.is_file() && metadata.len() < layout.len() as u64 {
+            file.set_len(layout.len() as u64)?;
+        }
+        Self::map(file, layout, 0)
+    }
+
+    /// Maps the framebuffer memory up to the end of the visible area, which starts `offset`
+    /// bytes in.
+    fn map(file: File, layout: FramebufferLayout, offset: usize) -> io::Result<Self> {
+        if !matches!(layout.bits_per_pixel, 16 | 24 | 32) {
+            return Err(io::Error::new(
+                ErrorKind::Unsupported,
+                "only 16, 24 and 32 bits per pixel framebuffers are supported",
+            ));
+        }
+        if (layout.line_length as usize) < layout.width as usize * layout.bytes_per_pixel() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "framebuffer rows are shorter than their width",
+            ));
+        }
+        if layout.len() == 0 {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "empty framebuffer"));
+        }
+        let fits = |channel: Channel| {
+            channel
+                .offset
+                .checked_add(channel.length)
+                .is_some_and(|end| end <= layout.bits_per_pixel)
+        };
+        if ![layout.red, layout.green, layout.blue]
+            .into_iter()
+            .all(fits)
+        {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "color channels extend past the pixel",
+            ));
+        }
+        let map_len = offset + layout.len();
+        // SAFETY: Maps a shared writable view of the open file, which is checked below.
+        let map = unsafe {
+            libc::mmap(
+                core::ptr::null_mut(),
+                map_len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if map == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self {
+            _file: file,
+            map: map.cast(),
+            map_len,
+            offset,
+            layout,
+        })
+    }
+
+    /// Returns the geometry and pixel format of the framebuffer.
+    pub fn layout(&self) -> &FramebufferLayout {
+        &self.layout
+    }
+
+    /// Writes a rectangle of the pixmap to the same place on the framebuffer, clipped to both.
+    pub fn write_region(&mut self, pixmap: &RgbPixmap, rect: PixelRect) {
+        let layout = self.layout;
+        let bytes_per_pixel = layout.bytes_per_pixel();
+        let x_end = (rect.x + rect.width)
+            .min(pixmap.width())
+            .min(layout.width as usize);
+        let y_end = (rect.y + rect.height)
+            .min(pixmap.height())
+            .min(layout.height as usize);
+        if rect.x >= x_end {
+            return;
+        }
+        // SAFETY: The mapping is `map_len` bytes long, the visible area ending at its end, and
+        // lives as long as `self`.
+        let framebuffer =
+            unsafe { core::slice::from_raw_parts_mut(self.map.add(self.offset), layout.len()) };
+        for y in rect.y..y_end {
+            let source =
+                &pixmap.data()[3 * (y * pixmap.width() + rect.x)..][..3 * (x_end - rect.x)];
+            let start = y * layout.line_length as usize + rect.x * bytes_per_pixel;
+            let row = &mut framebuffer[start..][..(x_end - rect.x) * bytes_per_pixel];
+            for (pixel, rgb) in row
+                .chunks_exact_mut(bytes_per_pixel)
+                .zip(source.chunks_exact(3))
+            {
+                let value =
+                    layout.red.pack(rgb[0]) | layout.green.pack(rgb[1]) | layout.blue.pack(rgb[2]);
+                pixel.copy_from_slice(&value.to_le_bytes()[..bytes_per_pixel]);
+            }
+        }
+    }
+
+    /// Writes the regions of the backend's pixmap redrawn since the last call to the
+    /// framebuffer, and forgets them.
+    pub fn present(&mut self, backend: &mut SoftBackend) {
+        for region in backend.dirty_regions() {
+            self.write_region(&backend.rgb_pixmap, region);
+        }
+        backend.clear_dirty();
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        // SAFETY: Unmaps the mapping made in `map`, which is not used afterwards.
+        unsafe {
+            libc::munmap(self.map.cast(), self.map_len);
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! HTML export of the terminal frame as selectable text, e.g. for web reports and issue
+//! trackers.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use ratatui::style::{Color, Modifier};
+use unicode_width::UnicodeWidthStr;
+
+use crate::colors::rat_to_rgb;
+use crate::image;
+use crate::soft_backend::{SoftBackend, cell_colors};
+use crate::svg::{base_background, escape_xml, hex};
+
+/// The style sheet of an HTML document, making blinking text blink.
+const BLINK_STYLE: &str = "\
+@keyframes soft-ratatui-blink { 50% { color: transparent; } }
+.slow-blink { animation: soft-ratatui-blink 1.6s step-end infinite; }
+.rapid-blink { animation: soft-ratatui-blink 0.4s step-end infinite; }
+";
+
+/// The attributes of a run of cells that share a `<span>`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct SpanStyle {
+    fg: [u8; 3],
+    bg: [u8; 3],
+    modifier: Modifier,
+}
+
+impl SpanStyle {
+    const MODIFIERS: Modifier = Modifier::BOLD
+        .union(Modifier::ITALIC)
+        .union(Modifier::UNDERLINED)
+        .union(Modifier::CROSSED_OUT)
+        .union(Modifier::SLOW_BLINK)
+        .union(Modifier::RAPID_BLINK);
+
+    /// Writes the opening tag of the span, or nothing if the style is the default one.
+    fn open(&self, out: &mut String, default: &SpanStyle, blink: bool) -> bool {
+        let mut style = String::new();
+        if self.fg != default.fg {
+            let _ = write!(style, "color: {};", hex(self.fg));
+        }
+        if self.bg != default.bg {
+            let _ = write!(style, "background-color: {};", hex(self.bg));
+        }
+        if self.modifier.contains(Modifier::BOLD) {
+            style.push_str("font-weight: bold;");
+        }
+        if self.modifier.contains(Modifier::ITALIC) {
+            style.push_str("font-style: italic;");
+        }
+        match (
+            self.modifier.contains(Modifier::UNDERLINED),
+            self.modifier.contains(Modifier::CROSSED_OUT),
+        ) {
+            (true, true) => style.push_str("text-decoration: underline line-through;"),
+            (true, false) => style.push_str("text-decoration: underline;"),
+            (false, true) => style.push_str("text-decoration: line-through;"),
+            (false, false) => {}
+        }
+        let class = if !blink {
+            None
+        } else if self.modifier.contains(Modifier::RAPID_BLINK) {
+            Some("rapid-blink")
+        } else if self.modifier.contains(Modifier::SLOW_BLINK) {
+            Some("slow-blink")
+        } else {
+            None
+        };
+        if style.is_empty() && class.is_none() {
+            return false;
+        }
+        out.push_str("<span");
+        if let Some(class) = class {
+            let _ = write!(out, " class=\"{class}\"");
+        }
+        if !style.is_empty() {
+            let _ = write!(out, " style=\"{style}\"");
+        }
+        out.push('>');
+        true
+    }
+}
+
+impl SoftBackend {
+    /// Exports the current buffer as a self-contained HTML document, with the text in a `<pre>`.
+    ///
+    /// (to-html) -> String
+    ///
+    /// Each run of cells with the same style becomes a `<span>`, colored as in the pixmap,
+    /// including `Reset` colors and reversed, hidden and dim text. Bold, italic, underlined and
+    /// crossed out text are styled with CSS, and blinking text blinks with a CSS animation.
+    /// Images are left out.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ratatui::Terminal;
+    /// use ratatui::style::Stylize;
+    /// use ratatui::widgets::Paragraph;
+    /// use soft_ratatui::{BitmapFont, SoftBackend};
+    ///
+    /// let backend = SoftBackend::new_with_bitmap_font(20, 1, BitmapFont::FIXED_8X13);
+    /// let mut terminal = Terminal::new(backend).unwrap();
+    /// terminal
+    ///     .draw(|frame| frame.render_widget(Paragraph::new("<b>".bold()), frame.area()))
+    ///     .unwrap();
+    /// let html = terminal.backend().to_html();
+    /// assert!(html.starts_with("<!DOCTYPE html>"));
+    /// assert!(html.contains("<span style=\"font-weight: bold;\">&lt;b&gt;</span>"));
+    /// ```
+    pub fn to_html(&self) -> String {
+        let mut out =
+            String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>\n");
+        out.push_str(BLINK_STYLE);
+        out.push_str("</style>\n</head>\n<body>\n");
+        self.write_html_pre(&mut out, true);
+        out.push_str("\n</body>\n</html>\n");
+        out
+    }
+
+    /// Exports the current buffer as an HTML `<pre>` fragment with inline styles only, for
+    /// hosts that put it on the clipboard as "copy as HTML".
+    ///
+    /// (copy-as-html) -> String
+    ///
+    /// The styles are the same as in [`Self::to_html`], except that blinking text doesn't blink,
+    /// since pasted HTML usually loses its style sheets.
+    pub fn copy_as_html(&self) -> String {
+        let mut out = String::new();
+        self.write_html_pre(&mut out, false);
+        out
+    }
+
+    fn write_html_pre(&self, out: &mut String, blink: bool) {
+        let area = self.buffer.area;
+        let default = SpanStyle {
+            fg: rat_to_rgb(&Color::Reset, true),
+            bg: base_background(&self.buffer),
+            modifier: Modifier::empty(),
+        };
+        let _ = write!(
+            out,
+            "<pre style=\"font-family: monospace; color: {}; background-color: {}; \
+             padding: 0.5em;\">",
+            hex(default.fg),
+            hex(default.bg)
+        );
+        for y in 0..area.height {
+            if y > 0 {
+                out.push('\n');
+            }
+            let row =
+                &self.buffer.content[y as usize * area.width as usize..][..area.width as usize];
+            let mut runs: Vec<(SpanStyle, String)> = Vec::new();
+            let mut skip = 0;
+            for cell in row {
+                // Cells covered by a wide char are not drawn.
+                if skip > 0 {
+                    skip -= 1;
+                    continue;
+                }
+                let symbol = cell.symbol();
+                let symbol =
+                    if cell.modifier.contains(Modifier::HIDDEN) || image::is_placeholder(symbol) {
+                        " "
+                    } else {
+                        skip = symbol.width().saturating_sub(1);
+                        symbol
+                    };
+                let (fg, bg) = cell_colors(cell);
+                let style = SpanStyle {
+                    fg,
+                    bg,
+                    modifier: cell.modifier & SpanStyle::MODIFIERS,
+                };
+                match runs.last_mut() {
+                    Some((run_style, text)) if *run_style == style => text.push_str(symbol),
+                    _ => runs.push((style, String::from(symbol))),
+                }
+            }
+            // Trailing blanks of the default style only pad the line.
+            if let Some((style, text)) = runs.last_mut()
+                && *style == default
+            {
+                text.truncate(text.trim_end_matches(' ').len());
+            }
+            for (style, text) in runs {
+                let span = style.open(out, &default, blink);
+                escape_xml(out, &text);
+                if span {
+                    out.push_str("</span>");
+                }
+            }
+        }
+        out.push_str("</pre>");
+    }
+
+    /// Writes the current buffer as an HTML document, see [`Self::to_html`].
+    #[cfg(feature = "std")]
+    pub fn write_html<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(self.to_html().as_bytes())
+    }
+}
@@ -0,0 +1,390 @@
+//! True-pixel images shown in a region of cells.
+//!
+//! Images are registered on the [`SoftBackend`] and placed with the [`ImageWidget`], which fills
+//! its area with placeholder cells. Each placeholder names the image and the position of the cell
+//! within the area, so the backend repaints its slice of the image whenever the cell is drawn,
+//! without the widget needing access to the backend.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::widgets::Widget;
+
+use crate::colors::*;
+use crate::pixmap::RgbPixmap;
+use crate::soft_backend::SoftBackend;
+
+/// The char that starts an image placeholder, the same one used by kitty's unicode placeholders.
+const PLACEHOLDER: char = '\u{10EEEE}';
+/// Placeholder values are written in base 240 with the zero width variation selectors
+/// VS17 to VS256, so the placeholder stays a single column wide.
+const DIGIT_BASE: u32 = 0xE0100;
+const DIGITS: u32 = 240;
+/// The largest value that fits two digits, which bounds image ids and placement sizes.
+const MAX_VALUE: u32 = DIGITS * DIGITS - 1;
+
+/// An RGBA image that can be shown in cells with an [`ImageWidget`].
+#[derive(Debug, Clone)]
+pub struct RgbaImage {
+    width: usize,
+    height: usize,
+    data: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Creates an image from decoded RGBA pixels, row by row.
+    ///
+    /// (from-rgba data width height) -> RgbaImage
+    ///
+    /// * data       : &[u8] - RGBA pixels of the image
+    /// * width      : usize - Width of the image in pixels
+    /// * height     : usize - Height of the image in pixels
+    pub fn from_rgba(data: &[u8], width: usize, height: usize) -> Self {
+        assert_eq!(
+            data.len(),
+            width * height * 4,
+            "RGBA data does not match image size"
+        );
+        Self {
+            width,
+            height,
+            data: data.to_vec(),
+        }
+    }
+
+    /// Returns the width of the image in pixels
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    /// Returns the height of the image in pixels
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    /// Returns the RGBA pixels of the image as a flat array
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn pixel(&self, x: usize, y: usize) -> [f32; 4] {
+        let i = (y * self.width + x) * 4;
+        let px = &self.data[i..i + 4];
+        [px[0] as f32, px[1] as f32, px[2] as f32, px[3] as f32]
+    }
+}
+
+/// A handle to an image registered with [`SoftBackend::add_image`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ImageId(u16);
+
+/// How an image is scaled to the area of its widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageFit {
+    /// Scales the image to fit inside the area, keeping its aspect ratio. The cell backgrounds
+    /// show in the uncovered bands.
+    #[default]
+    Fit,
+    /// Scales the image to cover the whole area, keeping its aspect ratio and cropping the
+    /// overflow evenly on both sides.
+    Fill,
+    /// Stretches the image to the exact area, ignoring its aspect ratio.
+    Stretch,
+}
+
+/// How image pixels are sampled when scaled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageFilter {
+    /// Picks the closest pixel, keeping pixel art sharp.
+    Nearest,
+    /// Interpolates between the four closest pixels, for smooth photos.
+    #[default]
+    Bilinear,
+}
+
+/// A widget that shows an image registered on the [`SoftBackend`] in its area.
+///
+/// The widget only replaces the symbols of its cells, so their background color shows through
+/// transparent pixels and the bands left by [`ImageFit::Fit`].
+///
+/// # Examples
+/// ```rust
+/// use ratatui::Terminal;
+/// use soft_ratatui::{BitmapFont, ImageFit, ImageWidget, RgbaImage, SoftBackend};
+///
+/// let backend = SoftBackend::new_with_bitmap_font(4, 2, BitmapFont::FIXED_8X13);
+/// let mut terminal = Terminal::new(backend).unwrap();
+/// // A single opaque red pixel, stretched over the whole terminal.
+/// let id = terminal
+///     .backend_mut()
+///     .add_image(RgbaImage::from_rgba(&[255, 0, 0, 255], 1, 1));
+/// terminal
+///     .draw(|frame| {
+///         let widget = ImageWidget::new(id).fit(ImageFit::Stretch);
+///         frame.render_widget(widget, frame.area());
+///     })
+///     .unwrap();
+/// assert_eq!(terminal.backend().rgb_pixmap.get_pixel(31, 25), [255, 0, 0]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageWidget {
+    id: ImageId,
+    fit: ImageFit,
+    filter: ImageFilter,
+}
+
+impl ImageWidget {
+    /// Creates a widget showing the image `id`, fitted with bilinear filtering.
+    pub fn new(id: ImageId) -> Self {
+        Self {
+            id,
+            fit: ImageFit::default(),
+            filter: ImageFilter::default(),
+        }
+    }
+
+    /// Sets how the image is scaled to the area.
+    pub fn fit(mut self, fit: ImageFit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Sets how the image pixels are sampled.
+    pub fn filter(mut self, filter: ImageFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+impl Widget for ImageWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.intersection(buf.area);
+        let columns = (area.width as u32).min(MAX_VALUE) as u16;
+        let rows = (area.height as u32).min(MAX_VALUE) as u16;
+        for row in 0..rows {
+            for column in 0..columns {
+                let placement = Placement {
+                    id: self.id,
+                    fit: self.fit,
+                    filter: self.filter,
+                    column,
+                    row,
+                    columns,
+                    rows,
+                };
+                let mut symbol = [0u8; 48];
+                let symbol = placement.encode(&mut symbol);
+                buf[(area.x + column, area.y + row)].set_symbol(symbol);
+            }
+        }
+    }
+}
+
+/// The image slice a placeholder cell shows, decoded from its symbol.
+#[derive(Debug, Clone, Copy)]
+struct Placement {
+    id: ImageId,
+    fit: ImageFit,
+    filter: ImageFilter,
+    column: u16,
+    row: u16,
+    columns: u16,
+    rows: u16,
+}
+
+impl Placement {
+    fn encode<'a>(&self, out: &'a mut [u8; 48]) -> &'a str {
+        let flags = self.fit as u32 * 2 + self.filter as u32;
+        let values = [
+            self.id.0 as u32,
+            self.column as u32,
+            self.row as u32,
+            self.columns as u32,
+            self.rows as u32,
+        ];
+        let mut len = PLACEHOLDER.encode_utf8(out).len();
+        let digits = values
+            .iter()
+            .flat_map(|value| [value / DIGITS, value % DIGITS])
+            .chain([flags]);
+        for digit in digits {
+            let c = char::from_u32(DIGIT_BASE + digit).unwrap();
+            len += c.encode_utf8(&mut out[len..]).len();
+        }
+        core::str::from_utf8(&out[..len]).unwrap()
+    }
+
+    fn decode(symbol: &str) -> Option<Self> {
+        let mut chars = symbol.chars();
+        if chars.next()? != PLACEHOLDER {
+            return None;
+        }
+        let mut digit = || {
+            let value = (chars.next()? as u32).checked_sub(DIGIT_BASE)?;
+            (value < DIGITS).then_some(value)
+        };
+        let mut value = || Some((digit()? * DIGITS + digit()?) as u16);
+        let (id, column, row, columns, rows) = (value()?, value()?, value()?, value()?, value()?);
+        let flags = digit()?;
+        let fit = match flags / 2 {
+            0 => ImageFit::Fit,
+            1 => ImageFit::Fill,
+            _ => ImageFit::Stretch,
+        };
+        let filter = match flags % 2 {
+            0 => ImageFilter::Nearest,
+            _ => ImageFilter::Bilinear,
+        };
+        Some(Self {
+            id: ImageId(id),
+            fit,
+            filter,
+            column,
+            row,
+            columns,
+            rows,
+        })
+    }
+}
+
+/// Returns true if `symbol` was written by an [`ImageWidget`].
+pub(crate) fn is_placeholder(symbol: &str) -> bool {
+    symbol.starts_with(PLACEHOLDER)
+}
+
+/// Rounds toward negative infinity, as `f32::floor` is not available without `std`.
+fn floor(value: f32) -> f32 {
+    let truncated = value as i32 as f32;
+    if truncated > value {
+        truncated - 1.0
+    } else {
+        truncated
+    }
+}
+
+/// Composites the slice of `image` shown by the placeholder `symbol` over the cell at
+/// (begin_x, begin_y), whose background is already drawn. Returns false if `symbol` is not a
+/// placeholder.
+pub(crate) fn draw_placeholder(
+    pixmap: &mut RgbPixmap,
+    begin_x: usize,
+    begin_y: usize,
+    cell_size: (usize, usize),
+    symbol: &str,
+    images: &BTreeMap<ImageId, RgbaImage>,
+) -> bool {
+    let Some(placement) = Placement::decode(symbol) else {
+        return false;
+    };
+    let Some(image) = images.get(&placement.id) else {
+        return true;
+    };
+    if image.width == 0 || image.height == 0 {
+        return true;
+    }
+
+    let (char_width, char_height) = cell_size;
+    let area_width = (placement.columns as usize * char_width) as f32;
+    let area_height = (placement.rows as usize * char_height) as f32;
+    let scale_x = area_width / image.width as f32;
+    let scale_y = area_height / image.height as f32;
+    let (scale_x, scale_y) = match placement.fit {
+        ImageFit::Fit => (scale_x.min(scale_y), scale_x.min(scale_y)),
+        ImageFit::Fill => (scale_x.max(scale_y), scale_x.max(scale_y)),
+        ImageFit::Stretch => (scale_x, scale_y),
+    };
+    let offset_x = (area_width - image.width as f32 * scale_x) / 2.0;
+    let offset_y = (area_height - image.height as f32 * scale_y) / 2.0;
+
+    let max_x = (image.width - 1) as f32;
+    let max_y = (image.height - 1) as f32;
+    for y in 0..char_height {
+        let area_y = (placement.row as usize * char_height + y) as f32 + 0.5;
+        // The center of the pixel in image coordinates.
+        let image_y = (area_y - offset_y) / scale_y;
+        if image_y < 0.0 || image_y >= image.height as f32 {
+            continue;
+        }
+        for x in 0..char_width {
+            let area_x = (placement.column as usize * char_width + x) as f32 + 0.5;
+            let image_x = (area_x - offset_x) / scale_x;
+            if image_x < 0.0 || image_x >= image.width as f32 {
+                continue;
+            }
+
+            let color = match placement.filter {
+                ImageFilter::Nearest => image.pixel(image_x as usize, image_y as usize),
+                ImageFilter::Bilinear => {
+                    let sample_x = (image_x - 0.5).clamp(0.0, max_x);
+                    let sample_y = (image_y - 0.5).clamp(0.0, max_y);
+                    let (x0, y0) = (floor(sample_x), floor(sample_y));
+                    let (tx, ty) = (sample_x - x0, sample_y - y0);
+                    let (x0, y0) = (x0 as usize, y0 as usize);
+                    let x1 = (x0 + 1).min(image.width - 1);
+                    let y1 = (y0 + 1).min(image.height - 1);
+                    let (a, b) = (image.pixel(x0, y0), image.pixel(x1, y0));
+                    let (c, d) = (image.pixel(x0, y1), image.pixel(x1, y1));
+                    core::array::from_fn(|i| {
+                        let top = a[i] + (b[i] - a[i]) * tx;
+                        let bottom = c[i] + (d[i] - c[i]) * tx;
+                        top + (bottom - top) * ty
+                    })
+                }
+            };
+            let alpha = (color[3] + 0.5) as u8;
+            if alpha == 0 {
+                continue;
+            }
+            let (px, py) = (begin_x + x, begin_y + y);
+            let [r, g, b] = pixmap.get_pixel(px, py);
+            let fg = [
+                (color[0] + 0.5) as u8,
+                (color[1] + 0.5) as u8,
+                (color[2] + 0.5) as u8,
+                alpha,
+            ];
+            pixmap.put_pixel(px, py, blend_rgba(fg, [r, g, b, 255]));
+        }
+    }
+    true
+}
+
+impl SoftBackend {
+    /// Registers an image to be shown with an [`ImageWidget`], returning its id.
+    pub fn add_image(&mut self, image: RgbaImage) -> ImageId {
+        let id = (0..=MAX_VALUE as u16)
+            .map(ImageId)
+            .find(|id| !self.images.contains_key(id))
+            .expect("Too many images registered");
+        self.images.insert(id, image);
+        id
+    }
+
+    /// Replaces the pixels of a registered image and repaints the cells showing it.
+    pub fn replace_image(&mut self, id: ImageId, image: RgbaImage) {
+        self.images.insert(id, image);
+        self.redraw_image(id);
+    }
+
+    /// Unregisters an image, returning it. Cells still showing it keep their background.
+    pub fn remove_image(&mut self, id: ImageId) -> Option<RgbaImage> {
+        let image = self.images.remove(&id);
+        self.redraw_image(id);
+        image
+    }
+
+    /// Returns a registered image.
+    pub fn image(&self, id: ImageId) -> Option<&RgbaImage> {
+        self.images.get(&id)
+    }
+
+    fn redraw_image(&mut self, id: ImageId) {
+        for index in 0..self.buffer.content.len() {
+            let symbol = self.buffer.content[index].symbol();
+            if Placement::decode(symbol).is_some_and(|placement| placement.id == id) {
+                let (x, y) = self.buffer.pos_of(index);
+                self.draw_cell(x, y);
+            }
+        }
+    }
+}
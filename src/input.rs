@@ -0,0 +1,170 @@
+//! Input events shared by the integrations, independent of any windowing or terminal library.
+
+/// A key on the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyCode {
+    /// A key that types a char. Shifted chars are given as typed, e.g. `'A'` rather than `'a'`
+    /// with shift.
+    Char(char),
+    Enter,
+    Backspace,
+    Tab,
+    /// Shift + Tab.
+    BackTab,
+    Esc,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    /// A function key, from F1 to F12.
+    F(u8),
+}
+
+/// The modifier keys held during a key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct KeyModifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl KeyModifiers {
+    /// No modifier held.
+    pub const NONE: Self = Self {
+        shift: false,
+        ctrl: false,
+        alt: false,
+    };
+
+    /// Returns true if no modifier is held.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::NONE
+    }
+}
+
+/// A key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyEvent {
+    /// Creates a key press with the given modifiers.
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+}
+
+impl KeyEvent {
+    /// Converts a key press of a web page, from the `key`, `shiftKey`, `ctrlKey` and `altKey`
+    /// properties of a DOM `KeyboardEvent`. Returns `None` for keys with no terminal
+    /// equivalent, such as modifier keys on their own.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use soft_ratatui::{KeyCode, KeyEvent};
+    ///
+    /// let event = KeyEvent::from_dom("ArrowUp", false, true, false).unwrap();
+    /// assert_eq!(event.code, KeyCode::Up);
+    /// assert!(event.modifiers.ctrl);
+    /// assert_eq!(KeyEvent::from_dom("Tab", true, false, false).unwrap().code, KeyCode::BackTab);
+    /// assert_eq!(KeyEvent::from_dom("é", false, false, false).unwrap().code, KeyCode::Char('é'));
+    /// assert_eq!(KeyEvent::from_dom("Shift", true, false, false), None);
+    /// ```
+    pub fn from_dom(key: &str, shift: bool, ctrl: bool, alt: bool) -> Option<Self> {
+        let code = match key {
+            "Enter" => KeyCode::Enter,
+            "Backspace" => KeyCode::Backspace,
+            "Tab" if shift => KeyCode::BackTab,
+            "Tab" => KeyCode::Tab,
+            "Escape" => KeyCode::Esc,
+            "ArrowLeft" => KeyCode::Left,
+            "ArrowRight" => KeyCode::Right,
+            "ArrowUp" => KeyCode::Up,
+            "ArrowDown" => KeyCode::Down,
+            "Home" => KeyCode::Home,
+            "End" => KeyCode::End,
+            "PageUp" => KeyCode::PageUp,
+            "PageDown" => KeyCode::PageDown,
+            "Insert" => KeyCode::Insert,
+            "Delete" => KeyCode::Delete,
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    (Some('F'), Some(_)) => match key[1..].parse() {
+                        Ok(n @ 1..=12) => KeyCode::F(n),
+                        _ => return None,
+                    },
+                    _ => return None,
+                }
+            }
+        };
+        Some(Self::new(code, KeyModifiers { shift, ctrl, alt }))
+    }
+}
+
+impl From<KeyCode> for KeyEvent {
+    fn from(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+}
+
+/// A mouse button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+}
+
+impl MouseButton {
+    /// Converts the `button` property of a DOM `MouseEvent`: 0 for the main button, 1 for the
+    /// middle one and 2 for the secondary one. Returns `None` for other buttons.
+    pub fn from_dom(button: i16) -> Option<Self> {
+        match button {
+            0 => Some(Self::Left),
+            1 => Some(Self::Middle),
+            2 => Some(Self::Right),
+            _ => None,
+        }
+    }
+}
+
+/// What the mouse did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseEventKind {
+    Down(MouseButton),
+    Up(MouseButton),
+    /// Moved with a button held.
+    Drag(MouseButton),
+    /// Moved with no button held.
+    Moved,
+    ScrollDown,
+    ScrollUp,
+    ScrollLeft,
+    ScrollRight,
+}
+
+/// A mouse event over a cell, in the style of terminal mouse reporting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MouseEvent {
+    pub kind: MouseEventKind,
+    pub column: u16,
+    pub row: u16,
+    pub modifiers: KeyModifiers,
+}
+
+/// A key press or a mouse event, as passed to the app by the integrations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputEvent {
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+}
@@ -0,0 +1,134 @@
+//! Kitty graphics protocol encoding, for showing rendered frames in kitty, WezTerm, Ghostty and
+//! Konsole, and over SSH.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::pixmap::{PixelRect, RgbPixmap};
+
+/// The largest payload the protocol allows in a single escape sequence.
+const CHUNK_SIZE: usize = 4096;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub(crate) fn base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Options of a kitty graphics protocol transmission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct KittyOptions {
+    /// The image id, so later transmissions replace the image instead of adding a new one.
+    pub image_id: Option<u32>,
+    /// The number of columns and rows of cells the image is scaled to. Shown at its pixel size
+    /// when `None`.
+    pub cells: Option<(u16, u16)>,
+    /// Compresses the pixels with zlib, which makes flat terminal frames many times smaller.
+    /// Ignored without the `zlib` feature.
+    pub compress: bool,
+}
+
+impl RgbPixmap {
+    /// Encodes a region of the pixmap as kitty graphics protocol escape sequences, which
+    /// transmit the pixels and display them at the cursor.
+    ///
+    /// (to-kitty region options) -> String
+    ///
+    /// * region     : PixelRect    - Region of the pixmap to encode, see [`Self::bounds`]
+    /// * options    : KittyOptions - Image id, display size and compression
+    ///
+    /// The payload is split into chunks of 4096 bytes, and the terminal is asked not to reply.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use soft_ratatui::{BitmapFont, KittyOptions, SoftBackend};
+    /// let backend = SoftBackend::new_with_bitmap_font(10, 2, BitmapFont::FIXED_8X13);
+    /// let pixmap = &backend.rgb_pixmap;
+    /// let kitty = pixmap.to_kitty(pixmap.bounds(), KittyOptions::default());
+    /// assert!(kitty.starts_with("\x1b_Ga=T,f=24,s=80,v=26,q=2"));
+    ///
+    /// // Compressed with the `zlib` feature, sent as is without it.
+    /// let options = KittyOptions { compress: true, ..KittyOptions::default() };
+    /// let kitty = pixmap.to_kitty(pixmap.bounds(), options);
+    /// assert_eq!(kitty.contains(",o=z"), cfg!(feature = "zlib"));
+    /// ```
+    pub fn to_kitty(&self, region: PixelRect, options: KittyOptions) -> String {
+        assert!(
+            region.x + region.width <= self.width() && region.y + region.height <= self.height(),
+            "Region out of bounds"
+        );
+        let mut pixels = Vec::with_capacity(region.width * region.height * 3);
+        for y in region.y..region.y + region.height {
+            let start = (y * self.width() + region.x) * 3;
+            pixels.extend_from_slice(&self.data()[start..start + region.width * 3]);
+        }
+
+        let mut control = String::new();
+        let _ = write!(
+            control,
+            "a=T,f=24,s={},v={},q=2",
+            region.width, region.height
+        );
+        if let Some(id) = options.image_id {
+            let _ = write!(control, ",i={id}");
+        }
+        if let Some((columns, rows)) = options.cells {
+            let _ = write!(control, ",c={columns},r={rows}");
+        }
+        #[cfg(feature = "zlib")]
+        let pixels = if options.compress {
+            control.push_str(",o=z");
+            miniz_oxide::deflate::compress_to_vec_zlib(&pixels, 6)
+        } else {
+            pixels
+        };
+
+        let payload = base64(&pixels);
+        let mut out = String::with_capacity(payload.len() + payload.len() / CHUNK_SIZE * 16 + 64);
+        let mut chunks = payload.as_bytes().chunks(CHUNK_SIZE).peekable();
+        let mut first = true;
+        while let Some(chunk) = chunks.next() {
+            let more = chunks.peek().is_some() as u8;
+            let chunk = core::str::from_utf8(chunk).unwrap();
+            if first {
+                let _ = write!(out, "\x1b_G{control},m={more};{chunk}\x1b\\");
+            } else {
+                let _ = write!(out, "\x1b_Gm={more};{chunk}\x1b\\");
+            }
+            first = false;
+        }
+        if first {
+            let _ = write!(out, "\x1b_G{control};\x1b\\");
+        }
+        out
+    }
+
+    /// Writes a region of the pixmap as kitty graphics protocol escape sequences, see
+    /// [`Self::to_kitty`].
+    #[cfg(feature = "std")]
+    pub fn write_kitty<W: std::io::Write>(
+        &self,
+        writer: &mut W,
+        region: PixelRect,
+        options: KittyOptions,
+    ) -> std::io::Result<()> {
+        writer.write_all(self.to_kitty(region, options).as_bytes())
+    }
+}
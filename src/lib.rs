@@ -1,7 +1,86 @@
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 mod soft_backend;
 
-pub use pixmap::RgbPixmap;
+#[cfg(feature = "std")]
+pub use asciicast::AsciicastWriter;
+pub use asciicast::{Asciicast, AsciicastError, AsciicastEvent, AsciicastPlayer};
+#[cfg(feature = "bevy")]
+pub use bevy_plugin::{
+    SoftRatatuiPlugin, SoftTerminal, TerminalFit, TerminalFocus, TerminalImage, TerminalInput,
+};
+pub use bitmap_font::BitmapFont;
+pub use dither::{Dither, GrayPixmap, PackLayout};
+#[cfg(feature = "egui")]
+pub use egui_widget::EguiTerminal;
+#[cfg(all(feature = "fbdev", target_os = "linux"))]
+pub use fbdev::{Channel, Framebuffer, FramebufferLayout};
+pub use image::{ImageFilter, ImageFit, ImageId, ImageWidget, RgbaImage};
+pub use input::{
+    InputEvent, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+pub use kitty::KittyOptions;
+pub use pdf::{PdfDocument, PdfOptions};
+pub use picking::CellHit;
+pub use pixmap::{PixelRect, RgbPixmap};
+pub use procedural::{BrailleStyle, DotShape};
+#[cfg(all(feature = "pty", target_os = "linux"))]
+pub use pty::PtyTerminal;
+#[cfg(feature = "std")]
+pub use record::{RecordError, RecordingBackend};
+pub use record::{RecordedEvent, Replay, ReplayError};
+pub use render::{RenderOptions, render_buffer, render_widget};
 pub use soft_backend::SoftBackend;
+pub use svg::SvgOptions;
+pub use tileset::{CP437, Tileset};
+#[cfg(feature = "vnc")]
+pub use vnc::{VncEvent, VncServer};
+pub use vt::VtScreen;
+#[cfg(feature = "wasm")]
+pub use wasm::WasmTerminal;
+#[cfg(feature = "window")]
+pub use window::{AppEvent, WindowError, run_app};
+mod asciicast;
+#[cfg(feature = "bevy")]
+mod bevy_plugin;
+mod bitmap_font;
+#[cfg(feature = "capi")]
+mod capi;
 mod colors;
+#[cfg(feature = "std")]
+mod cosmic_font;
+mod dither;
+#[cfg(feature = "egui")]
+mod egui_widget;
+#[cfg(feature = "embedded-graphics")]
+mod embedded;
+#[cfg(all(feature = "fbdev", target_os = "linux"))]
+mod fbdev;
+mod html;
+mod image;
+mod input;
+mod kitty;
+mod pdf;
+mod picking;
+mod procedural;
+#[cfg(all(feature = "pty", target_os = "linux"))]
+mod pty;
+mod record;
+mod render;
+mod sixel;
+mod svg;
+mod tileset;
+mod truetype;
+#[cfg(feature = "vnc")]
+mod vnc;
+mod vt;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "window")]
+mod window;
 
 mod pixmap;
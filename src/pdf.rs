@@ -0,0 +1,493 @@
+//! Vector PDF export of terminal frames, one page per frame, for printable reports.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use ratatui::style::Modifier;
+use unicode_width::UnicodeWidthStr;
+
+use crate::image;
+use crate::soft_backend::{SoftBackend, cell_colors};
+use crate::svg::base_background;
+use crate::truetype::TrueTypeFont;
+
+/// The advance width of the glyphs of Courier, in thousandths of the font size.
+const COURIER_ADVANCE: f32 = 600.0;
+
+/// Options of a PDF export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PdfOptions<'a> {
+    /// A TrueType font to draw the text with, usually the font of the backend. Only the glyphs
+    /// used by the pages are embedded. Without a TrueType font, the text is drawn with the
+    /// standard Courier font, which only has Latin-1 chars.
+    pub font_data: Option<&'a [u8]>,
+}
+
+/// Writes a number with at most three decimals.
+fn num(out: &mut String, value: f32) {
+    let start = out.len();
+    let _ = write!(out, "{value:.3}");
+    let trimmed = out[start..]
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .len();
+    out.truncate(start + trimmed);
+    if &out[start..] == "-0" {
+        out.truncate(start);
+        out.push('0');
+    }
+}
+
+fn color(out: &mut String, color: [u8; 3], operator: &str) {
+    for channel in color {
+        num(out, channel as f32 / 255.0);
+        out.push(' ');
+    }
+    out.push_str(operator);
+    out.push('\n');
+}
+
+fn rect(out: &mut String, x: f32, y: f32, width: f32, height: f32) {
+    for value in [x, y, width, height] {
+        num(out, value);
+        out.push(' ');
+    }
+    out.push_str("re f\n");
+}
+
+/// The attributes that split a row into separate text runs.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct TextStyle {
+    fg: [u8; 3],
+    modifier: Modifier,
+}
+
+/// A run of cells of the same text style: its first column and the symbols with their width
+/// in cells.
+struct TextRun<'a> {
+    x: u16,
+    symbols: Vec<(&'a str, u16)>,
+    style: TextStyle,
+}
+
+/// A PDF document built from terminal frames, one page per frame.
+///
+/// Backgrounds are drawn as filled rectangles and text with the embedded font, with every glyph
+/// placed on its cell, so the pages print sharply and match the layout of the pixmap, with one
+/// PDF point per pixel. Colors are resolved as in the pixmap. Bold text is drawn with a stroked
+/// outline and italic text slanted, and underlines and strike throughs are drawn as lines.
+///
+/// # Examples
+/// ```rust
+/// use ratatui::Terminal;
+/// use ratatui::widgets::Paragraph;
+/// use soft_ratatui::{BitmapFont, PdfDocument, PdfOptions, SoftBackend};
+///
+/// let backend = SoftBackend::new_with_bitmap_font(20, 2, BitmapFont::FIXED_8X13);
+/// let mut terminal = Terminal::new(backend).unwrap();
+/// let mut document = PdfDocument::new(&PdfOptions::default());
+/// for text in ["First page", "Second page"] {
+///     terminal
+///         .draw(|frame| frame.render_widget(Paragraph::new(text), frame.area()))
+///         .unwrap();
+///     document.add_page(terminal.backend());
+/// }
+/// let pdf = document.finish();
+/// assert!(pdf.starts_with(b"%PDF-1.7"));
+/// ```
+pub struct PdfDocument {
+    font: Option<TrueTypeFont>,
+    /// The glyphs used by the pages, with the char each one was drawn for.
+    glyphs: BTreeMap<u16, char>,
+    /// The size and content stream of every page.
+    pages: Vec<(usize, usize, String)>,
+}
+
+impl PdfDocument {
+    /// Creates a document without pages.
+    pub fn new(options: &PdfOptions) -> Self {
+        Self {
+            font: options.font_data.and_then(TrueTypeFont::parse),
+            glyphs: BTreeMap::new(),
+            pages: Vec::new(),
+        }
+    }
+
+    /// Returns the number of pages.
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Adds a page with the current buffer of the backend.
+    pub fn add_page(&mut self, backend: &SoftBackend) {
+        let buffer = backend.buffer();
+        let area = buffer.area;
+        let (cw, ch) = (backend.char_width as f32, backend.char_height as f32);
+        let (font_size, baseline) = backend.text_metrics();
+        let (width, height) = (
+            area.width as usize * backend.char_width,
+            area.height as usize * backend.char_height,
+        );
+        let page_height = height as f32;
+        let mut out = String::new();
+
+        let base = base_background(buffer);
+        color(&mut out, base, "rg");
+        rect(&mut out, 0.0, 0.0, width as f32, page_height);
+        let rows = || {
+            (0..area.height).map(|y| {
+                let start = y as usize * area.width as usize;
+                (y, &buffer.content[start..start + area.width as usize])
+            })
+        };
+        for (y, row) in rows() {
+            let mut x = 0;
+            while x < row.len() {
+                let bg = cell_colors(&row[x]).1;
+                let start = x;
+                while x < row.len() && cell_colors(&row[x]).1 == bg {
+                    x += 1;
+                }
+                if bg != base {
+                    color(&mut out, bg, "rg");
+                    let top = page_height - (y + 1) as f32 * ch;
+                    rect(
+                        &mut out,
+                        start as f32 * cw,
+                        top,
+                        (x - start) as f32 * cw,
+                        ch,
+                    );
+                }
+            }
+        }
+
+        let mut decorations = String::new();
+        out.push_str("BT\n/F1 ");
+        num(&mut out, font_size);
+        out.push_str(" Tf\n");
+        for (y, row) in rows() {
+            let mut runs: Vec<TextRun> = Vec::new();
+            let mut skip = 0;
+            for (x, cell) in row.iter().enumerate() {
+                // Cells covered by a wide char belong to its run, whatever their style.
+                if skip > 0
+                    && let Some((_, cells)) = runs.last_mut().and_then(|run| run.symbols.last_mut())
+                {
+                    skip -= 1;
+                    *cells += 1;
+                    continue;
+                }
+                let symbol = cell.symbol();
+                let symbol =
+                    if cell.modifier.contains(Modifier::HIDDEN) || image::is_placeholder(symbol) {
+                        " "
+                    } else {
+                        skip = symbol.width().saturating_sub(1);
+                        symbol
+                    };
+                let style = TextStyle {
+                    fg: cell_colors(cell).0,
+                    modifier: cell.modifier
+                        & (Modifier::BOLD
+                            | Modifier::ITALIC
+                            | Modifier::UNDERLINED
+                            | Modifier::CROSSED_OUT),
+                };
+                match runs.last_mut() {
+                    Some(run) if run.style == style => run.symbols.push((symbol, 1)),
+                    _ => runs.push(TextRun {
+                        x: x as u16,
+                        symbols: Vec::from([(symbol, 1)]),
+                        style,
+                    }),
+                }
+            }
+
+            let baseline_y = page_height - (y as f32 * ch + baseline);
+            for run in runs {
+                let modifier = run.style.modifier;
+                let cells: u16 = run.symbols.iter().map(|(_, cells)| cells).sum();
+                let left = run.x as f32 * cw;
+                let thickness = (font_size / 16.0).max(1.0);
+                if modifier.contains(Modifier::UNDERLINED) {
+                    color(&mut decorations, run.style.fg, "rg");
+                    let top = baseline_y - font_size * 0.12 - thickness;
+                    rect(&mut decorations, left, top, cells as f32 * cw, thickness);
+                }
+                if modifier.contains(Modifier::CROSSED_OUT) {
+                    color(&mut decorations, run.style.fg, "rg");
+                    let top = baseline_y + font_size * 0.3;
+                    rect(&mut decorations, left, top, cells as f32 * cw, thickness);
+                }
+
+                let leading = run.symbols.iter().take_while(|(s, _)| *s == " ").count();
+                if leading == run.symbols.len() {
+                    continue;
+                }
+                let trailing = run
+                    .symbols
+                    .iter()
+                    .rev()
+                    .take_while(|(s, _)| *s == " ")
+                    .count();
+                let symbols = &run.symbols[leading..run.symbols.len() - trailing];
+
+                color(&mut out, run.style.fg, "rg");
+                if modifier.contains(Modifier::BOLD) {
+                    color(&mut out, run.style.fg, "RG");
+                    out.push_str("2 Tr ");
+                    num(&mut out, font_size / 30.0);
+                    out.push_str(" w\n");
+                } else {
+                    out.push_str("0 Tr\n");
+                }
+                out.push_str(if modifier.contains(Modifier::ITALIC) {
+                    "1 0 0.2 1 "
+                } else {
+                    "1 0 0 1 "
+                });
+                num(&mut out, left + leading as f32 * cw);
+                out.push(' ');
+                num(&mut out, baseline_y);
+                out.push_str(" Tm\n[");
+                for (symbol, cells) in symbols {
+                    let mut target = *cells as f32 * cw / font_size * 1000.0;
+                    for c in symbol.chars() {
+                        let advance = self.show_char(&mut out, c);
+                        // Moves the next glyph to the next cell, combining chars stay on theirs.
+                        let adjustment = advance - target;
+                        if adjustment != 0.0 {
+                            num(&mut out, adjustment);
+                        }
+                        target = 0.0;
+                    }
+                }
+                out.push_str("] TJ\n");
+            }
+        }
+        out.push_str("ET\n");
+        out.push_str(&decorations);
+        self.pages.push((width, height, out));
+    }
+
+    /// Writes a glyph of the char as a PDF string, returning its advance in thousandths of the
+    /// font size.
+    fn show_char(&mut self, out: &mut String, c: char) -> f32 {
+        match &self.font {
+            Some(font) => {
+                let glyph = font.glyph_id(c);
+                self.glyphs.entry(glyph).or_insert(c);
+                let _ = write!(out, "<{glyph:04x}>");
+                font.advance(glyph) as f32 * 1000.0 / font.units_per_em as f32
+            }
+            None => {
+                // WinAnsiEncoding matches Latin-1 for printable chars.
+                let byte = match c as u32 {
+                    code @ (0x20..=0x7e | 0xa0..=0xff) => code as u8,
+                    _ => b'?',
+                };
+                let _ = write!(out, "<{byte:02x}>");
+                COURIER_ADVANCE
+            }
+        }
+    }
+
+    /// Finishes the document and returns the PDF file.
+    pub fn finish(self) -> Vec<u8> {
+        let mut pdf = PdfWriter::default();
+        pdf.out.extend_from_slice(b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n");
+        let font_objects = if self.font.is_some() { 5 } else { 1 };
+        let first_page = 3 + font_objects;
+        let kids: Vec<String> = (0..self.pages.len())
+            .map(|i| format!("{} 0 R", first_page + 2 * i))
+            .collect();
+        pdf.object(1, "<< /Type /Catalog /Pages 2 0 R >>");
+        pdf.object(
+            2,
+            &format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                self.pages.len()
+            ),
+        );
+
+        match &self.font {
+            Some(font) => self.write_font(&mut pdf, font),
+            None => pdf.object(
+                3,
+                "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>",
+            ),
+        }
+
+        for (i, (width, height, content)) in self.pages.iter().enumerate() {
+            let id = first_page + 2 * i;
+            pdf.object(
+                id,
+                &format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {width} {height}] \
+                     /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                    id + 1
+                ),
+            );
+            pdf.stream(id + 1, "", content.as_bytes());
+        }
+        pdf.finish()
+    }
+
+    /// Writes the Type 0 font, its descendant CID font, descriptor, subset font file and
+    /// Unicode mapping as objects 3 to 7.
+    fn write_font(&self, pdf: &mut PdfWriter, font: &TrueTypeFont) {
+        let glyphs: BTreeSet<u16> = self.glyphs.keys().copied().collect();
+        // Subset fonts are named with a tag made from their glyphs.
+        let hash = glyphs.iter().fold(0x811c_9dc5_u32, |hash, glyph| {
+            (hash ^ *glyph as u32).wrapping_mul(0x0100_0193)
+        });
+        let tag: String = (0..6)
+            .map(|i| (b'A' + (hash >> (5 * i) & 0x1f) as u8 % 26) as char)
+            .collect();
+        let name = font
+            .postscript_name()
+            .unwrap_or_else(|| String::from("SoftRatatui"));
+        let base_font = format!("{tag}+{name}");
+        let scale = |value: i16| value as i32 * 1000 / font.units_per_em as i32;
+
+        pdf.object(
+            3,
+            &format!(
+                "<< /Type /Font /Subtype /Type0 /BaseFont /{base_font} /Encoding /Identity-H \
+                 /DescendantFonts [4 0 R] /ToUnicode 7 0 R >>"
+            ),
+        );
+        let mut widths = String::new();
+        for glyph in &glyphs {
+            let width = font.advance(*glyph) as u32 * 1000 / font.units_per_em as u32;
+            let _ = write!(widths, "{glyph} [{width}] ");
+        }
+        pdf.object(
+            4,
+            &format!(
+                "<< /Type /Font /Subtype /CIDFontType2 /BaseFont /{base_font} \
+                 /CIDSystemInfo << /Registry (Adobe) /Ordering (Identity) /Supplement 0 >> \
+                 /FontDescriptor 5 0 R /CIDToGIDMap /Identity /W [{}] >>",
+                widths.trim_end()
+            ),
+        );
+        let [x_min, y_min, x_max, y_max] = font.bbox.map(scale);
+        pdf.object(
+            5,
+            &format!(
+                "<< /Type /FontDescriptor /FontName /{base_font} /Flags 5 \
+                 /FontBBox [{x_min} {y_min} {x_max} {y_max}] /ItalicAngle 0 /Ascent {} \
+                 /Descent {} /CapHeight {} /StemV 80 /FontFile2 6 0 R >>",
+                scale(font.ascender),
+                scale(font.descender),
+                scale(font.ascender)
+            ),
+        );
+        let file = font.subset(&glyphs);
+        pdf.stream(6, &format!("/Length1 {}", file.len()), &file);
+
+        let mut cmap = String::from(
+            "/CIDInit /ProcSet findresource begin\n12 dict begin\nbegincmap\n\
+             /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+             /CMapName /Adobe-Identity-UCS def\n/CMapType 2 def\n\
+             1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+        );
+        let mapped: Vec<(&u16, &char)> = self.glyphs.iter().filter(|(g, _)| **g != 0).collect();
+        for chunk in mapped.chunks(100) {
+            let _ = writeln!(cmap, "{} beginbfchar", chunk.len());
+            for (glyph, c) in chunk {
+                let _ = write!(cmap, "<{glyph:04x}> <");
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    let _ = write!(cmap, "{unit:04x}");
+                }
+                cmap.push_str(">\n");
+            }
+            cmap.push_str("endbfchar\n");
+        }
+        cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+        pdf.stream(7, "", cmap.as_bytes());
+    }
+}
+
+/// Writes numbered objects and the cross-reference table of a PDF file.
+#[derive(Default)]
+struct PdfWriter {
+    out: Vec<u8>,
+    /// The byte offset of every object, in order of their numbers starting at 1.
+    offsets: Vec<usize>,
+}
+
+impl PdfWriter {
+    fn object(&mut self, id: usize, body: &str) {
+        self.begin(id);
+        self.out.extend_from_slice(body.as_bytes());
+        self.out.extend_from_slice(b"\nendobj\n");
+    }
+
+    fn begin(&mut self, id: usize) {
+        debug_assert_eq!(id, self.offsets.len() + 1, "Objects out of order");
+        self.offsets.push(self.out.len());
+        self.out
+            .extend_from_slice(format!("{id} 0 obj\n").as_bytes());
+    }
+
+    /// Writes a stream object, compressed with the `zlib` feature.
+    fn stream(&mut self, id: usize, entries: &str, data: &[u8]) {
+        #[cfg(feature = "zlib")]
+        let (data, filter) = (
+            &miniz_oxide::deflate::compress_to_vec_zlib(data, 6)[..],
+            " /Filter /FlateDecode",
+        );
+        #[cfg(not(feature = "zlib"))]
+        let filter = "";
+        self.begin(id);
+        self.out.extend_from_slice(
+            format!("<< /Length {}{filter} {entries} >>\nstream\n", data.len()).as_bytes(),
+        );
+        self.out.extend_from_slice(data);
+        self.out.extend_from_slice(b"\nendstream\nendobj\n");
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let xref = self.out.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", self.offsets.len() + 1);
+        for offset in &self.offsets {
+            let _ = writeln!(table, "{offset:010} 00000 n ");
+        }
+        let _ = write!(
+            table,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            self.offsets.len() + 1
+        );
+        self.out.extend_from_slice(table.as_bytes());
+        self.out
+    }
+}
+
+impl SoftBackend {
+    /// Exports the current buffer as a single page PDF, see [`PdfDocument`].
+    ///
+    /// (to-pdf options) -> Vec<u8>
+    ///
+    /// * options    : PdfOptions - The font to embed
+    pub fn to_pdf(&self, options: &PdfOptions) -> Vec<u8> {
+        let mut document = PdfDocument::new(options);
+        document.add_page(self);
+        document.finish()
+    }
+
+    /// Writes the current buffer as a single page PDF, see [`PdfDocument`].
+    #[cfg(feature = "std")]
+    pub fn write_pdf<W: std::io::Write>(
+        &self,
+        writer: &mut W,
+        options: &PdfOptions,
+    ) -> std::io::Result<()> {
+        writer.write_all(&self.to_pdf(options))
+    }
+}
@@ -0,0 +1,85 @@
+//! Mapping of points on the pixmap, or on a texture showing it, back to terminal cells, e.g. to
+//! click on a screen in a 3D world after a raycast hit.
+
+use ratatui::layout::Position;
+
+use crate::input::{KeyModifiers, MouseEvent, MouseEventKind};
+use crate::soft_backend::SoftBackend;
+
+/// A cell under a point, with where the point is inside the cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellHit {
+    pub position: Position,
+    /// The offset of the point from the top left of the cell, as a fraction of the cell width
+    /// and height from 0.0 to 1.0.
+    pub offset: (f32, f32),
+}
+
+impl SoftBackend {
+    /// Returns the cell under a pixel of the pixmap, or `None` outside of it.
+    pub fn cell_at_pixel(&self, x: f32, y: f32) -> Option<CellHit> {
+        let (width, height) = (
+            self.buffer.area.width as f32 * self.char_width as f32,
+            self.buffer.area.height as f32 * self.char_height as f32,
+        );
+        if !(0.0..=width).contains(&x) || !(0.0..=height).contains(&y) {
+            return None;
+        }
+        let column = x / self.char_width as f32;
+        let row = y / self.char_height as f32;
+        // The right and bottom edges belong to the last cell.
+        let (cell_x, cell_y) = (
+            (column as u16).min(self.buffer.area.width.checked_sub(1)?),
+            (row as u16).min(self.buffer.area.height.checked_sub(1)?),
+        );
+        Some(CellHit {
+            position: Position::new(cell_x, cell_y),
+            offset: (column - cell_x as f32, row - cell_y as f32),
+        })
+    }
+
+    /// Returns the cell under a texture coordinate of the pixmap, from (0.0, 0.0) at the top
+    /// left to (1.0, 1.0) at the bottom right, or `None` outside of it.
+    ///
+    /// For APIs with the origin of textures at the bottom left, such as OpenGL, pass `1.0 - v`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ratatui::layout::Position;
+    /// use soft_ratatui::{BitmapFont, KeyModifiers, MouseButton, MouseEventKind, SoftBackend};
+    ///
+    /// let backend = SoftBackend::new_with_bitmap_font(10, 4, BitmapFont::FIXED_8X13);
+    /// let hit = backend.cell_at_uv(0.55, 0.5).unwrap();
+    /// assert_eq!(hit.position, Position::new(5, 2));
+    /// assert!((hit.offset.0 - 0.5).abs() < 1e-4);
+    ///
+    /// let kind = MouseEventKind::Down(MouseButton::Left);
+    /// let event = backend.mouse_event_at_uv(0.55, 0.5, kind, KeyModifiers::NONE).unwrap();
+    /// assert_eq!((event.column, event.row), (5, 2));
+    /// assert!(backend.cell_at_uv(1.5, 0.5).is_none());
+    /// ```
+    pub fn cell_at_uv(&self, u: f32, v: f32) -> Option<CellHit> {
+        self.cell_at_pixel(
+            u * self.buffer.area.width as f32 * self.char_width as f32,
+            v * self.buffer.area.height as f32 * self.char_height as f32,
+        )
+    }
+
+    /// Returns a mouse event of the given kind on the cell under a texture coordinate, see
+    /// [`Self::cell_at_uv`], to pass to the app like the mouse events of a terminal.
+    pub fn mouse_event_at_uv(
+        &self,
+        u: f32,
+        v: f32,
+        kind: MouseEventKind,
+        modifiers: KeyModifiers,
+    ) -> Option<MouseEvent> {
+        let hit = self.cell_at_uv(u, v)?;
+        Some(MouseEvent {
+            kind,
+            column: hit.position.x,
+            row: hit.position.y,
+            modifiers,
+        })
+    }
+}
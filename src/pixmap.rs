@@ -1,3 +1,14 @@
+use alloc::vec::Vec;
+
+/// A rectangle of a pixmap, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PixelRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
 /// A pixmap with RGB pixels stored in a flat vector.
 #[derive(Debug, Clone)]
 pub struct RgbPixmap {
@@ -28,8 +39,76 @@ impl RgbPixmap {
         rgba_data
     }
 
-    /// Sets the RGB value of a pixel at (x, y).
+    /// Copies a rectangle into an RGBA image of the same size as the pixmap, e.g. the texture
+    /// of a game engine, so only the dirty regions have to be converted and uploaded each frame.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use soft_ratatui::{PixelRect, RgbPixmap};
+    ///
+    /// let mut pixmap = RgbPixmap::new(4, 4);
+    /// let mut texture = pixmap.to_rgba();
+    /// pixmap.fill_rect(1, 1, 2, 2, [255, 0, 0]);
+    /// pixmap.copy_rect_to_rgba(PixelRect { x: 1, y: 1, width: 2, height: 2 }, &mut texture);
+    /// assert_eq!(texture, pixmap.to_rgba());
+    /// ```
+    pub fn copy_rect_to_rgba(&self, rect: PixelRect, rgba: &mut [u8]) {
+        assert_eq!(
+            rgba.len(),
+            self.width * self.height * 4,
+            "RGBA image size differs from the pixmap"
+        );
+        let x_end = (rect.x + rect.width).min(self.width);
+        let y_end = (rect.y + rect.height).min(self.height);
+        if rect.x >= x_end {
+            return;
+        }
+        for row in rect.y..y_end {
+            let start = row * self.width + rect.x;
+            let end = row * self.width + x_end;
+            let source = self.data[3 * start..3 * end].chunks_exact(3);
+            for (pixel, rgb) in rgba[4 * start..4 * end].chunks_exact_mut(4).zip(source) {
+                pixel[..3].copy_from_slice(rgb);
+                pixel[3] = 255;
+            }
+        }
+    }
+
+    /// Copies a rectangle into an image of the same size as the pixmap with a `u32` per pixel,
+    /// as `0x00RRGGBB`, the native format of window surfaces such as softbuffer's.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use soft_ratatui::RgbPixmap;
+    ///
+    /// let mut pixmap = RgbPixmap::new(2, 1);
+    /// pixmap.put_pixel(1, 0, [0x12, 0x34, 0x56]);
+    /// let mut surface = [0xffff_ffff; 2];
+    /// pixmap.copy_rect_to_xrgb(pixmap.bounds(), &mut surface);
+    /// assert_eq!(surface, [0, 0x0012_3456]);
+    /// ```
+    pub fn copy_rect_to_xrgb(&self, rect: PixelRect, xrgb: &mut [u32]) {
+        assert_eq!(
+            xrgb.len(),
+            self.width * self.height,
+            "XRGB image size differs from the pixmap"
+        );
+        let x_end = (rect.x + rect.width).min(self.width);
+        let y_end = (rect.y + rect.height).min(self.height);
+        if rect.x >= x_end {
+            return;
+        }
+        for row in rect.y..y_end {
+            let start = row * self.width + rect.x;
+            let end = row * self.width + x_end;
+            let source = self.data[3 * start..3 * end].chunks_exact(3);
+            for (pixel, rgb) in xrgb[start..end].iter_mut().zip(source) {
+                *pixel = (rgb[0] as u32) << 16 | (rgb[1] as u32) << 8 | rgb[2] as u32;
+            }
+        }
+    }
 
+    /// Sets the RGB value of a pixel at (x, y).
     pub fn put_pixel(&mut self, x: usize, y: usize, color: [u8; 3]) {
         debug_assert!(
             x < self.width && y < self.height,
@@ -40,7 +119,6 @@ impl RgbPixmap {
     }
 
     /// Returns the RGB value of a pixel at (x, y).
-
     pub fn get_pixel(&self, x: usize, y: usize) -> [u8; 3] {
         debug_assert!(
             x < self.width && y < self.height,
@@ -59,6 +137,20 @@ impl RgbPixmap {
         }
     }
 
+    /// Fills a rectangle of the pixmap with the specified RGB color.
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: [u8; 3]) {
+        debug_assert!(
+            x + width <= self.width && y + height <= self.height,
+            "Rectangle out of bounds"
+        );
+        for row in y..y + height {
+            let start = 3 * (row * self.width + x);
+            for chunk in self.data[start..start + 3 * width].chunks_exact_mut(3) {
+                chunk.copy_from_slice(&color);
+            }
+        }
+    }
+
     /// Returns the width of the pixmap in pixels
     pub fn width(&self) -> usize {
         self.width
@@ -67,6 +159,15 @@ impl RgbPixmap {
     pub fn height(&self) -> usize {
         self.height
     }
+    /// Returns the rectangle covering the whole pixmap
+    pub fn bounds(&self) -> PixelRect {
+        PixelRect {
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+        }
+    }
 
     /// Retuns the raw rgb data of the pixmap as a flat array
     pub fn data(&self) -> &[u8] {
@@ -0,0 +1,684 @@
+//! Procedural drawing of box-drawing, block element, Powerline, Braille, sextant and octant
+//! glyphs.
+//!
+//! Fonts rarely fill the exact cell box measured from `█`, so borders drawn from glyphs can leave
+//! gaps or misaligned joins between cells, and the dot patterns used by ratatui's `Canvas` are
+//! often missing or uneven. These glyphs are instead computed from the cell size, so lines always
+//! meet at the cell edges and plots land on an exact sub-cell grid.
+
+use crate::colors::*;
+use crate::pixmap::RgbPixmap;
+use ratatui::symbols::pixel::{OCTANTS, SEXTANTS};
+
+/// Subsamples per pixel along each axis when computing the coverage of curves and diagonals.
+const SUBSAMPLES: i64 = 4;
+
+/// The pixels of a single cell, with the colors to draw it in.
+pub(crate) struct CellCanvas<'a> {
+    pub(crate) pixmap: &'a mut RgbPixmap,
+    pub(crate) x: usize,
+    pub(crate) y: usize,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) fg: [u8; 3],
+    pub(crate) bg: [u8; 3],
+}
+
+impl CellCanvas<'_> {
+    /// Fills the rectangle from (x0, y0) to (x1, y1) exclusive, in cell pixel coordinates.
+    pub(crate) fn rect(&mut self, x0: usize, y0: usize, x1: usize, y1: usize) {
+        let (x1, y1) = (x1.min(self.width), y1.min(self.height));
+        if x0 < x1 && y0 < y1 {
+            self.pixmap
+                .fill_rect(self.x + x0, self.y + y0, x1 - x0, y1 - y0, self.fg);
+        }
+    }
+
+    /// Fills the pixels for which `inside` holds, anti-aliased by supersampling.
+    ///
+    /// `inside` receives subpixel coordinates, in units of `1 / SUBSAMPLES` of a pixel.
+    pub(crate) fn shape<F>(&mut self, inside: F)
+    where
+        F: Fn(i64, i64) -> bool,
+    {
+        self.shape_in(0, 0, self.width, self.height, inside);
+    }
+
+    /// Like [`Self::shape`], but only visits the pixels from (x0, y0) to (x1, y1) exclusive.
+    pub(crate) fn shape_in<F>(&mut self, x0: usize, y0: usize, x1: usize, y1: usize, inside: F)
+    where
+        F: Fn(i64, i64) -> bool,
+    {
+        for y in y0..y1.min(self.height) {
+            for x in x0..x1.min(self.width) {
+                let mut hits = 0;
+                for sy in 0..SUBSAMPLES {
+                    for sx in 0..SUBSAMPLES {
+                        if inside(x as i64 * SUBSAMPLES + sx, y as i64 * SUBSAMPLES + sy) {
+                            hits += 1;
+                        }
+                    }
+                }
+                if hits > 0 {
+                    let alpha = (hits * 255 / (SUBSAMPLES * SUBSAMPLES)) as u8;
+                    let color = blend_rgba(
+                        [self.fg[0], self.fg[1], self.fg[2], alpha],
+                        [self.bg[0], self.bg[1], self.bg[2], 255],
+                    );
+                    self.pixmap.put_pixel(self.x + x, self.y + y, color);
+                }
+            }
+        }
+    }
+
+    /// Thickness of a light line, heavy lines are twice as thick.
+    fn light(&self) -> usize {
+        ((self.width + 5) / 10).max(1)
+    }
+}
+
+/// Returns true if `c` is drawn procedurally rather than with the font.
+pub(crate) fn is_procedural(c: char) -> bool {
+    matches!(
+        c,
+        '\u{2500}'..='\u{259F}' | '\u{E0B0}'..='\u{E0BF}' | '\u{2800}'..='\u{28FF}'
+    ) || sextant_mask(c).is_some()
+        || octant_mask(c).is_some()
+}
+
+/// Draws `c` over the cell background. Returns false if `c` has no procedural drawing.
+pub(crate) fn draw(canvas: &mut CellCanvas, c: char, braille: BrailleStyle) -> bool {
+    match c {
+        '\u{2500}'..='\u{257F}' => draw_box(canvas, c),
+        '\u{2580}'..='\u{259F}' => draw_block(canvas, c),
+        '\u{E0B0}'..='\u{E0BF}' => draw_powerline(canvas, c),
+        '\u{2800}'..='\u{28FF}' => draw_braille(canvas, c as u32 as u8, braille),
+        _ => {
+            if let Some(mask) = sextant_mask(c) {
+                grid(canvas, 3, mask)
+            } else if let Some(mask) = octant_mask(c) {
+                grid(canvas, 4, mask)
+            } else {
+                false
+            }
+        }
+    }
+}
+
+/// The shape of Braille dots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DotShape {
+    #[default]
+    Round,
+    Square,
+}
+
+/// How Braille chars (U+2800 to U+28FF) are drawn.
+///
+/// Each cell is split into a 2x4 grid, and every dot is centred in its part of the grid.
+/// A square dot with a size of 100 fills its part completely, which makes `Canvas` plots look
+/// like true pixel plots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrailleStyle {
+    pub shape: DotShape,
+    /// Size of a dot, in percent of the smaller side of its part of the grid.
+    pub size: u8,
+}
+
+impl Default for BrailleStyle {
+    fn default() -> Self {
+        Self {
+            shape: DotShape::Round,
+            size: 70,
+        }
+    }
+}
+
+/// Returns the dots of a sextant char, in row-major order from the top left.
+fn sextant_mask(c: char) -> Option<u8> {
+    if !matches!(c, '\u{1FB00}'..='\u{1FB3B}') {
+        return None;
+    }
+    SEXTANTS.iter().position(|s| *s == c).map(|i| i as u8)
+}
+
+/// Returns the dots of an octant char, in row-major order from the top left.
+///
+/// Besides U+1CD00 to U+1CDE5 this covers the quarter blocks that ratatui uses for the octant
+/// patterns which have no char of their own, but not the block elements drawn by `draw_block`.
+fn octant_mask(c: char) -> Option<u8> {
+    // The octants and the legacy computing chars in the table, checked first as this runs for
+    // every cell.
+    if !matches!(
+        c,
+        '\u{1CD00}'..='\u{1CDE5}'
+            | '\u{1CEA0}'..='\u{1CEAB}'
+            | '\u{1FB82}'..='\u{1FB85}'
+            | '\u{1FBE6}'..='\u{1FBE7}'
+    ) {
+        return None;
+    }
+    OCTANTS.iter().position(|o| *o == c).map(|i| i as u8)
+}
+
+/// Fills the parts of a 2 column grid with `rows` rows whose bit is set in `mask`, in row-major
+/// order from the top left. The parts split the cell at whole pixels, so neighbouring cells
+/// tile without gaps or overlaps.
+fn grid(canvas: &mut CellCanvas, rows: usize, mask: u8) -> bool {
+    let (w, h) = (canvas.width, canvas.height);
+    for row in 0..rows {
+        for column in 0..2 {
+            if mask & (1 << (row * 2 + column)) != 0 {
+                let (x0, x1) = (column * w / 2, (column + 1) * w / 2);
+                let (y0, y1) = (row * h / rows, (row + 1) * h / rows);
+                canvas.rect(x0, y0, x1, y1);
+            }
+        }
+    }
+    true
+}
+
+/// The dot numbered `n + 1` of a Braille pattern, as (column, row).
+const BRAILLE_DOTS: [(usize, usize); 8] = [
+    (0, 0),
+    (0, 1),
+    (0, 2),
+    (1, 0),
+    (1, 1),
+    (1, 2),
+    (0, 3),
+    (1, 3),
+];
+
+fn draw_braille(canvas: &mut CellCanvas, dots: u8, style: BrailleStyle) -> bool {
+    let (w, h) = (canvas.width, canvas.height);
+    let s = SUBSAMPLES;
+    for (bit, (column, row)) in BRAILLE_DOTS.iter().enumerate() {
+        if dots & (1 << bit) == 0 {
+            continue;
+        }
+        let (x0, x1) = (column * w / 2, (column + 1) * w / 2);
+        let (y0, y1) = (row * h / 4, (row + 1) * h / 4);
+        let side = (x1 - x0).min(y1 - y0);
+        let size = (side * style.size.min(100) as usize / 100).max(1);
+        match style.shape {
+            DotShape::Square => {
+                let dx = x0 + (x1 - x0 - size) / 2;
+                let dy = y0 + (y1 - y0 - size) / 2;
+                canvas.rect(dx, dy, dx + size, dy + size);
+            }
+            DotShape::Round => {
+                // Centre and radius in doubled subpixels, so that the centre of a box with an
+                // odd size stays on the grid.
+                let (cx, cy) = ((x0 + x1) as i64 * s, (y0 + y1) as i64 * s);
+                let radius = size as i64 * s;
+                canvas.shape_in(x0, y0, x1, y1, |x, y| {
+                    let (x, y) = (2 * x + 1 - cx, 2 * y + 1 - cy);
+                    x * x + y * y <= radius * radius
+                });
+            }
+        }
+    }
+    true
+}
+
+const NONE: u8 = 0;
+const LIGHT: u8 = 1;
+const HEAVY: u8 = 2;
+const DOUBLE: u8 = 3;
+
+/// The line weight of the up, right, down and left arms of U+2500 to U+257F, for the chars
+/// made only of straight arms.
+#[rustfmt::skip]
+const BOX_ARMS: [[u8; 4]; 128] = {
+    const N: u8 = NONE;
+    const L: u8 = LIGHT;
+    const H: u8 = HEAVY;
+    const D: u8 = DOUBLE;
+    [
+        // ─ ━ │ ┃, then the dashed lines ┄ to ┋ which are drawn separately.
+        [N, L, N, L], [N, H, N, H], [L, N, L, N], [H, N, H, N],
+        [N; 4], [N; 4], [N; 4], [N; 4], [N; 4], [N; 4], [N; 4], [N; 4],
+        // ┌ ┍ ┎ ┏ ┐ ┑ ┒ ┓
+        [N, L, L, N], [N, H, L, N], [N, L, H, N], [N, H, H, N],
+        [N, N, L, L], [N, N, L, H], [N, N, H, L], [N, N, H, H],
+        // └ ┕ ┖ ┗ ┘ ┙ ┚ ┛
+        [L, L, N, N], [L, H, N, N], [H, L, N, N], [H, H, N, N],
+        [L, N, N, L], [L, N, N, H], [H, N, N, L], [H, N, N, H],
+        // ├ ┝ ┞ ┟ ┠ ┡ ┢ ┣
+        [L, L, L, N], [L, H, L, N], [H, L, L, N], [L, L, H, N],
+        [H, L, H, N], [H, H, L, N], [L, H, H, N], [H, H, H, N],
+        // ┤ ┥ ┦ ┧ ┨ ┩ ┪ ┫
+        [L, N, L, L], [L, N, L, H], [H, N, L, L], [L, N, H, L],
+        [H, N, H, L], [H, N, L, H], [L, N, H, H], [H, N, H, H],
+        // ┬ ┭ ┮ ┯ ┰ ┱ ┲ ┳
+        [N, L, L, L], [N, L, L, H], [N, H, L, L], [N, H, L, H],
+        [N, L, H, L], [N, L, H, H], [N, H, H, L], [N, H, H, H],
+        // ┴ ┵ ┶ ┷ ┸ ┹ ┺ ┻
+        [L, L, N, L], [L, L, N, H], [L, H, N, L], [L, H, N, H],
+        [H, L, N, L], [H, L, N, H], [H, H, N, L], [H, H, N, H],
+        // ┼ ┽ ┾ ┿ ╀ ╁ ╂ ╃
+        [L, L, L, L], [L, L, L, H], [L, H, L, L], [L, H, L, H],
+        [H, L, L, L], [L, L, H, L], [H, L, H, L], [H, L, L, H],
+        // ╄ ╅ ╆ ╇ ╈ ╉ ╊ ╋
+        [H, H, L, L], [L, L, H, H], [L, H, H, L], [H, H, L, H],
+        [L, H, H, H], [H, L, H, H], [H, H, H, L], [H, H, H, H],
+        // The dashed lines ╌ to ╏, drawn separately.
+        [N; 4], [N; 4], [N; 4], [N; 4],
+        // ═ ║ ╒ ╓ ╔ ╕ ╖ ╗
+        [N, D, N, D], [D, N, D, N], [N, D, L, N], [N, L, D, N],
+        [N, D, D, N], [N, N, L, D], [N, N, D, L], [N, N, D, D],
+        // ╘ ╙ ╚ ╛ ╜ ╝ ╞ ╟
+        [L, D, N, N], [D, L, N, N], [D, D, N, N], [L, N, N, D],
+        [D, N, N, L], [D, N, N, D], [L, D, L, N], [D, L, D, N],
+        // ╠ ╡ ╢ ╣ ╤ ╥ ╦ ╧
+        [D, D, D, N], [L, N, L, D], [D, N, D, L], [D, N, D, D],
+        [N, D, L, D], [N, L, D, L], [N, D, D, D], [L, D, N, D],
+        // ╨ ╩ ╪ ╫ ╬
+        [D, L, N, L], [D, D, N, D], [L, D, L, D], [D, L, D, L], [D, D, D, D],
+        // The arcs ╭ ╮ ╯ ╰ and diagonals ╱ ╲ ╳, drawn separately.
+        [N; 4], [N; 4], [N; 4], [N; 4], [N; 4], [N; 4], [N; 4],
+        // ╴ ╵ ╶ ╷ ╸ ╹ ╺ ╻
+        [N, N, N, L], [L, N, N, N], [N, L, N, N], [N, N, L, N],
+        [N, N, N, H], [H, N, N, N], [N, H, N, N], [N, N, H, N],
+        // ╼ ╽ ╾ ╿
+        [N, H, N, L], [L, N, H, N], [N, L, N, H], [H, N, L, N],
+    ]
+};
+
+fn draw_box(canvas: &mut CellCanvas, c: char) -> bool {
+    match c {
+        '┄' => dashes(canvas, 3, false, LIGHT),
+        '┅' => dashes(canvas, 3, false, HEAVY),
+        '┆' => dashes(canvas, 3, true, LIGHT),
+        '┇' => dashes(canvas, 3, true, HEAVY),
+        '┈' => dashes(canvas, 4, false, LIGHT),
+        '┉' => dashes(canvas, 4, false, HEAVY),
+        '┊' => dashes(canvas, 4, true, LIGHT),
+        '┋' => dashes(canvas, 4, true, HEAVY),
+        '╌' => dashes(canvas, 2, false, LIGHT),
+        '╍' => dashes(canvas, 2, false, HEAVY),
+        '╎' => dashes(canvas, 2, true, LIGHT),
+        '╏' => dashes(canvas, 2, true, HEAVY),
+        '╭' => arc(canvas, 1, 1),
+        '╮' => arc(canvas, -1, 1),
+        '╯' => arc(canvas, -1, -1),
+        '╰' => arc(canvas, 1, -1),
+        '╱' => diagonal(canvas, true, false),
+        '╲' => diagonal(canvas, false, true),
+        '╳' => diagonal(canvas, true, true),
+        _ => arms(canvas, BOX_ARMS[c as usize - 0x2500]),
+    }
+    true
+}
+
+/// The width a line of the given weight takes across its direction.
+fn extent(weight: u8, light: usize) -> usize {
+    match weight {
+        LIGHT => light,
+        HEAVY => light * 2,
+        DOUBLE => light * 3,
+        _ => 0,
+    }
+}
+
+/// Draws straight arms from the cell edges to the center.
+///
+/// Arms of one direction extend across the band occupied by the perpendicular arms, so corners
+/// and tees join without gaps. The strokes of a double line stop at the inner stroke of the
+/// perpendicular line on the sides where an arm joins, leaving the open centre of `╬`.
+fn arms(canvas: &mut CellCanvas, [up, right, down, left]: [u8; 4]) {
+    let t = canvas.light();
+    let (cx, cy) = (canvas.width / 2, canvas.height / 2);
+    let (w, h) = (canvas.width, canvas.height);
+
+    // The bands covered by the vertical and the horizontal arms.
+    let v_band = extent(up, t).max(extent(down, t));
+    let h_band = extent(left, t).max(extent(right, t));
+    let (vx0, hy0) = (cx.saturating_sub(v_band / 2), cy.saturating_sub(h_band / 2));
+    let (vx1, hy1) = (vx0 + v_band, hy0 + h_band);
+
+    // Returns the strokes of an arm across its direction, as (start, end, joins_before,
+    // joins_after), where the joins tell whether an arm leaves on that side of the stroke.
+    let strokes = |weight: u8, center: usize, before: bool, after: bool| {
+        let width = extent(weight, t);
+        let start = center.saturating_sub(width / 2);
+        match weight {
+            DOUBLE => [
+                Some((start, start + t, before)),
+                Some((start + 2 * t, start + 3 * t, after)),
+            ],
+            NONE => [None, None],
+            _ => [Some((start, start + width, false)), None],
+        }
+    };
+
+    for (weight, is_up) in [(up, true), (down, false)] {
+        for (x0, x1, joined) in strokes(weight, cx, left != NONE, right != NONE)
+            .into_iter()
+            .flatten()
+        {
+            // Stop at the near stroke of a double perpendicular line when an arm joins here.
+            let inset = if joined { h_band.saturating_sub(t) } else { 0 };
+            if is_up {
+                canvas.rect(x0, 0, x1, hy1.max(cy) - inset);
+            } else {
+                canvas.rect(x0, hy0.min(cy) + inset, x1, h);
+            }
+        }
+    }
+    for (weight, is_left) in [(left, true), (right, false)] {
+        for (y0, y1, joined) in strokes(weight, cy, up != NONE, down != NONE)
+            .into_iter()
+            .flatten()
+        {
+            let inset = if joined { v_band.saturating_sub(t) } else { 0 };
+            if is_left {
+                canvas.rect(0, y0, vx1.max(cx) - inset, y1);
+            } else {
+                canvas.rect(vx0.min(cx) + inset, y0, w, y1);
+            }
+        }
+    }
+}
+
+/// Draws a line split into `count` dashes, which repeat seamlessly across cells.
+fn dashes(canvas: &mut CellCanvas, count: usize, vertical: bool, weight: u8) {
+    let thickness = extent(weight, canvas.light());
+    let length = if vertical {
+        canvas.height
+    } else {
+        canvas.width
+    };
+    let across = if vertical {
+        canvas.width
+    } else {
+        canvas.height
+    };
+    let a0 = (across / 2).saturating_sub(thickness / 2);
+    let gap = (length / count / 3).max(1);
+    for i in 0..count {
+        let d0 = i * length / count;
+        let d1 = ((i + 1) * length / count).saturating_sub(gap);
+        if vertical {
+            canvas.rect(a0, d0, a0 + thickness, d1);
+        } else {
+            canvas.rect(d0, a0, d1, a0 + thickness);
+        }
+    }
+}
+
+/// Draws a rounded corner whose arms leave towards `dx` horizontally and `dy` vertically.
+fn arc(canvas: &mut CellCanvas, dx: i64, dy: i64) {
+    let t = canvas.light();
+    let (w, h) = (canvas.width as i64, canvas.height as i64);
+    let s = SUBSAMPLES;
+    // Center line of the strokes, in subpixels.
+    let sx = ((w / 2 - t as i64 / 2) * 2 + t as i64) * s / 2;
+    let sy = ((h / 2 - t as i64 / 2) * 2 + t as i64) * s / 2;
+    let to_edge_x = if dx > 0 { w * s - sx } else { sx };
+    let to_edge_y = if dy > 0 { h * s - sy } else { sy };
+    let radius = to_edge_x.min(to_edge_y);
+    let (ax, ay) = (sx + dx * radius, sy + dy * radius);
+    let half = t as i64 * s / 2;
+    let (inner, outer) = ((radius - half).max(0).pow(2), (radius + half).pow(2));
+
+    canvas.shape(|x, y| {
+        let (x, y) = (x * 2 + 1, y * 2 + 1);
+        let (x, y, ax, ay) = (x, y, ax * 2, ay * 2);
+        let in_corner = (x - ax) * dx <= 0 && (y - ay) * dy <= 0;
+        let d = ((x - ax).pow(2) + (y - ay).pow(2)) / 4;
+        in_corner && d >= inner && d <= outer
+    });
+
+    // Straight parts between the arc and the cell edges.
+    let (ax, ay) = ((ax / s) as usize, (ay / s) as usize);
+    let (x0, y0) = (
+        (w as usize / 2).saturating_sub(t / 2),
+        (h as usize / 2).saturating_sub(t / 2),
+    );
+    if dx > 0 {
+        canvas.rect(ax, y0, w as usize, y0 + t);
+    } else {
+        canvas.rect(0, y0, ax, y0 + t);
+    }
+    if dy > 0 {
+        canvas.rect(x0, ay, x0 + t, h as usize);
+    } else {
+        canvas.rect(x0, 0, x0 + t, ay);
+    }
+}
+
+/// Draws the corner to corner diagonals, rising from bottom left to top right and falling from
+/// top left to bottom right.
+fn diagonal(canvas: &mut CellCanvas, rising: bool, falling: bool) {
+    let s = SUBSAMPLES;
+    let (w, h) = (canvas.width as i64 * s, canvas.height as i64 * s);
+    let half = canvas.light() as i64 * s / 2;
+    let limit = half.max(1).pow(2) * (w * w + h * h);
+    let on_line = |x: i64, y: i64| {
+        let d = h * x + w * y - w * h;
+        d * d <= limit
+    };
+    canvas.shape(|x, y| (rising && on_line(x, y)) || (falling && on_line(w - 1 - x, y)));
+}
+
+fn draw_block(canvas: &mut CellCanvas, c: char) -> bool {
+    let (w, h) = (canvas.width, canvas.height);
+    let eighths = |n: usize, of: usize| (of * n + 4) / 8;
+    match c {
+        '▀' => canvas.rect(0, 0, w, h / 2),
+        '▁'..='▇' => {
+            let n = c as usize - 0x2580;
+            canvas.rect(0, h - eighths(n, h), w, h);
+        }
+        '█' => canvas.rect(0, 0, w, h),
+        '▉'..='▏' => {
+            let n = 0x2590 - c as usize;
+            canvas.rect(0, 0, eighths(n, w), h);
+        }
+        '▐' => canvas.rect(w / 2, 0, w, h),
+        '░' | '▒' | '▓' => shade(canvas, c),
+        '▔' => canvas.rect(0, 0, w, eighths(1, h)),
+        '▕' => canvas.rect(w - eighths(1, w), 0, w, h),
+        _ => {
+            // Quadrants, as upper left, upper right, lower left, lower right.
+            let quadrants: [bool; 4] = match c {
+                '▖' => [false, false, true, false],
+                '▗' => [false, false, false, true],
+                '▘' => [true, false, false, false],
+                '▙' => [true, false, true, true],
+                '▚' => [true, false, false, true],
+                '▛' => [true, true, true, false],
+                '▜' => [true, true, false, true],
+                '▝' => [false, true, false, false],
+                '▞' => [false, true, true, false],
+                _ => [false, true, true, true],
+            };
+            let (mx, my) = (w / 2, h / 2);
+            let boxes = [
+                (0, 0, mx, my),
+                (mx, 0, w, my),
+                (0, my, mx, h),
+                (mx, my, w, h),
+            ];
+            for (filled, (x0, y0, x1, y1)) in quadrants.into_iter().zip(boxes) {
+                if filled {
+                    canvas.rect(x0, y0, x1, y1);
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Draws the shade characters as 25%, 50% and 75% pixel patterns. The patterns are aligned to
+/// the pixmap rather than the cell, so shaded areas tile without seams.
+fn shade(canvas: &mut CellCanvas, c: char) {
+    for y in 0..canvas.height {
+        for x in 0..canvas.width {
+            let (px, py) = (canvas.x + x, canvas.y + y);
+            let on = match c {
+                '░' => px % 2 == 0 && py % 2 == 0,
+                '▒' => (px + py) % 2 == 0,
+                _ => !(px % 2 == 1 && py % 2 == 1),
+            };
+            if on {
+                canvas.pixmap.put_pixel(px, py, canvas.fg);
+            }
+        }
+    }
+}
+
+fn draw_powerline(canvas: &mut CellCanvas, c: char) -> bool {
+    let s = SUBSAMPLES;
+    let (w, h) = (canvas.width as i64 * s, canvas.height as i64 * s);
+    let half_line = canvas.light() as i64 * s / 2;
+    // Glyphs for the left-pointing variants are drawn as the mirror image of the right ones.
+    let mirrored = matches!(c, '\u{E0B2}' | '\u{E0B3}' | '\u{E0B6}' | '\u{E0B7}');
+    let flip = |x: i64| if mirrored { w - 1 - x } else { x };
+    match c {
+        // Solid and thin arrows.
+        '\u{E0B0}' | '\u{E0B2}' => canvas.shape(|x, y| {
+            let x = flip(x);
+            // Inside the triangle (0, 0), (w, h / 2), (0, h).
+            x * h <= w * (h - (2 * y - h).abs())
+        }),
+        '\u{E0B1}' | '\u{E0B3}' => canvas.shape(|x, y| {
+            let x = flip(x);
+            // Distance to the nearer of the two edges, folded onto the upper edge from (0, 0)
+            // to (w, h / 2).
+            let y = y.min(h - y);
+            let d = h * x - 2 * w * y;
+            x < w && d * d <= half_line.max(1).pow(2) * (h * h + 4 * w * w)
+        }),
+        // Solid and thin half circles.
+        '\u{E0B4}' | '\u{E0B6}' | '\u{E0B5}' | '\u{E0B7}' => {
+            let solid = matches!(c, '\u{E0B4}' | '\u{E0B6}');
+            let (rx, ry) = (w, h / 2);
+            canvas.shape(|x, y| {
+                let x = flip(x);
+                let dy = y - ry;
+                let outer = x * x * ry * ry + dy * dy * rx * rx <= rx * rx * ry * ry;
+                if solid {
+                    return outer;
+                }
+                let (ix, iy) = ((rx - half_line * 2).max(1), (ry - half_line * 2).max(1));
+                outer && x * x * iy * iy + dy * dy * ix * ix > ix * ix * iy * iy
+            })
+        }
+        // Solid corner triangles.
+        '\u{E0B8}' => canvas.shape(|x, y| y * w >= x * h),
+        '\u{E0BA}' => canvas.shape(|x, y| y * w >= (w - x) * h),
+        '\u{E0BC}' => canvas.shape(|x, y| x * h + y * w <= w * h),
+        '\u{E0BE}' => canvas.shape(|x, y| x * h >= y * w),
+        // Thin diagonal separators.
+        '\u{E0BB}' | '\u{E0BD}' => diagonal(canvas, true, false),
+        _ => diagonal(canvas, false, true),
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// Draws `c` in a cell of `width` by `height` pixels, returning which pixels are set.
+    fn cell(c: char, width: usize, height: usize, braille: BrailleStyle) -> Vec<Vec<bool>> {
+        let mut pixmap = RgbPixmap::new(width, height);
+        let mut canvas = CellCanvas {
+            pixmap: &mut pixmap,
+            x: 0,
+            y: 0,
+            width,
+            height,
+            fg: [255; 3],
+            bg: [0; 3],
+        };
+        assert!(draw(&mut canvas, c, braille));
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| pixmap.get_pixel(x, y) != [0; 3])
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn only_dot_patterns_are_procedural() {
+        for c in ['a', '9', '中', '🦀', ' ', '\u{1CDE6}', '\u{1FB3C}'] {
+            assert!(!is_procedural(c), "{c:?}");
+        }
+        for c in [
+            '─',
+            '█',
+            '\u{E0B0}',
+            '⠁',
+            '\u{1FB00}',
+            '\u{1CD00}',
+            '\u{1CEA8}',
+        ] {
+            assert!(is_procedural(c), "{c:?}");
+        }
+    }
+
+    #[test]
+    fn sextant_masks() {
+        // The first sextant is the top left, the last the whole cell but the top left.
+        assert_eq!(sextant_mask('\u{1FB00}'), Some(0b000001));
+        assert_eq!(sextant_mask('\u{1FB3B}'), Some(0b111110));
+        // Drawn as block elements.
+        assert_eq!(sextant_mask('▌'), None);
+        assert_eq!(
+            cell('\u{1FB00}', 4, 6, BrailleStyle::default()),
+            [
+                [true, true, false, false],
+                [true, true, false, false],
+                [false; 4],
+                [false; 4],
+                [false; 4],
+                [false; 4],
+            ]
+        );
+    }
+
+    #[test]
+    fn octant_masks() {
+        assert_eq!(octant_mask('\u{1CD00}'), Some(0b0000_0100));
+        assert_eq!(octant_mask('\u{1CEA8}'), Some(0b0000_0001));
+        assert_eq!(octant_mask('\u{1FBE6}'), Some(0b0001_0100));
+        for c in [' ', '▘', 'a', '\u{1CDE6}'] {
+            assert_eq!(octant_mask(c), None, "{c:?}");
+        }
+        // Every pattern has one char, in mask order.
+        for (mask, c) in OCTANTS.iter().enumerate() {
+            if !matches!(c, ' ' | '\u{2580}'..='\u{259F}') {
+                assert_eq!(octant_mask(*c), Some(mask as u8), "{c:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn braille_dots() {
+        let square = BrailleStyle {
+            shape: DotShape::Square,
+            size: 100,
+        };
+        // Dots 1 and 8, the top left and bottom right parts of the 2x4 grid.
+        let pixels = cell('\u{2881}', 2, 4, square);
+        assert_eq!(
+            pixels,
+            [[true, false], [false, false], [false, false], [false, true],]
+        );
+        // Round dots stay inside their part of the grid.
+        let pixels = cell('\u{2801}', 8, 16, BrailleStyle::default());
+        assert!(pixels[1][1]);
+        assert!(pixels.iter().enumerate().all(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .all(|(x, set)| !set || (x < 4 && y < 4))
+        }));
+    }
+}
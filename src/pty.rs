@@ -0,0 +1,295 @@
+//! A terminal emulator that runs a program on a Linux pseudo terminal and draws its output
+//! through [`VtScreen`], e.g. to embed a shell panel in a game or editor.
+
+use std::fs::File;
+use std::io::{self, ErrorKind, Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::widgets::Widget;
+
+use crate::input::KeyEvent;
+use crate::vt::VtScreen;
+
+/// The number of rows kept in the scrollback by default.
+const DEFAULT_SCROLLBACK: usize = 10_000;
+
+fn window_size(width: u16, height: u16) -> libc::winsize {
+    libc::winsize {
+        ws_row: height,
+        ws_col: width,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
+
+/// A child process running on a pseudo terminal, with its screen and scrollback.
+///
+/// Poll [`Self::update`] every frame to read the output of the program, and render the terminal
+/// as a widget. When the rendered area changes size, for example after
+/// [`SoftBackend::resize`](crate::SoftBackend::resize), the screen and the pseudo terminal are
+/// resized to match and the program receives `SIGWINCH`.
+///
+/// # Examples
+/// ```rust
+/// use std::process::Command;
+/// use std::time::{Duration, Instant};
+/// use ratatui::Terminal;
+/// use ratatui::style::Color;
+/// use soft_ratatui::{BitmapFont, KeyCode, PtyTerminal, SoftBackend};
+///
+/// fn row(terminal: &PtyTerminal, y: u16) -> String {
+///     let buffer = terminal.screen().buffer();
+///     let row: String = (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect();
+///     row.trim_end().to_string()
+/// }
+///
+/// // Reads the output until a row shows `expected`, for up to 5 seconds.
+/// fn wait_for_row(terminal: &mut PtyTerminal, y: u16, expected: &str) {
+///     let deadline = Instant::now() + Duration::from_secs(5);
+///     while row(terminal, y) != expected && Instant::now() < deadline {
+///         terminal.update().unwrap();
+///         std::thread::sleep(Duration::from_millis(10));
+///     }
+///     assert_eq!(row(terminal, y), expected);
+/// }
+///
+/// let mut printf = Command::new("printf");
+/// printf.arg("\\033[31mred\\033[m plain");
+/// let mut printf = PtyTerminal::spawn(printf, 40, 10).unwrap();
+/// wait_for_row(&mut printf, 0, "red plain");
+/// assert_eq!(printf.screen().buffer()[(0, 0)].fg, Color::Red);
+/// assert_eq!(printf.screen().buffer()[(4, 0)].fg, Color::Reset);
+///
+/// let mut shell = Command::new("/bin/sh");
+/// shell.env("PS1", "$ ");
+/// let mut shell = PtyTerminal::spawn(shell, 40, 10).unwrap();
+/// wait_for_row(&mut shell, 0, "$");
+/// shell.send_str("echo hello").unwrap();
+/// shell.send_key(KeyCode::Enter.into()).unwrap();
+/// wait_for_row(&mut shell, 1, "hello");
+///
+/// let backend = SoftBackend::new_with_bitmap_font(40, 10, BitmapFont::FIXED_8X13);
+/// let mut terminal = Terminal::new(backend).unwrap();
+/// terminal
+///     .draw(|frame| frame.render_widget(&mut shell, frame.area()))
+///     .unwrap();
+/// ```
+pub struct PtyTerminal {
+    screen: VtScreen,
+    master: File,
+    child: Child,
+    closed: bool,
+    /// Input the program's full input queue didn't take yet, written by later updates.
+    pending_input: Vec<u8>,
+}
+
+impl PtyTerminal {
+    /// Spawns `command` on a new pseudo terminal of `width` columns and `height` rows.
+    ///
+    /// The standard streams of the command are attached to the terminal, which becomes its
+    /// controlling terminal, and `TERM` is set to `xterm-256color` unless the command sets it.
+    pub fn spawn(mut command: Command, width: u16, height: u16) -> io::Result<Self> {
+        let mut master = -1;
+        let mut slave = -1;
+        let size = window_size(width, height);
+        // SAFETY: The out pointers are valid and the name, termios and winsize arguments may be
+        // null or point to initialized values.
+        let result = unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                core::ptr::null_mut(),
+                core::ptr::null(),
+                &size,
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: openpty succeeded, so both descriptors are open and owned by nobody else.
+        let (master, slave) =
+            unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+
+        // SAFETY: Plain fcntl calls on a descriptor we own.
+        unsafe {
+            let fd = master.as_raw_fd();
+            libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+            let flags = libc::fcntl(fd, libc::F_GETFL);
+            if libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        if !command.get_envs().any(|(key, _)| key == "TERM") {
+            command.env("TERM", "xterm-256color");
+        }
+        command
+            .stdin(Stdio::from(slave.try_clone()?))
+            .stdout(Stdio::from(slave.try_clone()?))
+            .stderr(Stdio::from(slave));
+        // SAFETY: Only async-signal-safe calls are made between fork and exec.
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command.spawn()?;
+
+        let mut screen = VtScreen::new(width, height);
+        screen.set_scrollback_limit(DEFAULT_SCROLLBACK);
+        Ok(Self {
+            screen,
+            master: File::from(master),
+            child,
+            closed: false,
+            pending_input: Vec::new(),
+        })
+    }
+
+    /// Returns the screen of the terminal.
+    pub fn screen(&self) -> &VtScreen {
+        &self.screen
+    }
+    /// Returns the screen of the terminal, e.g. to move the view into the scrollback.
+    pub fn screen_mut(&mut self) -> &mut VtScreen {
+        &mut self.screen
+    }
+    /// Returns the child process.
+    pub fn child(&mut self) -> &mut Child {
+        &mut self.child
+    }
+
+    /// Returns true while the program has not exited.
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Reads all the output available without blocking and feeds it to the screen, and writes
+    /// the input that the program couldn't take yet. Returns true if the screen changed.
+    pub fn update(&mut self) -> io::Result<bool> {
+        let mut changed = false;
+        let mut chunk = [0; 16 * 1024];
+        while !self.closed {
+            match self.master.read(&mut chunk) {
+                // Reading the master fails with EIO once every slave descriptor is closed.
+                Ok(0) => self.closed = true,
+                Err(error) if error.raw_os_error() == Some(libc::EIO) => self.closed = true,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+                Ok(read) => {
+                    self.screen.feed(&chunk[..read]);
+                    changed = true;
+                }
+            }
+        }
+        let responses = self.screen.take_responses();
+        self.send_bytes(&responses)?;
+        Ok(changed)
+    }
+
+    /// Sends raw bytes to the program.
+    ///
+    /// Never blocks: what doesn't fit in the input queue of the terminal, e.g. a large paste to
+    /// a program busy writing output, is kept and written by the next calls to [`Self::update`].
+    pub fn send_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.closed {
+            self.pending_input.clear();
+            return Ok(());
+        }
+        self.pending_input.extend_from_slice(bytes);
+        while !self.pending_input.is_empty() {
+            match self.master.write(&self.pending_input) {
+                Ok(written) => {
+                    self.pending_input.drain(..written);
+                }
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends text to the program as if typed.
+    pub fn send_str(&mut self, text: &str) -> io::Result<()> {
+        self.send_bytes(text.as_bytes())
+    }
+
+    /// Sends a key press, translated to the sequence a terminal would send. Moves the view back
+    /// to the live screen.
+    pub fn send_key(&mut self, key: KeyEvent) -> io::Result<()> {
+        self.screen.scroll_view_down(usize::MAX);
+        let bytes = self.screen.encode_key(key);
+        self.send_bytes(&bytes)
+    }
+
+    /// Sends pasted text, in bracketed paste markers if the program asked for them.
+    pub fn paste(&mut self, text: &str) -> io::Result<()> {
+        let bytes = self.screen.encode_paste(text);
+        self.send_bytes(&bytes)
+    }
+
+    /// Resizes the screen and the pseudo terminal, signaling the program with `SIGWINCH`.
+    pub fn resize(&mut self, width: u16, height: u16) -> io::Result<()> {
+        let area = self.screen.buffer().area;
+        if (area.width, area.height) == (width, height) {
+            return Ok(());
+        }
+        self.screen.resize(width, height);
+        let size = window_size(width, height);
+        // SAFETY: TIOCSWINSZ reads a winsize from a valid pointer.
+        let result = unsafe { libc::ioctl(self.master.as_raw_fd(), libc::TIOCSWINSZ, &size) };
+        if result == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+/// Draws the screen into the area, first resizing the terminal to the area if it changed.
+impl Widget for &mut PtyTerminal {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.intersection(buf.area);
+        // A failed resize keeps the old size, which is still drawn correctly.
+        let _ = self.resize(area.width, area.height);
+        self.screen.render(area, buf);
+    }
+}
+
+/// Hangs up the terminal, which sends `SIGHUP` to the program if it is still running, and
+/// reaps it. A program that doesn't exit within 100 ms of the hangup is killed.
+impl Drop for PtyTerminal {
+    fn drop(&mut self) {
+        // Once reaped, the process group id may belong to another process.
+        if !matches!(self.child.try_wait(), Ok(None)) {
+            return;
+        }
+        let group = -(self.child.id() as libc::pid_t);
+        // SAFETY: Sends a signal to the process group of our own child, which is still running.
+        unsafe {
+            libc::kill(group, libc::SIGHUP);
+        }
+        let deadline = Instant::now() + Duration::from_millis(100);
+        while Instant::now() < deadline {
+            if !matches!(self.child.try_wait(), Ok(None)) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        // SAFETY: As above, the child has not been reaped.
+        unsafe {
+            libc::kill(group, libc::SIGKILL);
+        }
+        let _ = self.child.wait();
+    }
+}
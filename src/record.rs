@@ -0,0 +1,782 @@
+//! Recording of the calls ratatui makes to a backend in a compact binary format, and their
+//! replay into a [`SoftBackend`], e.g. to reproduce visual bugs or render them offline.
+//!
+//! A recording starts with the magic bytes `SRREC` and a version byte. Each event follows as
+//! the microseconds since the previous event (LEB128 varint), a tag byte and its payload.
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::time::Duration;
+
+#[cfg(feature = "std")]
+use ratatui::backend::WindowSize;
+use ratatui::backend::{Backend, ClearType};
+use ratatui::buffer::Cell;
+use ratatui::layout::{Position, Size};
+use ratatui::style::{Color, Modifier};
+
+use crate::soft_backend::SoftBackend;
+
+const MAGIC: &[u8; 5] = b"SRREC";
+const VERSION: u8 = 1;
+
+const TAG_DRAW: u8 = 0;
+const TAG_CLEAR: u8 = 1;
+const TAG_CLEAR_REGION: u8 = 2;
+const TAG_HIDE_CURSOR: u8 = 3;
+const TAG_SHOW_CURSOR: u8 = 4;
+const TAG_SET_CURSOR: u8 = 5;
+const TAG_RESIZE: u8 = 6;
+const TAG_FLUSH: u8 = 7;
+const TAG_APPEND_LINES: u8 = 8;
+
+/// The most cells a replayed resize may have, so a corrupt recording can't allocate gigabytes of
+/// pixmap. It is far more than any real screen.
+const MAX_RESIZE_CELLS: u32 = 1 << 18;
+
+/// The named colors, in the order of their ANSI codes.
+const NAMED_COLORS: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// Set in the header byte of a drawn cell when it is right after the previous one.
+const CELL_NEXT_POSITION: u8 = 1;
+/// Set in the header byte of a drawn cell when it has the colors and modifiers of the previous one.
+const CELL_SAME_STYLE: u8 = 2;
+
+/// A backend call stored in a recording.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedEvent {
+    /// Cells drawn, with their positions.
+    Draw(Vec<(u16, u16, Cell)>),
+    Clear,
+    ClearRegion(ClearType),
+    HideCursor,
+    ShowCursor,
+    SetCursor(Position),
+    /// The backend changed size, also the first event of every recording. [`Replay`] rejects
+    /// sizes of more than 2^18 cells.
+    Resize(Size),
+    /// The end of a frame.
+    Flush,
+    AppendLines(u16),
+}
+
+impl RecordedEvent {
+    /// Performs the call on a backend. Drawn cells outside of the backend are skipped.
+    pub fn apply(&self, backend: &mut SoftBackend) {
+        let area = backend.buffer.area;
+        let _ = match self {
+            Self::Draw(cells) => backend.draw(
+                cells
+                    .iter()
+                    .filter(|(x, y, _)| area.contains(Position::new(*x, *y)))
+                    .map(|(x, y, cell)| (*x, *y, cell)),
+            ),
+            Self::Clear => backend.clear(),
+            Self::ClearRegion(clear_type) => backend.clear_region(*clear_type),
+            Self::HideCursor => backend.hide_cursor(),
+            Self::ShowCursor => backend.show_cursor(),
+            Self::SetCursor(position) => backend.set_cursor_position(*position),
+            Self::Resize(size) => {
+                backend.resize(size.width, size.height);
+                Ok(())
+            }
+            Self::Flush => backend.flush(),
+            Self::AppendLines(lines) => backend.append_lines(*lines),
+        };
+    }
+}
+
+#[cfg(feature = "std")]
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+#[cfg(feature = "std")]
+fn write_color(out: &mut Vec<u8>, color: Color) {
+    match color {
+        Color::Reset => out.push(0),
+        Color::Indexed(index) => out.extend_from_slice(&[17, index]),
+        Color::Rgb(r, g, b) => out.extend_from_slice(&[18, r, g, b]),
+        named => {
+            let index = NAMED_COLORS
+                .iter()
+                .position(|color| *color == named)
+                .unwrap_or(0);
+            out.push(1 + index as u8);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn clear_type_index(clear_type: ClearType) -> u8 {
+    match clear_type {
+        ClearType::All => 0,
+        ClearType::AfterCursor => 1,
+        ClearType::BeforeCursor => 2,
+        ClearType::CurrentLine => 3,
+        ClearType::UntilNewLine => 4,
+    }
+}
+
+#[cfg(feature = "std")]
+/// Encodes events, keeping the time of the last one.
+struct Encoder {
+    /// The microseconds since the start of the recording of the last event.
+    last_micros: u64,
+}
+
+#[cfg(feature = "std")]
+impl Encoder {
+    fn event(&mut self, out: &mut Vec<u8>, micros: u64, event: &RecordedEvent) {
+        write_varint(out, micros.saturating_sub(self.last_micros));
+        self.last_micros = self.last_micros.max(micros);
+        match event {
+            RecordedEvent::Draw(cells) => {
+                out.push(TAG_DRAW);
+                write_varint(out, cells.len() as u64);
+                let mut previous: Option<(u16, u16, &Cell)> = None;
+                for (x, y, cell) in cells {
+                    let mut header = 0;
+                    if let Some((px, py, pcell)) = previous {
+                        if py == *y && px.checked_add(1) == Some(*x) {
+                            header |= CELL_NEXT_POSITION;
+                        }
+                        if (pcell.fg, pcell.bg, pcell.modifier) == (cell.fg, cell.bg, cell.modifier)
+                        {
+                            header |= CELL_SAME_STYLE;
+                        }
+                    }
+                    out.push(header);
+                    if header & CELL_NEXT_POSITION == 0 {
+                        write_varint(out, *x as u64);
+                        write_varint(out, *y as u64);
+                    }
+                    write_varint(out, cell.symbol().len() as u64);
+                    out.extend_from_slice(cell.symbol().as_bytes());
+                    if header & CELL_SAME_STYLE == 0 {
+                        write_color(out, cell.fg);
+                        write_color(out, cell.bg);
+                        write_varint(out, cell.modifier.bits() as u64);
+                    }
+                    previous = Some((*x, *y, cell));
+                }
+            }
+            RecordedEvent::Clear => out.push(TAG_CLEAR),
+            RecordedEvent::ClearRegion(clear_type) => {
+                out.extend_from_slice(&[TAG_CLEAR_REGION, clear_type_index(*clear_type)]);
+            }
+            RecordedEvent::HideCursor => out.push(TAG_HIDE_CURSOR),
+            RecordedEvent::ShowCursor => out.push(TAG_SHOW_CURSOR),
+            RecordedEvent::SetCursor(position) => {
+                out.push(TAG_SET_CURSOR);
+                write_varint(out, position.x as u64);
+                write_varint(out, position.y as u64);
+            }
+            RecordedEvent::Resize(size) => {
+                out.push(TAG_RESIZE);
+                write_varint(out, size.width as u64);
+                write_varint(out, size.height as u64);
+            }
+            RecordedEvent::Flush => out.push(TAG_FLUSH),
+            RecordedEvent::AppendLines(lines) => {
+                out.push(TAG_APPEND_LINES);
+                write_varint(out, *lines as u64);
+            }
+        }
+    }
+}
+
+/// An error found while reading a recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The data doesn't start with the magic bytes of a recording.
+    NotARecording,
+    /// The recording was made by a newer version of the format.
+    UnsupportedVersion(u8),
+    /// The recording ends in the middle of an event.
+    Truncated,
+    /// An event or value is malformed, at the given byte offset.
+    Invalid(usize),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotARecording => write!(f, "not a soft_ratatui recording"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported recording version {version}")
+            }
+            Self::Truncated => write!(f, "truncated recording"),
+            Self::Invalid(offset) => write!(f, "invalid recording data at byte {offset}"),
+        }
+    }
+}
+
+impl core::error::Error for ReplayError {}
+
+/// Reads the events of a recording, with the time since the start of the recording.
+///
+/// # Examples
+/// ```rust
+/// use ratatui::Terminal;
+/// use ratatui::widgets::Paragraph;
+/// use soft_ratatui::{BitmapFont, RecordingBackend, Replay, SoftBackend};
+///
+/// let backend = SoftBackend::new_with_bitmap_font(20, 2, BitmapFont::FIXED_8X13);
+/// let mut terminal = Terminal::new(RecordingBackend::new(backend, Vec::new()).unwrap()).unwrap();
+/// terminal
+///     .draw(|frame| frame.render_widget(Paragraph::new("Hello"), frame.area()))
+///     .unwrap();
+/// let recording = terminal.backend().writer();
+///
+/// let mut player = SoftBackend::new_with_bitmap_font(1, 1, BitmapFont::FIXED_8X13);
+/// for event in Replay::new(recording).unwrap() {
+///     let (_time, event) = event.unwrap();
+///     event.apply(&mut player);
+/// }
+/// assert_eq!(player.buffer, terminal.backend().inner().buffer);
+/// assert_eq!(player.get_pixmap_data(), terminal.backend().inner().get_pixmap_data());
+/// ```
+pub struct Replay<'a> {
+    data: &'a [u8],
+    offset: usize,
+    micros: u64,
+    failed: bool,
+}
+
+impl<'a> Replay<'a> {
+    /// Starts reading a recording.
+    pub fn new(data: &'a [u8]) -> Result<Self, ReplayError> {
+        if !data.starts_with(MAGIC) {
+            return Err(ReplayError::NotARecording);
+        }
+        match data.get(MAGIC.len()) {
+            Some(&VERSION) => {}
+            Some(&version) => return Err(ReplayError::UnsupportedVersion(version)),
+            None => return Err(ReplayError::Truncated),
+        }
+        Ok(Self {
+            data,
+            offset: MAGIC.len() + 1,
+            micros: 0,
+            failed: false,
+        })
+    }
+
+    fn byte(&mut self) -> Result<u8, ReplayError> {
+        let byte = *self.data.get(self.offset).ok_or(ReplayError::Truncated)?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<u64, ReplayError> {
+        let start = self.offset;
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ReplayError::Invalid(start))
+    }
+
+    fn u16(&mut self) -> Result<u16, ReplayError> {
+        let start = self.offset;
+        u16::try_from(self.varint()?).map_err(|_| ReplayError::Invalid(start))
+    }
+
+    fn color(&mut self) -> Result<Color, ReplayError> {
+        let start = self.offset;
+        Ok(match self.byte()? {
+            0 => Color::Reset,
+            index @ 1..=16 => NAMED_COLORS[index as usize - 1],
+            17 => Color::Indexed(self.byte()?),
+            18 => Color::Rgb(self.byte()?, self.byte()?, self.byte()?),
+            _ => return Err(ReplayError::Invalid(start)),
+        })
+    }
+
+    fn event(&mut self) -> Result<RecordedEvent, ReplayError> {
+        let start = self.offset;
+        Ok(match self.byte()? {
+            TAG_DRAW => {
+                let count = self.varint()? as usize;
+                // Every cell takes at least two bytes.
+                if count > self.data.len() - self.offset {
+                    return Err(ReplayError::Invalid(start));
+                }
+                let mut cells: Vec<(u16, u16, Cell)> = Vec::with_capacity(count);
+                for _ in 0..count {
+                    let header_offset = self.offset;
+                    let header = self.byte()?;
+                    let previous = cells.last();
+                    let (x, y) = match previous {
+                        Some((x, y, _)) if header & CELL_NEXT_POSITION != 0 => (
+                            x.checked_add(1)
+                                .ok_or(ReplayError::Invalid(header_offset))?,
+                            *y,
+                        ),
+                        _ if header & CELL_NEXT_POSITION != 0 => {
+                            return Err(ReplayError::Invalid(header_offset));
+                        }
+                        _ => (self.u16()?, self.u16()?),
+                    };
+                    let length = self.varint()? as usize;
+                    let symbol_offset = self.offset;
+                    let bytes = self
+                        .data
+                        .get(self.offset..self.offset.saturating_add(length))
+                        .ok_or(ReplayError::Truncated)?;
+                    let symbol = core::str::from_utf8(bytes)
+                        .map_err(|_| ReplayError::Invalid(symbol_offset))?;
+                    self.offset += length;
+                    let mut cell = Cell::default();
+                    cell.set_symbol(symbol);
+                    if header & CELL_SAME_STYLE != 0 {
+                        let (_, _, previous) =
+                            cells.last().ok_or(ReplayError::Invalid(header_offset))?;
+                        (cell.fg, cell.bg, cell.modifier) =
+                            (previous.fg, previous.bg, previous.modifier);
+                    } else {
+                        cell.fg = self.color()?;
+                        cell.bg = self.color()?;
+                        let bits = self.u16()?;
+                        cell.modifier = Modifier::from_bits_truncate(bits);
+                    }
+                    cells.push((x, y, cell));
+                }
+                RecordedEvent::Draw(cells)
+            }
+            TAG_CLEAR => RecordedEvent::Clear,
+            TAG_CLEAR_REGION => RecordedEvent::ClearRegion(match self.byte()? {
+                0 => ClearType::All,
+                1 => ClearType::AfterCursor,
+                2 => ClearType::BeforeCursor,
+                3 => ClearType::CurrentLine,
+                4 => ClearType::UntilNewLine,
+                _ => return Err(ReplayError::Invalid(start + 1)),
+            }),
+            TAG_HIDE_CURSOR => RecordedEvent::HideCursor,
+            TAG_SHOW_CURSOR => RecordedEvent::ShowCursor,
+            TAG_SET_CURSOR => RecordedEvent::SetCursor(Position::new(self.u16()?, self.u16()?)),
+            TAG_RESIZE => {
+                let size = Size::new(self.u16()?, self.u16()?);
+                if u32::from(size.width) * u32::from(size.height) > MAX_RESIZE_CELLS {
+                    return Err(ReplayError::Invalid(start));
+                }
+                RecordedEvent::Resize(size)
+            }
+            TAG_FLUSH => RecordedEvent::Flush,
+            TAG_APPEND_LINES => RecordedEvent::AppendLines(self.u16()?),
+            _ => return Err(ReplayError::Invalid(start)),
+        })
+    }
+
+    /// Replays the recording into a backend, waiting between events to keep their timing, and
+    /// calls `on_frame` after every frame, e.g. to present the pixmap.
+    ///
+    /// `speed` divides the waits: 1.0 keeps the original speed, 2.0 plays twice as fast and
+    /// `f64::INFINITY` doesn't wait at all.
+    #[cfg(feature = "std")]
+    pub fn play(
+        self,
+        backend: &mut SoftBackend,
+        speed: f64,
+        mut on_frame: impl FnMut(&SoftBackend),
+    ) -> Result<(), ReplayError> {
+        let start = std::time::Instant::now();
+        for event in self {
+            let (time, event) = event?;
+            let due = time.as_secs_f64() / speed;
+            if due.is_finite() {
+                let elapsed = start.elapsed().as_secs_f64();
+                if due > elapsed {
+                    std::thread::sleep(Duration::from_secs_f64(due - elapsed));
+                }
+            }
+            event.apply(backend);
+            if event == RecordedEvent::Flush {
+                on_frame(backend);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Replay<'_> {
+    type Item = Result<(Duration, RecordedEvent), ReplayError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.offset == self.data.len() {
+            return None;
+        }
+        let result = self.varint().and_then(|delta| {
+            self.micros = self.micros.saturating_add(delta);
+            Ok((Duration::from_micros(self.micros), self.event()?))
+        });
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+/// An error of a [`RecordingBackend`].
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub enum RecordError<E> {
+    /// The wrapped backend failed.
+    Backend(E),
+    /// The recording couldn't be written.
+    Io(std::io::Error),
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Display> fmt::Display for RecordError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Backend(error) => error.fmt(f),
+            Self::Io(error) => write!(f, "failed to write the recording: {error}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: core::error::Error + 'static> core::error::Error for RecordError<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Backend(error) => Some(error),
+            Self::Io(error) => Some(error),
+        }
+    }
+}
+
+/// A backend that forwards every call to another backend and records it, with its time, to a
+/// writer. The recording can be replayed with [`Replay`].
+///
+/// Size changes of the wrapped backend are recorded as they are seen, before the next call.
+#[cfg(feature = "std")]
+pub struct RecordingBackend<B, W> {
+    inner: B,
+    writer: W,
+    encoder: Encoder,
+    start: std::time::Instant,
+    size: Size,
+    scratch: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl<B: Backend, W: std::io::Write> RecordingBackend<B, W> {
+    /// Wraps a backend, writing the header of the recording and its size.
+    pub fn new(inner: B, mut writer: W) -> Result<Self, RecordError<B::Error>> {
+        let size = inner.size().map_err(RecordError::Backend)?;
+        writer.write_all(MAGIC).map_err(RecordError::Io)?;
+        writer.write_all(&[VERSION]).map_err(RecordError::Io)?;
+        let mut backend = Self {
+            inner,
+            writer,
+            encoder: Encoder { last_micros: 0 },
+            start: std::time::Instant::now(),
+            size,
+            scratch: Vec::new(),
+        };
+        backend.record(&RecordedEvent::Resize(size))?;
+        Ok(backend)
+    }
+
+    /// The wrapped backend.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// The wrapped backend, mutably. Calls made through it are not recorded.
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    /// The writer of the recording.
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns the wrapped backend and the writer of the recording.
+    pub fn into_parts(self) -> (B, W) {
+        (self.inner, self.writer)
+    }
+
+    fn record(&mut self, event: &RecordedEvent) -> Result<(), RecordError<B::Error>> {
+        let micros = self.start.elapsed().as_micros() as u64;
+        self.scratch.clear();
+        self.encoder.event(&mut self.scratch, micros, event);
+        self.writer
+            .write_all(&self.scratch)
+            .map_err(RecordError::Io)
+    }
+
+    /// Records a resize if the wrapped backend changed size since the last call.
+    fn record_size(&mut self) -> Result<(), RecordError<B::Error>> {
+        let size = self.inner.size().map_err(RecordError::Backend)?;
+        if size != self.size {
+            self.size = size;
+            self.record(&RecordedEvent::Resize(size))?;
+        }
+        Ok(())
+    }
+
+    /// Records a call then performs it on the wrapped backend.
+    fn forward<T>(
+        &mut self,
+        event: RecordedEvent,
+        call: impl FnOnce(&mut B) -> Result<T, B::Error>,
+    ) -> Result<T, RecordError<B::Error>> {
+        self.record_size()?;
+        self.record(&event)?;
+        call(&mut self.inner).map_err(RecordError::Backend)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<B: Backend, W: std::io::Write> Backend for RecordingBackend<B, W>
+where
+    B::Error: 'static,
+{
+    type Error = RecordError<B::Error>;
+
+    fn draw<'a, I>(&mut self, content: I) -> Result<(), Self::Error>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        let cells: Vec<(u16, u16, Cell)> =
+            content.map(|(x, y, cell)| (x, y, cell.clone())).collect();
+        let event = RecordedEvent::Draw(cells);
+        self.record_size()?;
+        self.record(&event)?;
+        let RecordedEvent::Draw(cells) = event else {
+            unreachable!()
+        };
+        self.inner
+            .draw(cells.iter().map(|(x, y, cell)| (*x, *y, cell)))
+            .map_err(RecordError::Backend)
+    }
+
+    fn append_lines(&mut self, n: u16) -> Result<(), Self::Error> {
+        self.forward(RecordedEvent::AppendLines(n), |inner| inner.append_lines(n))
+    }
+
+    fn hide_cursor(&mut self) -> Result<(), Self::Error> {
+        self.forward(RecordedEvent::HideCursor, B::hide_cursor)
+    }
+
+    fn show_cursor(&mut self) -> Result<(), Self::Error> {
+        self.forward(RecordedEvent::ShowCursor, B::show_cursor)
+    }
+
+    fn get_cursor_position(&mut self) -> Result<Position, Self::Error> {
+        self.inner
+            .get_cursor_position()
+            .map_err(RecordError::Backend)
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> Result<(), Self::Error> {
+        let position = position.into();
+        self.forward(RecordedEvent::SetCursor(position), |inner| {
+            inner.set_cursor_position(position)
+        })
+    }
+
+    fn clear(&mut self) -> Result<(), Self::Error> {
+        self.forward(RecordedEvent::Clear, B::clear)
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> Result<(), Self::Error> {
+        self.forward(RecordedEvent::ClearRegion(clear_type), |inner| {
+            inner.clear_region(clear_type)
+        })
+    }
+
+    fn size(&self) -> Result<Size, Self::Error> {
+        self.inner.size().map_err(RecordError::Backend)
+    }
+
+    fn window_size(&mut self) -> Result<WindowSize, Self::Error> {
+        self.inner.window_size().map_err(RecordError::Backend)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.forward(RecordedEvent::Flush, B::flush)?;
+        self.writer.flush().map_err(RecordError::Io)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::BitmapFont;
+    use alloc::vec;
+
+    /// Encodes events into a recording, one millisecond apart.
+    fn recording(events: &[RecordedEvent]) -> Vec<u8> {
+        let mut data = [&MAGIC[..], &[VERSION]].concat();
+        let mut encoder = Encoder { last_micros: 0 };
+        for (i, event) in events.iter().enumerate() {
+            encoder.event(&mut data, i as u64 * 1000, event);
+        }
+        data
+    }
+
+    fn replay(data: &[u8]) -> Result<Vec<RecordedEvent>, ReplayError> {
+        Replay::new(data)?
+            .map(|event| event.map(|(_, event)| event))
+            .collect()
+    }
+
+    fn cell(symbol: &'static str, fg: Color) -> Cell {
+        let mut cell = Cell::new(symbol);
+        cell.fg = fg;
+        cell
+    }
+
+    #[test]
+    fn events_round_trip() {
+        let mut bold = cell("é", Color::Rgb(1, 2, 3));
+        bold.bg = Color::Indexed(200);
+        bold.modifier = Modifier::BOLD | Modifier::SLOW_BLINK;
+        let events = vec![
+            RecordedEvent::Resize(Size::new(20, 4)),
+            RecordedEvent::Draw(vec![
+                (0, 0, cell("a", Color::Red)),
+                (1, 0, cell("b", Color::Red)),
+                (5, 1, bold.clone()),
+                (6, 1, bold),
+                (u16::MAX, 3, cell("", Color::Reset)),
+            ]),
+            RecordedEvent::Clear,
+            RecordedEvent::ClearRegion(ClearType::UntilNewLine),
+            RecordedEvent::HideCursor,
+            RecordedEvent::ShowCursor,
+            RecordedEvent::SetCursor(Position::new(3, 2)),
+            RecordedEvent::AppendLines(2),
+            RecordedEvent::Flush,
+        ];
+        let data = recording(&events);
+        assert_eq!(replay(&data), Ok(events));
+        let times: Vec<_> = Replay::new(&data).unwrap().map(|e| e.unwrap().0).collect();
+        assert_eq!(times[2], Duration::from_millis(2));
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        assert_eq!(replay(b"PNG"), Err(ReplayError::NotARecording));
+        assert_eq!(replay(b"SRREC"), Err(ReplayError::Truncated));
+        assert_eq!(
+            replay(b"SRREC\x02"),
+            Err(ReplayError::UnsupportedVersion(2))
+        );
+        assert_eq!(replay(b"SRREC\x01"), Ok(vec![]));
+    }
+
+    #[test]
+    fn rejects_truncated_and_invalid_events() {
+        let data = recording(&[RecordedEvent::Draw(vec![(1, 2, cell("x", Color::Blue))])]);
+        for end in 7..data.len() {
+            assert!(replay(&data[..end]).is_err());
+        }
+        assert_eq!(replay(&data[..data.len() - 1]), Err(ReplayError::Truncated));
+        // An unknown tag, then the second cell of a draw continuing a missing first one.
+        assert_eq!(replay(b"SRREC\x01\x00\x09"), Err(ReplayError::Invalid(7)));
+        assert_eq!(
+            replay(b"SRREC\x01\x00\x00\x01\x01"),
+            Err(ReplayError::Invalid(9))
+        );
+        // A clear type and a color that don't exist.
+        assert_eq!(
+            replay(b"SRREC\x01\x00\x02\x05"),
+            Err(ReplayError::Invalid(8))
+        );
+        assert_eq!(
+            replay(b"SRREC\x01\x00\x00\x01\x00\x00\x00\x00\x13"),
+            Err(ReplayError::Invalid(13))
+        );
+        // Iteration stops after an error.
+        let mut events = Replay::new(b"SRREC\x01\x00\x09\x00\x07").unwrap();
+        assert!(events.next().unwrap().is_err());
+        assert!(events.next().is_none());
+    }
+
+    #[test]
+    fn rejects_huge_resizes() {
+        let data = recording(&[RecordedEvent::Resize(Size::new(u16::MAX, u16::MAX))]);
+        assert_eq!(replay(&data), Err(ReplayError::Invalid(7)));
+        let data = recording(&[RecordedEvent::Resize(Size::new(512, 512))]);
+        assert!(replay(&data).is_ok());
+    }
+
+    #[test]
+    fn skips_cells_outside_of_the_backend() {
+        let data = recording(&[
+            RecordedEvent::Resize(Size::new(2, 1)),
+            RecordedEvent::Draw(vec![
+                (100, 100, cell("x", Color::Red)),
+                (1, 0, cell("y", Color::Red)),
+                (2, 0, cell("z", Color::Red)),
+            ]),
+            RecordedEvent::SetCursor(Position::new(50, 50)),
+            RecordedEvent::ClearRegion(ClearType::AfterCursor),
+            RecordedEvent::Flush,
+        ]);
+        let mut backend = SoftBackend::new_with_bitmap_font(10, 10, BitmapFont::FIXED_8X13);
+        Replay::new(&data)
+            .unwrap()
+            .play(&mut backend, f64::INFINITY, |_| {})
+            .unwrap();
+        assert_eq!(backend.buffer.area, ratatui::layout::Rect::new(0, 0, 2, 1));
+        assert_eq!(backend.buffer[(1, 0)].symbol(), "y");
+    }
+
+    #[test]
+    fn random_data_does_not_panic() {
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut backend = SoftBackend::new_with_bitmap_font(4, 4, BitmapFont::FIXED_8X13);
+        for _ in 0..300 {
+            let mut data = [&MAGIC[..], &[VERSION]].concat();
+            for _ in 0..64 {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                // Mostly small values, so that tags and lengths are often valid.
+                data.push(if seed & 1 == 0 {
+                    (seed >> 8) as u8 % 10
+                } else {
+                    (seed >> 8) as u8
+                });
+            }
+            for event in Replay::new(&data).unwrap() {
+                match event {
+                    Ok((_, event)) => event.apply(&mut backend),
+                    Err(_) => break,
+                }
+            }
+        }
+    }
+}
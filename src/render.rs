@@ -0,0 +1,156 @@
+//! Rendering of a single ratatui `Buffer` or widget to a pixmap without a `Terminal`, e.g. for
+//! documentation images and thumbnails.
+
+use core::cell::RefCell;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::widgets::Widget;
+
+use crate::bitmap_font::BitmapFont;
+use crate::image::{ImageId, RgbaImage};
+use crate::pixmap::RgbPixmap;
+use crate::procedural::BrailleStyle;
+use crate::soft_backend::SoftBackend;
+use crate::tileset::Tileset;
+
+/// The font and drawing settings used by [`render_buffer`] and [`render_widget`].
+///
+/// The font, with its cache of rasterized glyphs, is loaded once and reused by every render,
+/// so keep the options around when rendering many buffers.
+///
+/// # Examples
+/// ```rust
+/// use soft_ratatui::{BitmapFont, ImageFit, ImageWidget, RenderOptions, RgbaImage, render_widget};
+///
+/// let mut options = RenderOptions::new_with_bitmap_font(BitmapFont::FIXED_8X13);
+/// options.set_procedural_glyphs(false);
+/// let logo = options.add_image(RgbaImage::from_rgba(&[255, 0, 0, 255], 1, 1));
+/// let widget = ImageWidget::new(logo).fit(ImageFit::Stretch);
+/// let pixmap = render_widget(widget, 2, 1, &options);
+/// assert_eq!(pixmap.get_pixel(0, 0), [255, 0, 0]);
+/// ```
+pub struct RenderOptions {
+    backend: RefCell<SoftBackend>,
+}
+
+impl RenderOptions {
+    fn with_backend(backend: SoftBackend) -> Self {
+        Self {
+            backend: RefCell::new(backend),
+        }
+    }
+
+    /// Creates options that render with the given font data.
+    ///
+    /// (new-with-font font-size font-data) -> RenderOptions
+    ///
+    /// * font-size  : i32   - Font size in pixels
+    /// * font-data  : &[u8] - Byte slice of the font (e.g., included with `include_bytes!`)
+    #[cfg(feature = "std")]
+    pub fn new_with_font(font_size: i32, font_data: &[u8]) -> Self {
+        Self::with_backend(SoftBackend::new_with_font(1, 1, font_size, font_data))
+    }
+
+    /// Creates options that render with the system fonts.
+    ///
+    /// ⚠️ Not supported on WASM/Web targets.
+    #[cfg(feature = "std")]
+    pub fn new_with_system_fonts(font_size: i32) -> Self {
+        Self::with_backend(SoftBackend::new_with_system_fonts(1, 1, font_size))
+    }
+
+    /// Creates options that render every cell with a tile from a sprite sheet.
+    pub fn new_with_tileset(tileset: Tileset) -> Self {
+        Self::with_backend(SoftBackend::new_with_tileset(1, 1, tileset))
+    }
+
+    /// Creates options that render with a bitmap font, such as the built-in
+    /// [`BitmapFont::FIXED_8X13`]. Available without `std`.
+    pub fn new_with_bitmap_font(font: BitmapFont) -> Self {
+        Self::with_backend(SoftBackend::new_with_bitmap_font(1, 1, font))
+    }
+
+    /// Sets whether box-drawing, block element, Powerline, Braille, sextant and octant chars
+    /// are drawn procedurally, see [`SoftBackend::set_procedural_glyphs`].
+    pub fn set_procedural_glyphs(&mut self, enabled: bool) {
+        self.backend.get_mut().set_procedural_glyphs(enabled);
+    }
+
+    /// Sets the dot shape and size used to draw Braille chars, see
+    /// [`SoftBackend::set_braille_style`].
+    pub fn set_braille_style(&mut self, style: BrailleStyle) {
+        self.backend.get_mut().set_braille_style(style);
+    }
+
+    /// Registers an image to be shown with an [`ImageWidget`](crate::ImageWidget), returning its
+    /// id.
+    pub fn add_image(&mut self, image: RgbaImage) -> ImageId {
+        self.backend.get_mut().add_image(image)
+    }
+
+    /// Returns the size of a cell in pixels, as (width, height).
+    pub fn cell_size(&self) -> (usize, usize) {
+        let backend = self.backend.borrow();
+        (backend.char_width, backend.char_height)
+    }
+}
+
+/// Renders a buffer to a new pixmap of the size of its area.
+///
+/// (render-buffer buffer options) -> RgbPixmap
+///
+/// * buffer     : &Buffer        - The cells to draw, the position of its area is ignored
+/// * options    : &RenderOptions - Font and drawing settings
+///
+/// Blinking text is drawn visible, and the cursor is not drawn.
+///
+/// # Examples
+/// ```rust
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::Rect;
+/// use soft_ratatui::{BitmapFont, RenderOptions, render_buffer};
+///
+/// let options = RenderOptions::new_with_bitmap_font(BitmapFont::FIXED_8X13);
+/// let mut buffer = Buffer::empty(Rect::new(0, 0, 12, 1));
+/// buffer.set_string(0, 0, "Hello world!", ratatui::style::Style::new());
+/// let pixmap = render_buffer(&buffer, &options);
+/// assert_eq!((pixmap.width(), pixmap.height()), (96, 13));
+/// ```
+pub fn render_buffer(buffer: &Buffer, options: &RenderOptions) -> RgbPixmap {
+    let mut backend = options.backend.borrow_mut();
+    backend.redraw_buffer(buffer);
+    backend.clear_dirty();
+    backend.rgb_pixmap.clone()
+}
+
+/// Renders a widget into `cols` by `rows` cells and returns the pixmap.
+///
+/// (render-widget widget cols rows options) -> RgbPixmap
+///
+/// * widget     : impl Widget    - The widget, rendered into the whole area
+/// * cols       : u16            - Width of the area in cells
+/// * rows       : u16            - Height of the area in cells
+/// * options    : &RenderOptions - Font and drawing settings
+///
+/// # Examples
+/// ```rust
+/// use ratatui::widgets::{Block, Paragraph};
+/// use soft_ratatui::{BitmapFont, RenderOptions, render_widget};
+///
+/// let options = RenderOptions::new_with_bitmap_font(BitmapFont::FIXED_8X13);
+/// let widget = Paragraph::new("Hello").block(Block::bordered().title("Thumbnail"));
+/// let pixmap = render_widget(widget, 40, 10, &options);
+/// assert_eq!((pixmap.width(), pixmap.height()), (320, 130));
+/// ```
+pub fn render_widget<W: Widget>(
+    widget: W,
+    cols: u16,
+    rows: u16,
+    options: &RenderOptions,
+) -> RgbPixmap {
+    let area = Rect::new(0, 0, cols, rows);
+    let mut buffer = Buffer::empty(area);
+    widget.render(area, &mut buffer);
+    render_buffer(&buffer, options)
+}
@@ -0,0 +1,271 @@
+//! Sixel encoding, for showing rendered frames in terminals such as xterm, foot, WezTerm or
+//! mlterm, and over SSH.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::ops::Range;
+
+use crate::pixmap::{PixelRect, RgbPixmap};
+
+/// The number of bits kept per channel when building the median cut histogram.
+const HISTOGRAM_BITS: u32 = 5;
+const HISTOGRAM_SIZE: usize = 1 << (3 * HISTOGRAM_BITS);
+
+fn histogram_key(color: [u8; 3]) -> usize {
+    let shift = 8 - HISTOGRAM_BITS;
+    ((color[0] as usize >> shift) << (2 * HISTOGRAM_BITS))
+        | ((color[1] as usize >> shift) << HISTOGRAM_BITS)
+        | (color[2] as usize >> shift)
+}
+
+fn histogram_color(key: usize) -> [u8; 3] {
+    let mask = (1 << HISTOGRAM_BITS) - 1;
+    let shift = 8 - HISTOGRAM_BITS;
+    let half = 1 << (shift - 1);
+    [
+        key >> (2 * HISTOGRAM_BITS),
+        (key >> HISTOGRAM_BITS) & mask,
+        key & mask,
+    ]
+    .map(|channel| ((channel << shift) | half) as u8)
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    (0..3)
+        .map(|i| (a[i] as i32 - b[i] as i32).pow(2) as u32)
+        .sum()
+}
+
+/// Reduces the colors of a region to a palette of at most `max_colors`, returning the palette
+/// and the palette index of every pixel, row by row.
+///
+/// Regions with few enough colors, as most terminal frames are, keep their exact colors. Others
+/// are quantized with median cut.
+fn quantize(pixmap: &RgbPixmap, region: PixelRect, max_colors: usize) -> (Vec<[u8; 3]>, Vec<u8>) {
+    let pixels = || {
+        (region.y..region.y + region.height).flat_map(move |y| {
+            (region.x..region.x + region.width).map(move |x| pixmap.get_pixel(x, y))
+        })
+    };
+
+    let mut exact: BTreeMap<[u8; 3], u8> = BTreeMap::new();
+    let mut overflow = false;
+    for color in pixels() {
+        if !exact.contains_key(&color) {
+            if exact.len() == max_colors {
+                overflow = true;
+                break;
+            }
+            exact.insert(color, exact.len() as u8);
+        }
+    }
+    if !overflow {
+        let mut palette = vec![[0; 3]; exact.len()];
+        for (color, index) in &exact {
+            palette[*index as usize] = *color;
+        }
+        let indices = pixels().map(|color| exact[&color]).collect();
+        return (palette, indices);
+    }
+
+    let mut counts = vec![0u32; HISTOGRAM_SIZE];
+    for color in pixels() {
+        counts[histogram_key(color)] += 1;
+    }
+    let mut entries: Vec<([u8; 3], u32)> = counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .map(|(key, count)| (histogram_color(key), *count))
+        .collect();
+
+    // Every box is a range of `entries`, split along its widest channel at the weighted median.
+    let mut boxes: Vec<Range<usize>> = Vec::with_capacity(max_colors);
+    boxes.push(0..entries.len());
+    while boxes.len() < max_colors {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, range)| range.len() > 1)
+            .map(|(index, range)| {
+                let (channel, spread) = (0..3)
+                    .map(|channel| {
+                        let values = entries[range.clone()].iter().map(|(c, _)| c[channel]);
+                        let spread = values.clone().max().unwrap() - values.min().unwrap();
+                        (channel, spread)
+                    })
+                    .max_by_key(|(_, spread)| *spread)
+                    .unwrap();
+                (index, channel, spread)
+            })
+            .max_by_key(|(_, _, spread)| *spread);
+        let Some((index, channel, _)) = widest else {
+            break;
+        };
+
+        let range = boxes[index].clone();
+        let slice = &mut entries[range.clone()];
+        slice.sort_unstable_by_key(|(color, _)| color[channel]);
+        let total: u32 = slice.iter().map(|(_, count)| count).sum();
+        let mut seen = 0;
+        let mut split = 1;
+        for (i, (_, count)) in slice.iter().enumerate() {
+            seen += count;
+            if seen * 2 >= total {
+                split = (i + 1).clamp(1, slice.len() - 1);
+                break;
+            }
+        }
+        boxes[index] = range.start..range.start + split;
+        boxes.push(range.start + split..range.end);
+    }
+
+    let palette: Vec<[u8; 3]> = boxes
+        .iter()
+        .map(|range| {
+            let mut sum = [0u64; 3];
+            let mut total = 0u64;
+            for (color, count) in &entries[range.clone()] {
+                for i in 0..3 {
+                    sum[i] += color[i] as u64 * *count as u64;
+                }
+                total += *count as u64;
+            }
+            sum.map(|channel| ((channel + total / 2) / total) as u8)
+        })
+        .collect();
+
+    let mut nearest = vec![u8::MAX as u16 + 1; HISTOGRAM_SIZE];
+    let indices = pixels()
+        .map(|color| {
+            let key = histogram_key(color);
+            if nearest[key] > u8::MAX as u16 {
+                let center = histogram_color(key);
+                nearest[key] = (0..palette.len())
+                    .min_by_key(|i| distance(palette[*i], center))
+                    .unwrap() as u16;
+            }
+            nearest[key] as u8
+        })
+        .collect();
+    (palette, indices)
+}
+
+/// Appends a run of `count` identical sixels, using the repeat introducer when it is shorter.
+fn push_run(out: &mut String, sixel: char, count: usize) {
+    if count > 3 {
+        let _ = write!(out, "!{count}{sixel}");
+    } else {
+        for _ in 0..count {
+            out.push(sixel);
+        }
+    }
+}
+
+impl RgbPixmap {
+    /// Encodes a region of the pixmap as a Sixel image, quantized to at most `max_colors`
+    /// palette entries.
+    ///
+    /// (to-sixel region max-colors) -> String
+    ///
+    /// * region     : PixelRect - Region of the pixmap to encode, see [`Self::bounds`]
+    /// * max-colors : u16       - Palette size, from 2 to 256. Most terminals support 256
+    ///
+    /// # Examples
+    /// ```rust
+    /// use soft_ratatui::{BitmapFont, SoftBackend};
+    /// let backend = SoftBackend::new_with_bitmap_font(10, 2, BitmapFont::FIXED_8X13);
+    /// let pixmap = &backend.rgb_pixmap;
+    /// let sixel = pixmap.to_sixel(pixmap.bounds(), 256);
+    /// assert!(sixel.starts_with("\x1bPq"));
+    /// assert!(sixel.ends_with("\x1b\\"));
+    /// // 26 rows make 5 bands, separated by 4 graphics newlines.
+    /// assert_eq!(sixel.matches('-').count(), 4);
+    /// ```
+    pub fn to_sixel(&self, region: PixelRect, max_colors: u16) -> String {
+        assert!(
+            region.x + region.width <= self.width() && region.y + region.height <= self.height(),
+            "Region out of bounds"
+        );
+        let (palette, indices) = quantize(self, region, max_colors.clamp(2, 256) as usize);
+        let (width, height) = (region.width, region.height);
+
+        let mut out = String::new();
+        let _ = write!(out, "\x1bPq\"1;1;{width};{height}");
+        for (index, [r, g, b]) in palette.iter().enumerate() {
+            let percent = |channel: &u8| (*channel as u32 * 100 + 127) / 255;
+            let _ = write!(
+                out,
+                "#{index};2;{};{};{}",
+                percent(r),
+                percent(g),
+                percent(b)
+            );
+        }
+
+        // Each band of six rows is written once per color it uses, returning to the start of the
+        // band with `$` in between.
+        let mut used = vec![false; palette.len()];
+        for band in (0..height).step_by(6) {
+            // A graphics newline only between bands, one after the last would move the cursor
+            // a band too far in some terminals.
+            if band > 0 {
+                out.push('-');
+            }
+            let rows = band..(band + 6).min(height);
+            used.fill(false);
+            for y in rows.clone() {
+                for &index in &indices[y * width..(y + 1) * width] {
+                    used[index as usize] = true;
+                }
+            }
+            let mut first = true;
+            for (color, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+                if !first {
+                    out.push('$');
+                }
+                first = false;
+                let _ = write!(out, "#{color}");
+
+                let mut run = (None, 0);
+                for x in 0..width {
+                    let bits = rows
+                        .clone()
+                        .filter(|y| indices[y * width + x] as usize == color)
+                        .fold(0, |bits, y| bits | 1 << (y - band));
+                    let sixel = char::from(63 + bits as u8);
+                    if run.0 == Some(sixel) {
+                        run.1 += 1;
+                    } else {
+                        if let (Some(previous), count) = run {
+                            push_run(&mut out, previous, count);
+                        }
+                        run = (Some(sixel), 1);
+                    }
+                }
+                // Trailing empty sixels leave the band unchanged, so they are dropped.
+                if let (Some(previous), count) = run
+                    && previous != '?'
+                {
+                    push_run(&mut out, previous, count);
+                }
+            }
+        }
+        out.push_str("\x1b\\");
+        out
+    }
+
+    /// Writes a region of the pixmap as a Sixel image, see [`Self::to_sixel`].
+    #[cfg(feature = "std")]
+    pub fn write_sixel<W: std::io::Write>(
+        &self,
+        writer: &mut W,
+        region: PixelRect,
+        max_colors: u16,
+    ) -> std::io::Result<()> {
+        writer.write_all(self.to_sixel(region, max_colors).as_bytes())
+    }
+}
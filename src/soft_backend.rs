@@ -1,51 +1,75 @@
 //! This module provides the `SoftBackend` implementation for the [`Backend`] trait.
 //! It is used in the integration tests to verify the correctness of the library.
 
-use std::collections::HashSet;
-use std::io;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::Infallible;
 
+use crate::bitmap_font::BitmapFont;
 use crate::colors::*;
-use crate::pixmap::RgbPixmap;
-
-use cosmic_text::fontdb::Database;
-use ratatui::backend::{Backend, WindowSize};
+#[cfg(feature = "std")]
+use crate::cosmic_font::CosmicFont;
+use crate::image::{self, ImageId, RgbaImage};
+use crate::pixmap::{PixelRect, RgbPixmap};
+use crate::procedural::{self, BrailleStyle, CellCanvas};
+use crate::tileset::Tileset;
+
+use ratatui::backend::{Backend, ClearType, WindowSize};
 use ratatui::buffer::{Buffer, Cell};
 use ratatui::layout::{Position, Rect, Size};
 use ratatui::style::Modifier;
 
-use cosmic_text::{
-    Attrs, AttrsList, CacheKeyFlags, Family, LineEnding, Metrics, Shaping, Weight, Wrap,
-};
+/// The source of the glyph images drawn into each cell.
+enum Glyphs {
+    #[cfg(feature = "std")]
+    Cosmic(Box<CosmicFont>),
+    Tileset(Box<Tileset>),
+    Bitmap(BitmapFont),
+}
+
+/// Resolves the foreground and background colors a cell is drawn with, applying the hidden,
+/// reversed and dim modifiers.
+pub(crate) fn cell_colors(cell: &Cell) -> ([u8; 3], [u8; 3]) {
+    let mut rat_fg = cell.fg;
+    let rat_bg = cell.bg;
+    if cell.modifier.contains(Modifier::HIDDEN) {
+        rat_fg = rat_bg;
+    }
 
-use cosmic_text::{Buffer as CosmicBuffer, FontSystem, SwashCache};
+    let (fg_color, bg_color) = if cell.modifier.contains(Modifier::REVERSED) {
+        (rat_to_rgb(&rat_bg, false), rat_to_rgb(&rat_fg, true))
+    } else {
+        (rat_to_rgb(&rat_fg, true), rat_to_rgb(&rat_bg, false))
+    };
+
+    if cell.modifier.contains(Modifier::DIM) {
+        (dim_rgb(fg_color), dim_rgb(bg_color))
+    } else {
+        (fg_color, bg_color)
+    }
+}
 
 /// SoftBackend is a Software rendering backend for Ratatui. It stores the generated image internally as rgb_pixmap.
 pub struct SoftBackend {
     pub buffer: Buffer,
     pub cursor: bool,
     pub pos: (u16, u16),
-    font_system: FontSystem,
-
-    cosmic_buffer: CosmicBuffer,
+    glyphs: Glyphs,
     pub char_width: usize,
     pub char_height: usize,
 
     pub blink_counter: u16,
     pub blinking_fast: bool,
     pub blinking_slow: bool,
-    swash_cache: SwashCache,
     pub rgb_pixmap: RgbPixmap,
-    always_redraw_list: HashSet<(u16, u16)>,
-}
-
-fn add_strikeout(text: &String) -> String {
-    let strike = '\u{0336}';
-    text.chars().flat_map(|c| [c, strike]).collect()
-}
-
-fn add_underline(text: &String) -> String {
-    let strike = '\u{0332}';
-    text.chars().flat_map(|c| [c, strike]).collect()
+    always_redraw_list: BTreeSet<(u16, u16)>,
+    /// The first and last redrawn column of each row since the last `clear_dirty`.
+    dirty_rows: Vec<Option<(u16, u16)>>,
+    procedural_glyphs: bool,
+    braille_style: BrailleStyle,
+    pub(crate) images: BTreeMap<ImageId, RgbaImage>,
 }
 
 impl SoftBackend {
@@ -66,231 +90,318 @@ impl SoftBackend {
         self.rgb_pixmap.height()
     }
 
-    fn draw_cell(&mut self, xik: u16, yik: u16) {
-        let rat_cell = self.buffer.cell(Position::new(xik, yik)).unwrap();
+    /// Returns the regions of the pixmap redrawn since the last call to [`Self::clear_dirty`].
+    ///
+    /// Rows with the same dirty columns are merged, so a full redraw is a single rectangle.
+    pub fn dirty_regions(&self) -> Vec<PixelRect> {
+        let mut regions: Vec<PixelRect> = Vec::new();
+        let mut last_span = None;
+        for (y, span) in self.dirty_rows.iter().enumerate() {
+            if let Some((first, last)) = *span {
+                let rect = PixelRect {
+                    x: first as usize * self.char_width,
+                    y: y * self.char_height,
+                    width: (last - first + 1) as usize * self.char_width,
+                    height: self.char_height,
+                };
+                match regions.last_mut() {
+                    Some(prev) if last_span == *span => prev.height += rect.height,
+                    _ => regions.push(rect),
+                }
+            }
+            last_span = *span;
+        }
+        regions
+    }
 
-        let mut rat_fg = rat_cell.fg;
-        let rat_bg = rat_cell.bg;
-        if rat_cell.modifier.contains(Modifier::HIDDEN) {
-            rat_fg = rat_bg;
+    /// Copies the regions redrawn since the last call to [`Self::clear_dirty`] into an RGBA image
+    /// of the size of the pixmap, e.g. a texture kept by the host, and forgets them. Returns the
+    /// regions, for hosts that can upload parts of a texture.
+    ///
+    /// After a resize the image has to be recreated with [`Self::get_pixmap_data_as_rgba`].
+    pub fn update_rgba(&mut self, rgba: &mut [u8]) -> Vec<PixelRect> {
+        let regions = self.dirty_regions();
+        for region in &regions {
+            self.rgb_pixmap.copy_rect_to_rgba(*region, rgba);
         }
+        self.clear_dirty();
+        regions
+    }
 
-        let (mut fg_color, mut bg_color) = if rat_cell.modifier.contains(Modifier::REVERSED) {
-            (rat_to_rgb(&rat_bg, false), rat_to_rgb(&rat_fg, true))
-        } else {
-            (rat_to_rgb(&rat_fg, true), rat_to_rgb(&rat_bg, false))
-        };
+    /// Like [`Self::update_rgba`], for a window surface with a `u32` per pixel, see
+    /// [`RgbPixmap::copy_rect_to_xrgb`].
+    pub fn update_xrgb(&mut self, xrgb: &mut [u32]) -> Vec<PixelRect> {
+        let regions = self.dirty_regions();
+        for region in &regions {
+            self.rgb_pixmap.copy_rect_to_xrgb(*region, xrgb);
+        }
+        self.clear_dirty();
+        regions
+    }
 
-        if rat_cell.modifier.contains(Modifier::DIM) {
-            (fg_color, bg_color) = (dim_rgb(fg_color), dim_rgb(bg_color));
-        };
+    /// Forgets the dirty regions, call after the pixmap has been presented.
+    pub fn clear_dirty(&mut self) {
+        self.dirty_rows.fill(None);
+    }
 
-        let begin_x = xik as usize * self.char_width;
-        let begin_y = yik as usize * self.char_height;
-        for y in 0..self.char_height {
-            for x in 0..self.char_width {
-                self.rgb_pixmap
-                    .put_pixel(begin_x + x, begin_y + y, bg_color);
-            }
-        }
+    pub(crate) fn mark_dirty(&mut self, x: u16, y: u16) {
+        let span = &mut self.dirty_rows[y as usize];
+        *span = Some(match *span {
+            Some((first, last)) => (first.min(x), last.max(x)),
+            None => (x, x),
+        });
+    }
+
+    fn mark_all_dirty(&mut self) {
+        let width = self.buffer.area.width;
+        let span = (width > 0).then(|| (0, width - 1));
+        self.dirty_rows.fill(span);
+    }
 
-        let mut text_symbol: String = rat_cell.symbol().to_string();
+    pub(crate) fn draw_cell(&mut self, xik: u16, yik: u16) {
+        self.mark_dirty(xik, yik);
+        let rat_cell = self.buffer.cell(Position::new(xik, yik)).unwrap();
+        let (mut fg_color, bg_color) = cell_colors(rat_cell);
 
-        if rat_cell.modifier.contains(Modifier::CROSSED_OUT) {
-            text_symbol = add_strikeout(&text_symbol);
-        }
-        if rat_cell.modifier.contains(Modifier::UNDERLINED) {
-            text_symbol = add_underline(&text_symbol);
-        }
+        let begin_x = xik as usize * self.char_width;
+        let begin_y = yik as usize * self.char_height;
+        self.rgb_pixmap.fill_rect(
+            begin_x,
+            begin_y,
+            self.char_width,
+            self.char_height,
+            bg_color,
+        );
 
         if rat_cell.modifier.contains(Modifier::SLOW_BLINK) {
             self.always_redraw_list.insert((xik, yik));
             if self.blinking_slow {
-                fg_color = bg_color.clone();
+                fg_color = bg_color;
             }
         }
         if rat_cell.modifier.contains(Modifier::RAPID_BLINK) {
             self.always_redraw_list.insert((xik, yik));
             if self.blinking_fast {
-                fg_color = bg_color.clone();
+                fg_color = bg_color;
             }
         }
 
-        let mut attrs = Attrs::new().family(Family::Monospace);
-        if rat_cell.modifier.contains(Modifier::BOLD) {
-            attrs = attrs.weight(Weight::BOLD);
-        }
-        if rat_cell.modifier.contains(Modifier::ITALIC) {
-            attrs = attrs.cache_key_flags(CacheKeyFlags::FAKE_ITALIC);
+        let symbol = rat_cell.symbol();
+        let modifier = rat_cell.modifier;
+
+        if image::draw_placeholder(
+            &mut self.rgb_pixmap,
+            begin_x,
+            begin_y,
+            (self.char_width, self.char_height),
+            symbol,
+            &self.images,
+        ) {
+            return;
         }
-        let mets = self.cosmic_buffer.metrics().font_size;
-        let line = self.cosmic_buffer.lines.get_mut(0).unwrap();
-        line.set_text(&text_symbol, LineEnding::None, AttrsList::new(&attrs));
-
-        line.layout(&mut self.font_system, mets, None, Wrap::None, None, 1);
-
-        for run in self.cosmic_buffer.layout_runs() {
-            for glyph in run.glyphs.iter() {
-                let physical_glyph = glyph.physical((0., 0.), 1.0);
 
-                //TODO : Handle Content::Color (emojis?)
-
-                if let Some(image) = self
-                    .swash_cache
-                    .get_image(&mut self.font_system, physical_glyph.cache_key)
-                {
-                    //    println!("imagik {:#?}", image.data.len());
-                    let x = image.placement.left;
-
-                    let y = -image.placement.top;
-                    let mut i = 0;
-
-                    for off_y in 0..image.placement.height {
-                        for off_x in 0..image.placement.width {
-                            let real_x = physical_glyph.x + x + off_x as i32;
-
-                            let real_y = run.line_y as i32 + physical_glyph.y + y + off_y as i32;
-
-                            if real_x >= 0 && real_y >= 0 {
-                                let get_x = begin_x + real_x as usize;
-                                let get_y = begin_y + real_y as usize;
+        let mut chars = symbol.chars();
+        if let (Some(c), None) = (chars.next(), chars.next())
+            && self.procedural_glyphs
+            && procedural::is_procedural(c)
+        {
+            let mut canvas = CellCanvas {
+                pixmap: &mut self.rgb_pixmap,
+                x: begin_x,
+                y: begin_y,
+                width: self.char_width,
+                height: self.char_height,
+                fg: fg_color,
+                bg: bg_color,
+            };
+            procedural::draw(&mut canvas, c, self.braille_style);
+            if modifier.contains(Modifier::UNDERLINED) {
+                canvas.rect(0, self.char_height - 1, self.char_width, self.char_height);
+            }
+            if modifier.contains(Modifier::CROSSED_OUT) {
+                let y = self.char_height / 2;
+                canvas.rect(0, y, self.char_width, y + 1);
+            }
+            return;
+        }
+        match &mut self.glyphs {
+            #[cfg(feature = "std")]
+            Glyphs::Cosmic(font) => font.draw_symbol(
+                &mut self.rgb_pixmap,
+                begin_x,
+                begin_y,
+                symbol,
+                modifier,
+                fg_color,
+                bg_color,
+            ),
+            Glyphs::Tileset(tileset) => tileset.draw_symbol(
+                &mut self.rgb_pixmap,
+                begin_x,
+                begin_y,
+                symbol,
+                modifier,
+                fg_color,
+                bg_color,
+            ),
+            Glyphs::Bitmap(font) => font.draw_symbol(
+                &mut self.rgb_pixmap,
+                begin_x,
+                begin_y,
+                symbol,
+                modifier,
+                fg_color,
+                bg_color,
+            ),
+        }
+    }
 
-                                let put_color = blend_rgba(
-                                    [fg_color[0], fg_color[1], fg_color[2], image.data[i]],
-                                    [bg_color[0], bg_color[1], bg_color[2], 255],
-                                );
-                                self.rgb_pixmap.put_pixel(get_x, get_y, put_color);
-                            }
+    /// Returns the font size and the distance from the top of a cell to the text baseline, in
+    /// pixels, as used to place text in vector exports.
+    pub(crate) fn text_metrics(&self) -> (f32, f32) {
+        match &self.glyphs {
+            #[cfg(feature = "std")]
+            Glyphs::Cosmic(font) => (font.font_size, font.baseline),
+            // Bitmap glyphs and tiles keep about a fifth of the cell below the baseline.
+            _ => (
+                self.char_height as f32,
+                ((self.char_height * 4 + 2) / 5) as f32,
+            ),
+        }
+    }
 
-                            i += 1;
-                        }
-                    }
-                }
-            }
+    /// Returns the size of the font, `None` when rendering with a [`Tileset`] or a
+    /// [`BitmapFont`], whose glyphs have a fixed size.
+    #[cfg(feature = "window")]
+    pub(crate) fn font_size(&self) -> Option<f32> {
+        match &self.glyphs {
+            Glyphs::Cosmic(font) => Some(font.font_size),
+            _ => None,
         }
     }
 
     /// Sets a new font size for the terminal image.
     /// This will recreate the pixmap and do a full redraw. Do not run every frame.
+    ///
+    /// Has no effect when rendering with a [`Tileset`] or a [`BitmapFont`], whose glyphs have a
+    /// fixed size.
+    #[cfg(feature = "std")]
     pub fn set_font_size(&mut self, font_size: i32) {
-        let metrics = Metrics::new(font_size as f32, font_size as f32);
-        self.cosmic_buffer
-            .set_metrics(&mut self.font_system, metrics);
-        let mut buffer = CosmicBuffer::new(&mut self.font_system, metrics);
-        let mut buffer = buffer.borrow_with(&mut self.font_system);
-        //"█\n█",
-        buffer.set_text(
-            "█\n█",
-            &Attrs::new().family(Family::Monospace),
-            Shaping::Advanced,
-        );
-        buffer.shape_until_scroll(true);
-        let boop = buffer.layout_runs().next().unwrap();
-        let physical_glyph = boop.glyphs.iter().next().unwrap().physical((0., 0.), 1.0);
-
-        let wa = self
-            .swash_cache
-            .get_image(&mut self.font_system, physical_glyph.cache_key)
-            .clone()
-            .unwrap()
-            .placement;
-        // println!("Glyph height (bbox): {:#?}", wa);
-
-        let char_width = wa.width as usize;
-        let char_height = wa.height as usize;
-        self.cosmic_buffer.set_size(
-            &mut self.font_system,
-            Some(char_width as f32),
-            Some(char_height as f32),
-        );
-        self.char_width = char_width;
-        self.char_height = char_height;
-        self.rgb_pixmap = RgbPixmap::new(
-            self.char_width * self.buffer.area.width as usize,
-            self.char_height * self.buffer.area.height as usize,
-        );
+        if let Glyphs::Cosmic(font) = &mut self.glyphs {
+            font.set_font_size(font_size);
+            self.char_width = font.char_width;
+            self.char_height = font.char_height;
+            self.rgb_pixmap = RgbPixmap::new(
+                self.char_width * self.buffer.area.width as usize,
+                self.char_height * self.buffer.area.height as usize,
+            );
+
+            self.redraw();
+        }
+    }
 
+    /// Sets whether box-drawing (U+2500 to U+257F), block element (U+2580 to U+259F),
+    /// Powerline (U+E0B0 to U+E0BF), Braille (U+2800 to U+28FF), sextant and octant chars are
+    /// drawn procedurally to fill the exact cell box, or with the glyphs of the font.
+    /// Redraws the pixmap.
+    ///
+    /// Enabled by default, except when rendering with a [`Tileset`], whose tiles are usually
+    /// drawn in the style of the sheet.
+    pub fn set_procedural_glyphs(&mut self, enabled: bool) {
+        self.procedural_glyphs = enabled;
         self.redraw();
     }
 
-    /// Creates a new Software Backend with the given font data.
-    ///
-    /// (new-with-font width height font-size font-data) -> SoftBackend
-    ///
-    /// * width      : usize - Width of the terminal in cells
-    /// * height     : usize - Height of the terminal in cells
-    /// * font-size  : u32   - Font size in pixels
-    /// * font-data  : &[u8] - Byte slice of the font (e.g., included with `include_bytes!`)
+    /// Sets the dot shape and size used to draw Braille chars. Redraws the pixmap.
     ///
-    /// # Examples
-    /// ```rust
-    /// static FONT_DATA: &[u8] = include_bytes!("../../assets/iosevka.ttf");
-    /// let backend = SoftBackend::new_with_font(20, 20, 16, FONT_DATA);
-    /// ```
-
-    pub fn new_with_font(width: u16, height: u16, font_size: i32, font_data: &[u8]) -> Self {
-        let mut swash_cache = SwashCache::new();
-
-        let mut db = Database::new();
-        // "assets/iosevka.ttf"
-        db.load_font_data(font_data.to_vec());
-        //  db.set_monospace_family("Fira Mono");
-
-        let mut font_system = FontSystem::new_with_locale_and_db("English".to_string(), db);
-        let metrics = Metrics::new(font_size as f32, font_size as f32);
-
-        let mut buffer = CosmicBuffer::new(&mut font_system, metrics);
-        let mut buffer = buffer.borrow_with(&mut font_system);
-        buffer.set_text(
-            "██",
-            &Attrs::new().family(Family::Monospace),
-            Shaping::Advanced,
-        );
-        buffer.shape_until_scroll(true);
-        let boop = buffer.layout_runs().next().unwrap();
-        let physical_glyph = boop.glyphs.iter().next().unwrap().physical((0., 0.), 1.0);
-
-        let wa = swash_cache
-            .get_image(&mut font_system, physical_glyph.cache_key)
-            .clone()
-            .unwrap()
-            .placement;
-        // println!("Glyph height (bbox): {:#?}", wa);
-
-        let mut cosmic_buffer = CosmicBuffer::new(&mut font_system, metrics);
-
-        let char_width = wa.width as usize;
-        let char_height = wa.height as usize;
-        cosmic_buffer.set_size(
-            &mut font_system,
-            Some(char_width as f32),
-            Some(char_height as f32),
-        );
+    /// Only applies while procedural glyphs are enabled, see [`Self::set_procedural_glyphs`].
+    pub fn set_braille_style(&mut self, style: BrailleStyle) {
+        self.braille_style = style;
+        self.redraw();
+    }
 
+    fn with_glyphs(width: u16, height: u16, glyphs: Glyphs) -> Self {
+        let procedural_glyphs = !matches!(glyphs, Glyphs::Tileset(_));
+        let (char_width, char_height) = match &glyphs {
+            #[cfg(feature = "std")]
+            Glyphs::Cosmic(font) => (font.char_width, font.char_height),
+            Glyphs::Tileset(tileset) => (tileset.tile_width(), tileset.tile_height()),
+            Glyphs::Bitmap(font) => (font.char_width(), font.char_height()),
+        };
         let rgb_pixmap = RgbPixmap::new(char_width * width as usize, char_height * height as usize);
 
         let mut return_struct = Self {
             buffer: Buffer::empty(Rect::new(0, 0, width, height)),
             cursor: false,
             pos: (0, 0),
-            font_system,
+            glyphs,
 
             rgb_pixmap,
-            cosmic_buffer,
             char_width,
             char_height,
 
             blink_counter: 0,
             blinking_fast: false,
             blinking_slow: false,
-            always_redraw_list: HashSet::new(),
-
-            swash_cache,
+            always_redraw_list: BTreeSet::new(),
+            dirty_rows: vec![None; height as usize],
+            procedural_glyphs,
+            braille_style: BrailleStyle::default(),
+            images: BTreeMap::new(),
         };
         _ = return_struct.clear();
         return_struct
     }
 
+    /// Creates a new Software Backend with the given font data.
+    ///
+    /// (new-with-font width height font-size font-data) -> SoftBackend
+    ///
+    /// * width      : usize - Width of the terminal in cells
+    /// * height     : usize - Height of the terminal in cells
+    /// * font-size  : u32   - Font size in pixels
+    /// * font-data  : &[u8] - Byte slice of the font (e.g., included with `include_bytes!`)
+    ///
+    /// Panics if the data holds no usable font, see [`Self::try_new_with_font`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use soft_ratatui::SoftBackend;
+    /// static FONT_DATA: &[u8] = include_bytes!("../assets/fm.ttf");
+    /// let backend = SoftBackend::new_with_font(20, 20, 16, FONT_DATA);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn new_with_font(width: u16, height: u16, font_size: i32, font_data: &[u8]) -> Self {
+        Self::try_new_with_font(width, height, font_size, font_data)
+            .expect("invalid font data or size")
+    }
+
+    /// Creates a new Software Backend with the given font data, like [`Self::new_with_font`],
+    /// or returns `None` if the data holds no usable font or the size isn't positive.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use soft_ratatui::SoftBackend;
+    /// static FONT_DATA: &[u8] = include_bytes!("../assets/fm.ttf");
+    /// assert!(SoftBackend::try_new_with_font(20, 20, 16, FONT_DATA).is_some());
+    /// assert!(SoftBackend::try_new_with_font(20, 20, 16, b"not a font").is_none());
+    /// assert!(SoftBackend::try_new_with_font(20, 20, 0, FONT_DATA).is_none());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn try_new_with_font(
+        width: u16,
+        height: u16,
+        font_size: i32,
+        font_data: &[u8],
+    ) -> Option<Self> {
+        let font = CosmicFont::from_font_data(font_size, font_data)?;
+        Some(Self::with_glyphs(
+            width,
+            height,
+            Glyphs::Cosmic(Box::new(font)),
+        ))
+    }
+
     /// Creates a new Software Backend using provided system fonts.
     ///
     /// (new-with-system-fonts width height font-size) -> SoftBackend
@@ -302,65 +413,53 @@ impl SoftBackend {
     /// ⚠️ Not supported on WASM/Web targets.
     ///
     /// # Examples
-    /// ```rust
+    /// ```rust,no_run
+    /// use soft_ratatui::SoftBackend;
     /// let backend = SoftBackend::new_with_system_fonts(20, 20, 16);
     /// ```
+    #[cfg(feature = "std")]
     pub fn new_with_system_fonts(width: u16, height: u16, font_size: i32) -> Self {
-        let mut swash_cache = SwashCache::new();
-
-        let mut font_system = FontSystem::new();
-        let metrics = Metrics::new(font_size as f32, font_size as f32);
-
-        let mut buffer = CosmicBuffer::new(&mut font_system, metrics);
-        let mut buffer = buffer.borrow_with(&mut font_system);
-        buffer.set_text(
-            "█\n█",
-            &Attrs::new().family(Family::Monospace),
-            Shaping::Advanced,
-        );
-        buffer.shape_until_scroll(true);
-        let boop = buffer.layout_runs().next().unwrap();
-        let physical_glyph = boop.glyphs.iter().next().unwrap().physical((0., 0.), 1.0);
-
-        let wa = swash_cache
-            .get_image(&mut font_system, physical_glyph.cache_key)
-            .clone()
-            .unwrap()
-            .placement;
-        //  println!("Glyph height (bbox): {:#?}", wa);
-
-        let mut cosmic_buffer = CosmicBuffer::new(&mut font_system, metrics);
-
-        let char_width = wa.width as usize;
-        let char_height = wa.height as usize;
-        cosmic_buffer.set_size(
-            &mut font_system,
-            Some(char_width as f32),
-            Some(char_height as f32),
-        );
-
-        let rgb_pixmap = RgbPixmap::new(char_width * width as usize, char_height * height as usize);
-
-        let mut return_struct = Self {
-            buffer: Buffer::empty(Rect::new(0, 0, width, height)),
-            cursor: false,
-            pos: (0, 0),
-            font_system,
-
-            rgb_pixmap,
-            cosmic_buffer,
-            char_width,
-            char_height,
+        let font = CosmicFont::from_system_fonts(font_size);
+        Self::with_glyphs(width, height, Glyphs::Cosmic(Box::new(font)))
+    }
 
-            blink_counter: 0,
-            blinking_fast: false,
-            blinking_slow: false,
-            always_redraw_list: HashSet::new(),
+    /// Creates a new Software Backend that draws every cell with a tile from a sprite sheet.
+    ///
+    /// (new-with-tileset width height tileset) -> SoftBackend
+    ///
+    /// * width      : usize   - Width of the terminal in cells
+    /// * height     : usize   - Height of the terminal in cells
+    /// * tileset    : Tileset - The sprite sheet, its tile size becomes the cell size
+    ///
+    /// # Examples
+    /// ```rust
+    /// use soft_ratatui::{SoftBackend, Tileset};
+    /// let sheet = vec![255; 128 * 128 * 4];
+    /// let tileset = Tileset::from_rgba(&sheet, 128, 128, 8, 8);
+    /// let backend = SoftBackend::new_with_tileset(20, 20, tileset);
+    /// assert_eq!(backend.get_pixmap_width(), 160);
+    /// ```
+    pub fn new_with_tileset(width: u16, height: u16, tileset: Tileset) -> Self {
+        Self::with_glyphs(width, height, Glyphs::Tileset(Box::new(tileset)))
+    }
 
-            swash_cache,
-        };
-        _ = return_struct.clear();
-        return_struct
+    /// Creates a new Software Backend that draws with a bitmap font, such as the built-in
+    /// [`BitmapFont::FIXED_8X13`]. Available without `std`.
+    ///
+    /// (new-with-bitmap-font width height font) -> SoftBackend
+    ///
+    /// * width      : usize      - Width of the terminal in cells
+    /// * height     : usize      - Height of the terminal in cells
+    /// * font       : BitmapFont - The font, its glyph size becomes the cell size
+    ///
+    /// # Examples
+    /// ```rust
+    /// use soft_ratatui::{BitmapFont, SoftBackend};
+    /// let backend = SoftBackend::new_with_bitmap_font(20, 20, BitmapFont::FIXED_8X13);
+    /// assert_eq!(backend.get_pixmap_height(), 260);
+    /// ```
+    pub fn new_with_bitmap_font(width: u16, height: u16, font: BitmapFont) -> Self {
+        Self::with_glyphs(width, height, Glyphs::Bitmap(font))
     }
 
     /// Returns a reference to the internal buffer of the `SoftBackend`.
@@ -372,16 +471,57 @@ impl SoftBackend {
     pub fn resize(&mut self, width: u16, height: u16) {
         self.buffer.resize(Rect::new(0, 0, width, height));
         let rgb_pixmap = RgbPixmap::new(
-            self.char_width as usize * width as usize,
-            self.char_height as usize * height as usize,
+            self.char_width * width as usize,
+            self.char_height * height as usize,
         );
         self.rgb_pixmap = rgb_pixmap;
+        self.dirty_rows = vec![None; height as usize];
+        self.redraw();
+    }
+
+    /// Resizes to as many cells as fit in an area of the given size in pixels, e.g. the space a
+    /// GUI allocates to the terminal, with at least one cell. Returns whether the size changed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use soft_ratatui::{BitmapFont, SoftBackend};
+    ///
+    /// let mut backend = SoftBackend::new_with_bitmap_font(10, 10, BitmapFont::FIXED_8X13);
+    /// assert!(backend.fit_to_pixels(100, 40));
+    /// assert_eq!((backend.buffer.area.width, backend.buffer.area.height), (12, 3));
+    /// assert!(!backend.fit_to_pixels(103, 51));
+    /// ```
+    pub fn fit_to_pixels(&mut self, width: usize, height: usize) -> bool {
+        let cells = |pixels: usize, cell: usize| (pixels / cell).clamp(1, u16::MAX as usize) as u16;
+        let (width, height) = (
+            cells(width, self.char_width),
+            cells(height, self.char_height),
+        );
+        if (width, height) == (self.buffer.area.width, self.buffer.area.height) {
+            return false;
+        }
+        self.resize(width, height);
+        true
+    }
+
+    /// Replaces the whole buffer, resizing the pixmap if the size changed, and redraws it.
+    pub(crate) fn redraw_buffer(&mut self, buffer: &Buffer) {
+        let (width, height) = (buffer.area.width, buffer.area.height);
+        if (self.buffer.area.width, self.buffer.area.height) != (width, height) {
+            self.rgb_pixmap = RgbPixmap::new(
+                self.char_width * width as usize,
+                self.char_height * height as usize,
+            );
+            self.dirty_rows = vec![None; height as usize];
+        }
+        self.buffer.area = Rect::new(0, 0, width, height);
+        self.buffer.content.clone_from(&buffer.content);
         self.redraw();
     }
 
     /// Redraws the pixmap
     pub fn redraw(&mut self) {
-        self.always_redraw_list = HashSet::new();
+        self.always_redraw_list = BTreeSet::new();
         for x in 0..self.buffer.area.width {
             for y in 0..self.buffer.area.height {
                 self.draw_cell(x, y);
@@ -398,7 +538,9 @@ impl SoftBackend {
 }
 
 impl Backend for SoftBackend {
-    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    type Error = Infallible;
+
+    fn draw<'a, I>(&mut self, content: I) -> Result<(), Self::Error>
     where
         I: Iterator<Item = (u16, u16, &'a Cell)>,
     {
@@ -406,7 +548,6 @@ impl Backend for SoftBackend {
         for (x, y, c) in content {
             self.buffer[(x, y)] = c.clone();
             self.draw_cell(x, y);
-            //   println!("{c:#?}");
         }
         for (x, y) in self.always_redraw_list.clone().iter() {
             self.draw_cell(*x, *y);
@@ -415,41 +556,70 @@ impl Backend for SoftBackend {
         Ok(())
     }
 
-    fn hide_cursor(&mut self) -> io::Result<()> {
+    fn hide_cursor(&mut self) -> Result<(), Self::Error> {
         self.cursor = false;
 
         Ok(())
     }
 
-    fn show_cursor(&mut self) -> io::Result<()> {
+    fn show_cursor(&mut self) -> Result<(), Self::Error> {
         self.cursor = true;
         Ok(())
     }
 
-    fn get_cursor_position(&mut self) -> io::Result<Position> {
+    fn get_cursor_position(&mut self) -> Result<Position, Self::Error> {
         Ok(self.pos.into())
     }
 
-    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> Result<(), Self::Error> {
         self.pos = position.into().into();
         Ok(())
     }
 
-    fn clear(&mut self) -> io::Result<()> {
+    fn clear(&mut self) -> Result<(), Self::Error> {
         self.buffer.reset();
         let clear_cell = Cell::EMPTY;
         let colorik = rat_to_rgb(&clear_cell.bg, false);
 
         self.rgb_pixmap.fill([colorik[0], colorik[1], colorik[2]]);
+        self.mark_all_dirty();
+
+        Ok(())
+    }
 
+    fn clear_region(&mut self, clear_type: ClearType) -> Result<(), Self::Error> {
+        if clear_type == ClearType::All {
+            return self.clear();
+        }
+        let (x, y) = self.pos;
+        // Nothing to clear in an empty buffer, or around a cursor outside of it.
+        if !self.buffer.area.contains(Position::new(x, y)) {
+            return Ok(());
+        }
+        let line_start = self.buffer.index_of(0, y);
+        let line_end = self.buffer.index_of(self.buffer.area.width - 1, y);
+        let cursor = self.buffer.index_of(x, y);
+        let region = match clear_type {
+            // Handled above.
+            ClearType::All => unreachable!(),
+            ClearType::AfterCursor => cursor + 1..self.buffer.content.len(),
+            ClearType::BeforeCursor => 0..cursor,
+            ClearType::CurrentLine => line_start..line_end + 1,
+            ClearType::UntilNewLine => cursor..line_end + 1,
+        };
+        for index in region {
+            self.buffer.content[index].reset();
+            let (x, y) = self.buffer.pos_of(index);
+            self.draw_cell(x, y);
+        }
         Ok(())
     }
 
-    fn size(&self) -> io::Result<Size> {
+    fn size(&self) -> Result<Size, Self::Error> {
         Ok(self.buffer.area.as_size())
     }
 
-    fn window_size(&mut self) -> io::Result<WindowSize> {
+    fn window_size(&mut self) -> Result<WindowSize, Self::Error> {
         let window_pixels = Size {
             width: self.get_pixmap_width() as u16,
             height: self.get_pixmap_height() as u16,
@@ -460,7 +630,7 @@ impl Backend for SoftBackend {
         })
     }
 
-    fn flush(&mut self) -> io::Result<()> {
+    fn flush(&mut self) -> Result<(), Self::Error> {
         Ok(())
     }
 }
@@ -0,0 +1,354 @@
+//! SVG export of the terminal frame, with the text kept as text so it scales and can be
+//! searched and copied.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use ratatui::buffer::Buffer;
+use ratatui::style::Modifier;
+use unicode_width::UnicodeWidthStr;
+
+use crate::image;
+use crate::kitty::base64;
+use crate::soft_backend::{SoftBackend, cell_colors};
+use crate::truetype::{read_u16, read_u32};
+
+/// Options of an SVG export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SvgOptions<'a> {
+    /// The CSS font family of the text. Falls back to the generic `monospace` family.
+    pub font_family: &'a str,
+    /// A TTF, OTF, WOFF or WOFF2 font embedded in the SVG under `font_family`, so the text looks
+    /// the same without the font installed. TTF and OTF fonts are converted to WOFF, compressed
+    /// with the `zlib` feature.
+    pub font_data: Option<&'a [u8]>,
+}
+
+impl Default for SvgOptions<'_> {
+    fn default() -> Self {
+        Self {
+            font_family: "monospace",
+            font_data: None,
+        }
+    }
+}
+
+/// The attributes that split a row into separate `<text>` elements.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct TextStyle {
+    fg: [u8; 3],
+    modifier: Modifier,
+}
+
+impl TextStyle {
+    const MODIFIERS: Modifier = Modifier::BOLD
+        .union(Modifier::ITALIC)
+        .union(Modifier::UNDERLINED)
+        .union(Modifier::CROSSED_OUT);
+
+    fn is_decorated(&self) -> bool {
+        self.modifier
+            .intersects(Modifier::UNDERLINED | Modifier::CROSSED_OUT)
+    }
+}
+
+/// A run of cells of the same text style.
+struct TextRun {
+    x: u16,
+    cells: u16,
+    text: String,
+    style: TextStyle,
+}
+
+/// Returns the most common background color of the buffer.
+pub(crate) fn base_background(buffer: &Buffer) -> [u8; 3] {
+    let mut counts: Vec<([u8; 3], usize)> = Vec::new();
+    for cell in &buffer.content {
+        let bg = cell_colors(cell).1;
+        match counts.iter_mut().find(|(color, _)| *color == bg) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((bg, 1)),
+        }
+    }
+    counts
+        .iter()
+        .max_by_key(|(_, count)| *count)
+        .map_or([0; 3], |(color, _)| *color)
+}
+
+pub(crate) fn hex(color: [u8; 3]) -> String {
+    let mut out = String::with_capacity(7);
+    let _ = write!(out, "#{:02x}{:02x}{:02x}", color[0], color[1], color[2]);
+    out
+}
+
+pub(crate) fn escape_xml(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            // Control chars are not allowed in XML.
+            c if c.is_control() => out.push(' '),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Repackages a TTF or OTF font as WOFF 1.0, returning `None` if the font can't be parsed.
+fn sfnt_to_woff(font: &[u8]) -> Option<Vec<u8>> {
+    let flavor = read_u32(font, 0)?;
+    let num_tables = read_u16(font, 4)? as usize;
+    let mut tables = Vec::with_capacity(num_tables);
+    for i in 0..num_tables {
+        let record = 12 + 16 * i;
+        let tag = read_u32(font, record)?;
+        let checksum = read_u32(font, record + 4)?;
+        let offset = read_u32(font, record + 8)? as usize;
+        let length = read_u32(font, record + 12)? as usize;
+        let data = font.get(offset..offset.checked_add(length)?)?;
+        #[cfg(feature = "zlib")]
+        let compressed = Some(miniz_oxide::deflate::compress_to_vec_zlib(data, 9))
+            .filter(|compressed| compressed.len() < data.len());
+        #[cfg(not(feature = "zlib"))]
+        let compressed: Option<Vec<u8>> = None;
+        tables.push((tag, checksum, data, compressed));
+    }
+    tables.sort_by_key(|table| table.0);
+
+    let sfnt_size = 12
+        + 16 * num_tables
+        + tables
+            .iter()
+            .map(|t| t.2.len().next_multiple_of(4))
+            .sum::<usize>();
+    let mut woff = Vec::new();
+    woff.extend_from_slice(b"wOFF");
+    woff.extend_from_slice(&flavor.to_be_bytes());
+    woff.extend_from_slice(&[0; 4]); // Total length, filled in below.
+    woff.extend_from_slice(&(num_tables as u16).to_be_bytes());
+    woff.extend_from_slice(&[0; 2]);
+    woff.extend_from_slice(&(sfnt_size as u32).to_be_bytes());
+    // Version, then the offsets and lengths of the metadata and private blocks, all unused.
+    woff.extend_from_slice(&[0; 24]);
+
+    let mut offset = woff.len() + 20 * num_tables;
+    for (tag, checksum, data, compressed) in &tables {
+        let stored = compressed.as_deref().unwrap_or(data).len();
+        for value in [
+            *tag,
+            offset as u32,
+            stored as u32,
+            data.len() as u32,
+            *checksum,
+        ] {
+            woff.extend_from_slice(&value.to_be_bytes());
+        }
+        offset += stored.next_multiple_of(4);
+    }
+    for (_, _, data, compressed) in &tables {
+        woff.extend_from_slice(compressed.as_deref().unwrap_or(data));
+        woff.resize(woff.len().next_multiple_of(4), 0);
+    }
+    let length = woff.len() as u32;
+    woff[8..12].copy_from_slice(&length.to_be_bytes());
+    Some(woff)
+}
+
+/// Returns the font as a `data:` URL and its CSS format name.
+fn font_url(font: &[u8]) -> (String, &'static str) {
+    let (data, mime, format) = match font.get(..4) {
+        Some(b"wOFF") => (None, "font/woff", "woff"),
+        Some(b"wOF2") => (None, "font/woff2", "woff2"),
+        _ => match sfnt_to_woff(font) {
+            Some(woff) => (Some(woff), "font/woff", "woff"),
+            None => (None, "font/ttf", "truetype"),
+        },
+    };
+    let mut url = String::from("data:");
+    url.push_str(mime);
+    url.push_str(";base64,");
+    url.push_str(&base64(data.as_deref().unwrap_or(font)));
+    (url, format)
+}
+
+impl SoftBackend {
+    /// Exports the current buffer as an SVG image, with the same size and cell metrics as the
+    /// pixmap so the two line up.
+    ///
+    /// (to-svg options) -> String
+    ///
+    /// * options    : SvgOptions - Font family and optional embedded font
+    ///
+    /// Backgrounds are drawn as one rectangle per run of cells of the same color, and text as
+    /// one `<text>` element per run of cells of the same style, stretched to the width of its
+    /// cells. Colors are resolved as in the pixmap, including reversed, hidden and dim text.
+    /// Bold, italic, underlined and crossed out text use the matching font and decoration
+    /// attributes. Blinking text is shown, and images are left out.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use ratatui::Terminal;
+    /// use ratatui::widgets::Paragraph;
+    /// use soft_ratatui::{BitmapFont, SoftBackend, SvgOptions};
+    ///
+    /// let backend = SoftBackend::new_with_bitmap_font(20, 2, BitmapFont::FIXED_8X13);
+    /// let mut terminal = Terminal::new(backend).unwrap();
+    /// terminal
+    ///     .draw(|frame| frame.render_widget(Paragraph::new("Hello & goodbye"), frame.area()))
+    ///     .unwrap();
+    /// let svg = terminal.backend().to_svg(&SvgOptions::default());
+    /// assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"160\""));
+    /// assert!(svg.contains(">Hello &amp; goodbye</text>"));
+    /// ```
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let (cw, ch) = (self.char_width, self.char_height);
+        let (font_size, baseline) = self.text_metrics();
+        let area = self.buffer.area;
+        let (width, height) = (area.width as usize * cw, area.height as usize * ch);
+
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\">\n<style>\n"
+        );
+        let mut family = String::new();
+        escape_xml(&mut family, options.font_family);
+        if let Some(font) = options.font_data {
+            let (url, format) = font_url(font);
+            let _ = writeln!(
+                out,
+                "@font-face {{ font-family: \"{family}\"; src: url({url}) format(\"{format}\"); }}"
+            );
+        }
+        let _ = write!(
+            out,
+            "text {{ font-family: \"{family}\", monospace; font-size: {font_size}px; \
+             white-space: pre; }}\n</style>\n"
+        );
+
+        // The most common background fills the whole image, the other runs are drawn over it.
+        let base = base_background(&self.buffer);
+        let _ = writeln!(
+            out,
+            "<rect width=\"{width}\" height=\"{height}\" fill=\"{}\"/>",
+            hex(base)
+        );
+
+        for y in 0..area.height {
+            let row =
+                &self.buffer.content[y as usize * area.width as usize..][..area.width as usize];
+            let mut x = 0;
+            while x < row.len() {
+                let bg = cell_colors(&row[x]).1;
+                let start = x;
+                while x < row.len() && cell_colors(&row[x]).1 == bg {
+                    x += 1;
+                }
+                if bg != base {
+                    let _ = writeln!(
+                        out,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{ch}\" fill=\"{}\"/>",
+                        start * cw,
+                        y as usize * ch,
+                        (x - start) * cw,
+                        hex(bg)
+                    );
+                }
+            }
+
+            let mut runs: Vec<TextRun> = Vec::new();
+            let mut skip = 0;
+            for (x, cell) in row.iter().enumerate() {
+                // Cells covered by a wide char belong to its run, whatever their style.
+                if skip > 0
+                    && let Some(run) = runs.last_mut()
+                {
+                    skip -= 1;
+                    run.cells += 1;
+                    continue;
+                }
+                let symbol = cell.symbol();
+                let symbol =
+                    if cell.modifier.contains(Modifier::HIDDEN) || image::is_placeholder(symbol) {
+                        " "
+                    } else {
+                        skip = symbol.width().saturating_sub(1);
+                        symbol
+                    };
+                let style = TextStyle {
+                    fg: cell_colors(cell).0,
+                    modifier: cell.modifier & TextStyle::MODIFIERS,
+                };
+                match runs.last_mut() {
+                    Some(run) if run.style == style => {
+                        run.cells += 1;
+                        run.text.push_str(symbol);
+                    }
+                    _ => runs.push(TextRun {
+                        x: x as u16,
+                        cells: 1,
+                        text: String::from(symbol),
+                        style,
+                    }),
+                }
+            }
+
+            for mut run in runs {
+                if !run.style.is_decorated() {
+                    let trimmed = run.text.trim_start_matches(' ');
+                    let leading = run.text.len() - trimmed.len();
+                    let trimmed = trimmed.trim_end_matches(' ');
+                    run.x += leading as u16;
+                    run.cells -= (run.text.len() - trimmed.len()) as u16;
+                    run.text = String::from(trimmed);
+                }
+                if run.text.is_empty() {
+                    continue;
+                }
+                let _ = write!(
+                    out,
+                    "<text x=\"{}\" y=\"{}\" textLength=\"{}\" fill=\"{}\"",
+                    run.x as usize * cw,
+                    y as usize * ch + baseline as usize,
+                    run.cells as usize * cw,
+                    hex(run.style.fg)
+                );
+                let modifier = run.style.modifier;
+                if modifier.contains(Modifier::BOLD) {
+                    out.push_str(" font-weight=\"bold\"");
+                }
+                if modifier.contains(Modifier::ITALIC) {
+                    out.push_str(" font-style=\"italic\"");
+                }
+                match (
+                    modifier.contains(Modifier::UNDERLINED),
+                    modifier.contains(Modifier::CROSSED_OUT),
+                ) {
+                    (true, true) => out.push_str(" text-decoration=\"underline line-through\""),
+                    (true, false) => out.push_str(" text-decoration=\"underline\""),
+                    (false, true) => out.push_str(" text-decoration=\"line-through\""),
+                    (false, false) => {}
+                }
+                out.push('>');
+                escape_xml(&mut out, &run.text);
+                out.push_str("</text>\n");
+            }
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    /// Writes the current buffer as an SVG image, see [`Self::to_svg`].
+    #[cfg(feature = "std")]
+    pub fn write_svg<W: std::io::Write>(
+        &self,
+        writer: &mut W,
+        options: &SvgOptions,
+    ) -> std::io::Result<()> {
+        writer.write_all(self.to_svg(options).as_bytes())
+    }
+}
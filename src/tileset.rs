@@ -0,0 +1,264 @@
+//! Sprite sheet tilesets, as used by Dwarf Fortress and libtcod style roguelikes.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::colors::*;
+use crate::pixmap::RgbPixmap;
+use ratatui::style::Modifier;
+
+/// The unicode characters of code page 437, indexed by their byte value.
+///
+/// Tile `n` of a CP437 sprite sheet (counting left to right, top to bottom) draws `CP437[n]`.
+pub const CP437: [char; 256] = [
+    ' ', '☺', '☻', '♥', '♦', '♣', '♠', '•', '◘', '○', '◙', '♂', '♀', '♪', '♫', '☼', //
+    '►', '◄', '↕', '‼', '¶', '§', '▬', '↨', '↑', '↓', '→', '←', '∟', '↔', '▲', '▼', //
+    ' ', '!', '"', '#', '$', '%', '&', '\'', '(', ')', '*', '+', ',', '-', '.', '/', //
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', ':', ';', '<', '=', '>', '?', //
+    '@', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', //
+    'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '[', '\\', ']', '^', '_', //
+    '`', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', //
+    'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', '{', '|', '}', '~', '⌂', //
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', //
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', //
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', //
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐', //
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧', //
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀', //
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', //
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// A sprite sheet of equally sized tiles, used in place of a font.
+///
+/// Every tile is treated as a coverage mask: white (or opaque) pixels take the cell's foreground
+/// color and black (or transparent) pixels take its background color, so both the classic
+/// white-on-black and the white-on-transparent sheet styles work.
+#[derive(Debug, Clone)]
+pub struct Tileset {
+    tile_width: usize,
+    tile_height: usize,
+    columns: usize,
+    tile_count: usize,
+    sheet_width: usize,
+    coverage: Vec<u8>,
+    mapping: BTreeMap<char, usize>,
+    fallback: usize,
+}
+
+impl Tileset {
+    /// Creates a tileset from a decoded RGBA sprite sheet, mapped with the [`CP437`] table.
+    ///
+    /// (from-rgba data width height tile-width tile-height) -> Tileset
+    ///
+    /// * data        : &[u8] - RGBA pixels of the sheet, row by row
+    /// * width       : usize - Width of the sheet in pixels
+    /// * height      : usize - Height of the sheet in pixels
+    /// * tile-width  : usize - Width of a single tile in pixels, becomes the cell width
+    /// * tile-height : usize - Height of a single tile in pixels, becomes the cell height
+    ///
+    /// # Examples
+    /// ```rust
+    /// use soft_ratatui::Tileset;
+    /// // A 16x16 grid of 8x8 tiles, all blank.
+    /// let sheet = vec![0; 128 * 128 * 4];
+    /// let tileset = Tileset::from_rgba(&sheet, 128, 128, 8, 8);
+    /// assert_eq!(tileset.tile_width(), 8);
+    /// ```
+    pub fn from_rgba(
+        data: &[u8],
+        width: usize,
+        height: usize,
+        tile_width: usize,
+        tile_height: usize,
+    ) -> Self {
+        assert_eq!(
+            data.len(),
+            width * height * 4,
+            "RGBA data does not match sheet size"
+        );
+        assert!(
+            tile_width > 0 && tile_height > 0 && tile_width <= width && tile_height <= height,
+            "Tile size does not fit the sheet"
+        );
+
+        let coverage = data
+            .chunks_exact(4)
+            .map(|px| {
+                let luma = px[0].max(px[1]).max(px[2]) as u32;
+                ((luma * px[3] as u32 + 127) / 255) as u8
+            })
+            .collect();
+
+        let columns = width / tile_width;
+        let tile_count = columns * (height / tile_height);
+        let mapping = CP437
+            .iter()
+            .take(tile_count)
+            .enumerate()
+            .map(|(index, c)| (*c, index))
+            .collect();
+
+        Self {
+            tile_width,
+            tile_height,
+            columns,
+            tile_count,
+            sheet_width: width,
+            coverage,
+            mapping,
+            fallback: ('?' as usize).min(tile_count - 1),
+        }
+    }
+
+    /// Replaces the char to tile table, for sheets that are not laid out in CP437 order.
+    ///
+    /// Entries with an index past the last tile are left out, so their chars draw the fallback
+    /// tile.
+    pub fn with_mapping(mut self, mut mapping: BTreeMap<char, usize>) -> Self {
+        mapping.retain(|_, index| *index < self.tile_count);
+        self.mapping = mapping;
+        self
+    }
+
+    /// Maps a single char to the tile at `index`, keeping the rest of the table. Returns false,
+    /// leaving the table unchanged, if `index` is past the last tile.
+    pub fn map_char(&mut self, c: char, index: usize) -> bool {
+        if index >= self.tile_count {
+            return false;
+        }
+        self.mapping.insert(c, index);
+        true
+    }
+
+    /// Sets the tile drawn for chars that have no entry in the table. Defaults to `?`. Returns
+    /// false, keeping the current fallback, if `index` is past the last tile.
+    pub fn set_fallback(&mut self, index: usize) -> bool {
+        if index >= self.tile_count {
+            return false;
+        }
+        self.fallback = index;
+        true
+    }
+
+    /// Returns the width of a tile in pixels
+    pub fn tile_width(&self) -> usize {
+        self.tile_width
+    }
+    /// Returns the height of a tile in pixels
+    pub fn tile_height(&self) -> usize {
+        self.tile_height
+    }
+    /// Returns the number of tiles in the sheet
+    pub fn tile_count(&self) -> usize {
+        self.tile_count
+    }
+
+    /// Returns the tile index used to draw a cell symbol.
+    pub fn tile_index(&self, symbol: &str) -> Option<usize> {
+        let c = symbol.chars().next()?;
+        Some(*self.mapping.get(&c).unwrap_or(&self.fallback))
+    }
+
+    /// Draws `symbol` with its top left corner at (begin_x, begin_y), tinting the tile with
+    /// `fg_color` over `bg_color`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn draw_symbol(
+        &self,
+        pixmap: &mut RgbPixmap,
+        begin_x: usize,
+        begin_y: usize,
+        symbol: &str,
+        modifier: Modifier,
+        fg_color: [u8; 3],
+        bg_color: [u8; 3],
+    ) {
+        if let Some(index) = self.tile_index(symbol) {
+            let tile_x = (index % self.columns) * self.tile_width;
+            let tile_y = (index / self.columns) * self.tile_height;
+
+            for y in 0..self.tile_height {
+                let row = (tile_y + y) * self.sheet_width + tile_x;
+                for x in 0..self.tile_width {
+                    let alpha = self.coverage[row + x];
+                    if alpha > 0 {
+                        let put_color = blend_rgba(
+                            [fg_color[0], fg_color[1], fg_color[2], alpha],
+                            [bg_color[0], bg_color[1], bg_color[2], 255],
+                        );
+                        pixmap.put_pixel(begin_x + x, begin_y + y, put_color);
+                    }
+                }
+            }
+        }
+
+        // Tiles have no combining marks, so the line modifiers are drawn directly.
+        if modifier.contains(Modifier::UNDERLINED) {
+            let y = begin_y + self.tile_height - 1;
+            pixmap.fill_rect(begin_x, y, self.tile_width, 1, fg_color);
+        }
+        if modifier.contains(Modifier::CROSSED_OUT) {
+            let y = begin_y + self.tile_height / 2;
+            pixmap.fill_rect(begin_x, y, self.tile_width, 1, fg_color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sheet of two 2x2 tiles side by side, the first fully covered and the second blank.
+    fn tileset() -> Tileset {
+        let mut sheet = alloc::vec![0; 4 * 2 * 4];
+        for y in 0..2 {
+            for x in 0..2 {
+                sheet[(y * 4 + x) * 4..][..4].copy_from_slice(&[255; 4]);
+            }
+        }
+        Tileset::from_rgba(&sheet, 4, 2, 2, 2)
+    }
+
+    #[test]
+    fn maps_the_first_cp437_tiles() {
+        let tileset = tileset();
+        assert_eq!(tileset.tile_count(), 2);
+        assert_eq!(tileset.tile_index(" "), Some(0));
+        assert_eq!(tileset.tile_index("☺"), Some(1));
+        // `?` is past the last tile, so the last tile is the fallback.
+        assert_eq!(tileset.tile_index("A"), Some(1));
+        assert_eq!(tileset.tile_index(""), None);
+    }
+
+    #[test]
+    fn ignores_indices_past_the_last_tile() {
+        let mut tileset = tileset();
+        assert!(tileset.map_char('a', 0));
+        assert!(!tileset.map_char('b', 2));
+        assert_eq!(tileset.tile_index("a"), Some(0));
+        assert_eq!(tileset.tile_index("b"), Some(1));
+
+        assert!(tileset.set_fallback(0));
+        assert!(!tileset.set_fallback(5));
+        assert_eq!(tileset.tile_index("b"), Some(0));
+
+        let mapping = BTreeMap::from([('x', 1), ('y', 100)]);
+        let tileset = tileset.with_mapping(mapping);
+        assert_eq!(tileset.tile_index("x"), Some(1));
+        assert_eq!(tileset.tile_index("y"), Some(0));
+    }
+
+    #[test]
+    fn draws_tile_coverage() {
+        let mut tileset = tileset().with_mapping(BTreeMap::from([('#', 0), ('.', 1)]));
+        tileset.set_fallback(1);
+        let mut pixmap = RgbPixmap::new(6, 2);
+        let (fg, bg) = ([255, 0, 0], [0, 0, 0]);
+        for (i, symbol) in ["#", ".", "out of range"].into_iter().enumerate() {
+            tileset.draw_symbol(&mut pixmap, i * 2, 0, symbol, Modifier::empty(), fg, bg);
+        }
+        assert_eq!(pixmap.get_pixel(1, 1), fg);
+        assert_eq!(pixmap.get_pixel(3, 1), [0; 3]);
+        assert_eq!(pixmap.get_pixel(5, 1), [0; 3]);
+    }
+}
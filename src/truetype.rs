@@ -0,0 +1,511 @@
+//! Just enough TrueType parsing to embed fonts in exports: char to glyph mapping, advance
+//! widths, names and glyph subsetting.
+
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(offset..offset.checked_add(2)?)?.try_into().ok()?,
+    ))
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset.checked_add(4)?)?.try_into().ok()?,
+    ))
+}
+
+fn read_i16(data: &[u8], offset: usize) -> Option<i16> {
+    read_u16(data, offset).map(|value| value as i16)
+}
+
+/// The checksum of a table, the sum of its big endian u32 words.
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Assembles an sfnt font file from its tables, which must be sorted by tag.
+pub(crate) fn write_sfnt(flavor: u32, tables: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let num_tables = tables.len() as u16;
+    let entry_selector = 15 - num_tables.max(1).leading_zeros() as u16;
+    let search_range = 16 << entry_selector;
+    let mut font = Vec::new();
+    font.extend_from_slice(&flavor.to_be_bytes());
+    for value in [
+        num_tables,
+        search_range,
+        entry_selector,
+        num_tables * 16 - search_range,
+    ] {
+        font.extend_from_slice(&value.to_be_bytes());
+    }
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, data) in tables {
+        font.extend_from_slice(tag);
+        font.extend_from_slice(&checksum(data).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in tables {
+        font.extend_from_slice(data);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+    font
+}
+
+/// A parsed TrueType font, with glyph outlines in a `glyf` table.
+pub(crate) struct TrueTypeFont {
+    data: Vec<u8>,
+    /// The tag, offset and length of every table.
+    tables: Vec<([u8; 4], usize, usize)>,
+    /// Ranges of chars mapped to consecutive glyphs, as (first char, last char, first glyph).
+    char_ranges: Vec<(u32, u32, u16)>,
+    num_glyphs: u16,
+    num_h_metrics: u16,
+    long_loca: bool,
+    pub(crate) units_per_em: u16,
+    pub(crate) ascender: i16,
+    pub(crate) descender: i16,
+    pub(crate) bbox: [i16; 4],
+}
+
+impl TrueTypeFont {
+    /// Parses a TrueType font, returning `None` for other fonts, such as CFF based OpenType
+    /// fonts and font collections.
+    pub(crate) fn parse(data: &[u8]) -> Option<Self> {
+        if !matches!(read_u32(data, 0)?, 0x0001_0000 | 0x7472_7565) {
+            return None;
+        }
+        let mut tables = Vec::new();
+        for i in 0..read_u16(data, 4)? as usize {
+            let record = 12 + 16 * i;
+            let tag = data.get(record..record + 4)?.try_into().ok()?;
+            let offset = read_u32(data, record + 8)? as usize;
+            let length = read_u32(data, record + 12)? as usize;
+            data.get(offset..offset.checked_add(length)?)?;
+            tables.push((tag, offset, length));
+        }
+        let mut font = Self {
+            data: data.to_vec(),
+            tables,
+            char_ranges: Vec::new(),
+            num_glyphs: 0,
+            num_h_metrics: 0,
+            long_loca: false,
+            units_per_em: 0,
+            ascender: 0,
+            descender: 0,
+            bbox: [0; 4],
+        };
+        let head = font.table(b"head")?;
+        let units_per_em = read_u16(head, 18)?.max(1);
+        let bbox = [
+            read_i16(head, 36)?,
+            read_i16(head, 38)?,
+            read_i16(head, 40)?,
+            read_i16(head, 42)?,
+        ];
+        let long_loca = read_i16(head, 50)? == 1;
+        let hhea = font.table(b"hhea")?;
+        let (ascender, descender) = (read_i16(hhea, 4)?, read_i16(hhea, 6)?);
+        let num_h_metrics = read_u16(hhea, 34)?.max(1);
+        font.units_per_em = units_per_em;
+        font.bbox = bbox;
+        font.long_loca = long_loca;
+        font.ascender = ascender;
+        font.descender = descender;
+        font.num_h_metrics = num_h_metrics;
+        font.num_glyphs = read_u16(font.table(b"maxp")?, 4)?;
+        font.table(b"hmtx")?;
+        font.table(b"glyf")?;
+        font.glyph_range(font.num_glyphs.checked_sub(1)?)?;
+        font.char_ranges = font.parse_cmap()?;
+        Some(font)
+    }
+
+    fn table(&self, tag: &[u8; 4]) -> Option<&[u8]> {
+        let (_, offset, length) = self.tables.iter().find(|table| &table.0 == tag)?;
+        Some(&self.data[*offset..*offset + *length])
+    }
+
+    /// Reads the best Unicode subtable of the `cmap` table.
+    fn parse_cmap(&self) -> Option<Vec<(u32, u32, u16)>> {
+        let cmap = self.table(b"cmap")?;
+        let mut best = None;
+        for i in 0..read_u16(cmap, 2)? as usize {
+            let platform = read_u16(cmap, 4 + 8 * i)?;
+            let encoding = read_u16(cmap, 6 + 8 * i)?;
+            let offset = read_u32(cmap, 8 + 8 * i)? as usize;
+            let format = read_u16(cmap, offset)?;
+            let rank = match (platform, encoding, format) {
+                (3, 10, 12) | (0, _, 12) => 2,
+                (3, 1, 4) | (0, _, 4) => 1,
+                _ => continue,
+            };
+            if best.is_none_or(|(best_rank, _)| rank > best_rank) {
+                best = Some((rank, offset));
+            }
+        }
+        let (_, offset) = best?;
+        let subtable = cmap.get(offset..)?;
+        let mut ranges = Vec::new();
+        if read_u16(subtable, 0)? == 12 {
+            for i in 0..read_u32(subtable, 12)? as usize {
+                let group = 16 + 12 * i;
+                ranges.push((
+                    read_u32(subtable, group)?,
+                    read_u32(subtable, group + 4)?,
+                    read_u32(subtable, group + 8)? as u16,
+                ));
+            }
+        } else {
+            let segments = read_u16(subtable, 6)? as usize / 2;
+            let ends = 14;
+            let starts = ends + 2 * segments + 2;
+            let deltas = starts + 2 * segments;
+            let range_offsets = deltas + 2 * segments;
+            for i in 0..segments {
+                let end = read_u16(subtable, ends + 2 * i)?;
+                let start = read_u16(subtable, starts + 2 * i)?;
+                let delta = read_u16(subtable, deltas + 2 * i)?;
+                let range_offset = read_u16(subtable, range_offsets + 2 * i)? as usize;
+                if start > end || start == 0xffff {
+                    continue;
+                }
+                if range_offset == 0 {
+                    // Split where the glyph ids wrap around, so each range stays consecutive.
+                    let first = start.wrapping_add(delta);
+                    let wrap = (0x1_0000 - first as u32).min(end as u32 - start as u32 + 1);
+                    ranges.push((start as u32, start as u32 + wrap - 1, first));
+                    if start as u32 + wrap <= end as u32 {
+                        ranges.push((start as u32 + wrap, end as u32, 0));
+                    }
+                } else {
+                    for c in start..=end {
+                        let index = range_offsets + 2 * i + range_offset + 2 * (c - start) as usize;
+                        let glyph = read_u16(subtable, index)?;
+                        if glyph != 0 {
+                            let glyph = glyph.wrapping_add(delta);
+                            ranges.push((c as u32, c as u32, glyph));
+                        }
+                    }
+                }
+            }
+        }
+        ranges.sort_unstable();
+        Some(ranges)
+    }
+
+    /// Returns the glyph of a char, 0 if the font doesn't have one.
+    pub(crate) fn glyph_id(&self, c: char) -> u16 {
+        let c = c as u32;
+        let index = self.char_ranges.partition_point(|range| range.1 < c);
+        match self.char_ranges.get(index) {
+            Some(&(first, _, glyph)) if first <= c => glyph.wrapping_add((c - first) as u16),
+            _ => 0,
+        }
+    }
+
+    /// Returns the advance width of a glyph, in font units.
+    pub(crate) fn advance(&self, glyph: u16) -> u16 {
+        let index = glyph.min(self.num_h_metrics - 1) as usize;
+        self.table(b"hmtx")
+            .and_then(|hmtx| read_u16(hmtx, 4 * index))
+            .unwrap_or(0)
+    }
+
+    /// Returns the PostScript name of the font, or `None` if it has none in ASCII.
+    pub(crate) fn postscript_name(&self) -> Option<String> {
+        let name = self.table(b"name")?;
+        let strings = read_u16(name, 4)? as usize;
+        for i in 0..read_u16(name, 2)? as usize {
+            let record = 6 + 12 * i;
+            if read_u16(name, record + 6)? != 6 {
+                continue;
+            }
+            let platform = read_u16(name, record)?;
+            let length = read_u16(name, record + 8)? as usize;
+            let offset = strings + read_u16(name, record + 10)? as usize;
+            let bytes = name.get(offset..offset + length)?;
+            let chars: String = match platform {
+                1 => bytes.iter().map(|b| *b as char).collect(),
+                0 | 3 => bytes
+                    .chunks_exact(2)
+                    .map(|pair| char::from(pair[1]))
+                    .collect(),
+                _ => continue,
+            };
+            if !chars.is_empty() && chars.chars().all(|c| c.is_ascii_graphic()) {
+                return Some(chars);
+            }
+        }
+        None
+    }
+
+    fn glyph_range(&self, glyph: u16) -> Option<(usize, usize)> {
+        let loca = self.table(b"loca")?;
+        let glyph = glyph as usize;
+        let (start, end) = if self.long_loca {
+            (
+                read_u32(loca, 4 * glyph)? as usize,
+                read_u32(loca, 4 * glyph + 4)? as usize,
+            )
+        } else {
+            (
+                read_u16(loca, 2 * glyph)? as usize * 2,
+                read_u16(loca, 2 * glyph + 2)? as usize * 2,
+            )
+        };
+        self.table(b"glyf")?.get(start..end)?;
+        Some((start, end))
+    }
+
+    /// Returns the glyphs a composite glyph is made of.
+    fn components(&self, glyph: u16) -> Vec<u16> {
+        let mut components = Vec::new();
+        let (Some((start, end)), Some(glyf)) = (self.glyph_range(glyph), self.table(b"glyf"))
+        else {
+            return components;
+        };
+        let data = &glyf[start..end];
+        if read_i16(data, 0).is_none_or(|contours| contours >= 0) {
+            return components;
+        }
+        let mut offset = 10;
+        while let (Some(flags), Some(component)) =
+            (read_u16(data, offset), read_u16(data, offset + 2))
+        {
+            components.push(component);
+            offset += 4;
+            offset += if flags & 0x0001 != 0 { 4 } else { 2 };
+            offset += match flags {
+                _ if flags & 0x0008 != 0 => 2,
+                _ if flags & 0x0040 != 0 => 4,
+                _ if flags & 0x0080 != 0 => 8,
+                _ => 0,
+            };
+            if flags & 0x0020 == 0 {
+                break;
+            }
+        }
+        components
+    }
+
+    /// Returns a copy of the font that keeps only the outlines of the given glyphs and the
+    /// glyphs they are made of. Glyph ids are unchanged, the other glyphs are left empty.
+    pub(crate) fn subset(&self, glyphs: &BTreeSet<u16>) -> Vec<u8> {
+        let mut keep = BTreeSet::from([0]);
+        let mut pending: Vec<u16> = glyphs.iter().copied().collect();
+        while let Some(glyph) = pending.pop() {
+            if glyph < self.num_glyphs && keep.insert(glyph) {
+                pending.extend(self.components(glyph));
+            }
+        }
+
+        let glyf = self.table(b"glyf").unwrap_or_default();
+        let mut new_glyf = Vec::new();
+        let mut loca = Vec::with_capacity(4 * (self.num_glyphs as usize + 1));
+        for glyph in 0..self.num_glyphs {
+            loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+            if keep.contains(&glyph)
+                && let Some((start, end)) = self.glyph_range(glyph)
+            {
+                new_glyf.extend_from_slice(&glyf[start..end]);
+                new_glyf.resize(new_glyf.len().next_multiple_of(4), 0);
+            }
+        }
+        loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+
+        let mut tables = Vec::new();
+        for tag in [
+            b"cvt ", b"fpgm", b"glyf", b"head", b"hhea", b"hmtx", b"loca", b"maxp", b"prep",
+        ] {
+            let data = match tag {
+                b"glyf" => core::mem::take(&mut new_glyf),
+                b"loca" => core::mem::take(&mut loca),
+                _ => match self.table(tag) {
+                    Some(data) => data.to_vec(),
+                    None => continue,
+                },
+            };
+            tables.push((*tag, data));
+        }
+        for (tag, data) in &mut tables {
+            if tag == b"head" && data.len() >= 54 {
+                // Clear the checksum adjustment and switch to long loca offsets.
+                data[8..12].fill(0);
+                data[50..52].copy_from_slice(&1u16.to_be_bytes());
+            }
+        }
+        let mut font = write_sfnt(0x0001_0000, &tables);
+        if let Some(head) = head_offset(&font) {
+            let adjustment = 0xb1b0_afba_u32.wrapping_sub(checksum(&font));
+            font[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+        }
+        font
+    }
+}
+
+/// Returns the offset of the `head` table of an sfnt font file.
+fn head_offset(font: &[u8]) -> Option<usize> {
+    (0..read_u16(font, 4)? as usize)
+        .map(|i| 12 + 16 * i)
+        .find(|record| font.get(*record..*record + 4) == Some(b"head"))
+        .and_then(|record| read_u32(font, record + 8))
+        .map(|offset| offset as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    static FONT_DATA: &[u8] = include_bytes!("../assets/fm.ttf");
+
+    /// Returns the tables of an sfnt font file, in the order of its table directory.
+    fn tables(font: &[u8]) -> Vec<([u8; 4], &[u8])> {
+        (0..read_u16(font, 4).unwrap() as usize)
+            .map(|i| {
+                let record = 12 + 16 * i;
+                let offset = read_u32(font, record + 8).unwrap() as usize;
+                let length = read_u32(font, record + 12).unwrap() as usize;
+                let tag = font[record..record + 4].try_into().unwrap();
+                (tag, &font[offset..offset + length])
+            })
+            .collect()
+    }
+
+    /// Returns the outline of a glyph in a font with long `loca` offsets, such as a subset.
+    fn glyph_data(font: &[u8], glyph: usize) -> &[u8] {
+        let tables = tables(font);
+        let table = |tag: &[u8; 4]| tables.iter().find(|table| &table.0 == tag).unwrap().1;
+        let loca = table(b"loca");
+        let start = read_u32(loca, 4 * glyph).unwrap() as usize;
+        let end = read_u32(loca, 4 * glyph + 4).unwrap() as usize;
+        &table(b"glyf")[start..end]
+    }
+
+    /// A font of three glyphs: none, a simple one and a composite of the simple one mapped
+    /// to 'a'.
+    fn composite_font() -> Vec<u8> {
+        let mut head = vec![0; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        let mut hhea = vec![0; 36];
+        hhea[34..36].copy_from_slice(&3u16.to_be_bytes());
+        let maxp = [0, 0, 0x50, 0, 0, 3].to_vec();
+        let hmtx = vec![0; 12];
+        let simple = [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        // numberOfContours -1, the bounding box, then one component with byte offsets.
+        let composite = [0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0];
+        let glyf = [&simple[..], &composite[..]].concat();
+        // Short offsets, in units of two bytes.
+        let loca = [0u16, 0, 6, 14]
+            .iter()
+            .flat_map(|v| v.to_be_bytes())
+            .collect();
+        let mut cmap: Vec<u8> = [0, 0, 0, 1, 0, 3, 0, 1, 0, 0, 0, 12].to_vec();
+        let delta = 2u16.wrapping_sub(0x61);
+        for value in [
+            4, 32, 0, 4, 0, 0, 0, 0x61, 0xffff, 0, 0x61, 0xffff, delta, 1, 0, 0,
+        ] {
+            cmap.extend_from_slice(&u16::to_be_bytes(value));
+        }
+        write_sfnt(
+            0x0001_0000,
+            &[
+                (*b"cmap", cmap),
+                (*b"glyf", glyf),
+                (*b"head", head),
+                (*b"hhea", hhea),
+                (*b"hmtx", hmtx),
+                (*b"loca", loca),
+                (*b"maxp", maxp),
+            ],
+        )
+    }
+
+    #[test]
+    fn parses_the_bundled_font() {
+        let font = TrueTypeFont::parse(FONT_DATA).unwrap();
+        assert_eq!(font.postscript_name().as_deref(), Some("FiraMono-Medium"));
+        assert_eq!(font.glyph_id('A'), 36);
+        assert_eq!(font.glyph_id('中'), 0);
+        // A monospace font.
+        assert_eq!(font.advance(font.glyph_id('A')), 600);
+        assert_eq!(font.advance(font.glyph_id('i')), 600);
+    }
+
+    #[test]
+    fn rejects_truncated_and_invalid_fonts() {
+        for len in (0..FONT_DATA.len()).step_by(61) {
+            assert!(TrueTypeFont::parse(&FONT_DATA[..len]).is_none());
+        }
+        // CFF based OpenType.
+        let mut font = FONT_DATA.to_vec();
+        font[..4].copy_from_slice(b"OTTO");
+        assert!(TrueTypeFont::parse(&font).is_none());
+        // A table past the end of the file.
+        let mut font = FONT_DATA.to_vec();
+        font[20..24].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(TrueTypeFont::parse(&font).is_none());
+    }
+
+    #[test]
+    fn write_sfnt_directory_and_checksums() {
+        let font = write_sfnt(
+            0x0001_0000,
+            &[(*b"abcd", vec![1, 2, 3]), (*b"efgh", vec![0; 8])],
+        );
+        // Search range, entry selector and range shift for two tables.
+        assert_eq!(font[4..12], [0, 2, 0, 32, 0, 1, 0, 0]);
+        assert_eq!(tables(&font)[0], (*b"abcd", &[1, 2, 3][..]));
+        assert_eq!(read_u32(&font, 16), Some(0x0102_0300));
+        // Tables start on four byte boundaries.
+        assert_eq!(read_u32(&font, 28 + 8), Some(12 + 32 + 4));
+    }
+
+    #[test]
+    fn subset_keeps_only_the_requested_glyphs() {
+        let font = TrueTypeFont::parse(FONT_DATA).unwrap();
+        let (a, i) = (font.glyph_id('A'), font.glyph_id('i'));
+        let subset = font.subset(&BTreeSet::from([a]));
+        assert!(subset.len() < FONT_DATA.len());
+
+        let tags: Vec<[u8; 4]> = tables(&subset).iter().map(|table| table.0).collect();
+        assert_eq!(
+            tags,
+            [
+                *b"cvt ", *b"fpgm", *b"glyf", *b"head", *b"hhea", *b"hmtx", *b"loca", *b"maxp",
+                *b"prep"
+            ]
+        );
+        // The checksum adjustment makes the whole file sum to the magic number.
+        assert_eq!(checksum(&subset), 0xb1b0_afba);
+
+        let (start, end) = font.glyph_range(a).unwrap();
+        let original = &font.table(b"glyf").unwrap()[start..end];
+        assert_eq!(&glyph_data(&subset, a as usize)[..original.len()], original);
+        assert!(!glyph_data(&subset, 0).is_empty());
+        assert!(glyph_data(&subset, i as usize).is_empty());
+    }
+
+    #[test]
+    fn subset_keeps_composite_components() {
+        let data = composite_font();
+        let font = TrueTypeFont::parse(&data).unwrap();
+        assert_eq!(font.glyph_id('a'), 2);
+        assert_eq!(font.glyph_id('b'), 0);
+        assert_eq!(font.components(2), [1]);
+
+        let subset = font.subset(&BTreeSet::from([2]));
+        assert_eq!(glyph_data(&subset, 1).len(), 12);
+        assert_eq!(glyph_data(&subset, 2).len(), 16);
+    }
+}
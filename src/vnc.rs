@@ -0,0 +1,688 @@
+//! A minimal VNC (RFB 3.8) server showing the pixmap of a [`SoftBackend`] to any VNC viewer,
+//! e.g. to look at a dashboard running on a headless machine.
+//!
+//! Only the "None" security type is offered, so the server should listen on a trusted network
+//! or behind a tunnel.
+
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::string::String;
+use std::vec;
+use std::vec::Vec;
+
+use ratatui::layout::Position;
+
+use crate::input::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crate::pixmap::{PixelRect, RgbPixmap};
+use crate::soft_backend::SoftBackend;
+
+const ENCODING_RAW: i32 = 0;
+#[cfg(feature = "zlib")]
+const ENCODING_ZLIB: i32 = 6;
+const ENCODING_DESKTOP_SIZE: i32 = -223;
+
+/// Output waiting for a slow viewer above which no new updates are queued for it.
+const MAX_PENDING_OUTPUT: usize = 4 << 20;
+/// The longest clipboard text accepted from a viewer.
+const MAX_CUT_TEXT: usize = 1 << 20;
+/// The most rectangles sent in an update, more are merged into their bounding box.
+const MAX_RECTS: usize = 64;
+
+/// An input event from a viewer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VncEvent {
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+}
+
+/// The pixel format of a viewer, `PIXEL_FORMAT` in the RFB specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PixelFormat {
+    bits_per_pixel: u8,
+    depth: u8,
+    big_endian: bool,
+    true_colour: bool,
+    max: [u16; 3],
+    shift: [u8; 3],
+}
+
+impl PixelFormat {
+    /// The format offered by the server, `0x00RRGGBB` in little endian.
+    const SERVER: Self = Self {
+        bits_per_pixel: 32,
+        depth: 24,
+        big_endian: false,
+        true_colour: true,
+        max: [255; 3],
+        shift: [16, 8, 0],
+    };
+
+    fn parse(bytes: &[u8]) -> Self {
+        Self {
+            bits_per_pixel: bytes[0],
+            depth: bytes[1],
+            big_endian: bytes[2] != 0,
+            true_colour: bytes[3] != 0,
+            max: [
+                u16::from_be_bytes([bytes[4], bytes[5]]),
+                u16::from_be_bytes([bytes[6], bytes[7]]),
+                u16::from_be_bytes([bytes[8], bytes[9]]),
+            ],
+            shift: [bytes[10], bytes[11], bytes[12]],
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[
+            self.bits_per_pixel,
+            self.depth,
+            self.big_endian as u8,
+            self.true_colour as u8,
+        ]);
+        for max in self.max {
+            out.extend_from_slice(&max.to_be_bytes());
+        }
+        out.extend_from_slice(&self.shift);
+        out.extend_from_slice(&[0; 3]);
+    }
+
+    /// Appends the pixels of a rectangle of the pixmap.
+    fn encode(&self, pixmap: &RgbPixmap, rect: PixelRect, out: &mut Vec<u8>) {
+        let bytes = self.bits_per_pixel as usize / 8;
+        out.reserve(rect.width * rect.height * bytes);
+        for y in rect.y..rect.y + rect.height {
+            let row = &pixmap.data()[3 * (y * pixmap.width() + rect.x)..][..3 * rect.width];
+            for rgb in row.chunks_exact(3) {
+                let mut value = 0;
+                for ((&component, max), shift) in rgb.iter().zip(self.max).zip(self.shift) {
+                    value |= ((component as u32 * max as u32 + 127) / 255) << shift;
+                }
+                if self.big_endian {
+                    out.extend_from_slice(&value.to_be_bytes()[4 - bytes..]);
+                } else {
+                    out.extend_from_slice(&value.to_le_bytes()[..bytes]);
+                }
+            }
+        }
+    }
+}
+
+/// Converts an X keysym to a key, `None` for modifiers and keys with no terminal equivalent.
+fn keysym_to_key(keysym: u32, shift: bool) -> Option<KeyCode> {
+    Some(match keysym {
+        0xff0d | 0xff8d => KeyCode::Enter,
+        0xff08 => KeyCode::Backspace,
+        0xff09 if shift => KeyCode::BackTab,
+        0xff09 => KeyCode::Tab,
+        0xfe20 => KeyCode::BackTab,
+        0xff1b => KeyCode::Esc,
+        0xff51 => KeyCode::Left,
+        0xff52 => KeyCode::Up,
+        0xff53 => KeyCode::Right,
+        0xff54 => KeyCode::Down,
+        0xff50 => KeyCode::Home,
+        0xff57 => KeyCode::End,
+        0xff55 => KeyCode::PageUp,
+        0xff56 => KeyCode::PageDown,
+        0xff63 => KeyCode::Insert,
+        0xffff => KeyCode::Delete,
+        0xffbe..=0xffc9 => KeyCode::F((keysym - 0xffbe + 1) as u8),
+        // Latin-1 keysyms are their code points, others are offset by 0x01000000.
+        0x20..=0x7e | 0xa0..=0xff => KeyCode::Char(char::from_u32(keysym)?),
+        0x0100_0100..=0x0110_ffff => KeyCode::Char(char::from_u32(keysym - 0x0100_0000)?),
+        _ => return None,
+    })
+}
+
+/// Returns the smallest rectangle containing both.
+fn union(a: PixelRect, b: PixelRect) -> PixelRect {
+    let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+    PixelRect {
+        x,
+        y,
+        width: (a.x + a.width).max(b.x + b.width) - x,
+        height: (a.y + a.height).max(b.y + b.height) - y,
+    }
+}
+
+/// Returns the part of a rectangle inside a `width` by `height` area.
+fn clip(rect: PixelRect, width: usize, height: usize) -> Option<PixelRect> {
+    let (x_end, y_end) = (
+        (rect.x + rect.width).min(width),
+        (rect.y + rect.height).min(height),
+    );
+    (rect.x < x_end && rect.y < y_end).then(|| PixelRect {
+        x: rect.x,
+        y: rect.y,
+        width: x_end - rect.x,
+        height: y_end - rect.y,
+    })
+}
+
+/// The handshake stage of a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    /// Waiting for the protocol version of the viewer.
+    Version,
+    /// Waiting for the security type chosen by the viewer.
+    Security,
+    /// Waiting for the `ClientInit` message.
+    ClientInit,
+    /// Exchanging normal messages.
+    Normal,
+}
+
+struct Client {
+    stream: TcpStream,
+    stage: Stage,
+    /// The minor RFB version sent by the viewer.
+    minor: u32,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    format: PixelFormat,
+    /// Whether the last encodings set by the viewer include zlib.
+    #[cfg(feature = "zlib")]
+    use_zlib: bool,
+    /// The zlib stream, kept once created since the viewer decompresses a single stream.
+    #[cfg(feature = "zlib")]
+    zlib: Option<std::boxed::Box<miniz_oxide::deflate::core::CompressorOxide>>,
+    desktop_size: bool,
+    /// The regions changed since the last update sent.
+    damage: Vec<PixelRect>,
+    /// Whether the viewer asked for an update that wasn't sent yet.
+    requested: bool,
+    /// The size of the pixmap known to the viewer.
+    size: (usize, usize),
+    buttons: u8,
+    pointer: Option<Position>,
+    modifiers: KeyModifiers,
+}
+
+impl Client {
+    /// Handles the complete messages received, returns an error to close the connection.
+    fn process(
+        &mut self,
+        server_name: &str,
+        backend: &SoftBackend,
+        events: &mut Vec<VncEvent>,
+    ) -> io::Result<()> {
+        let invalid = |message: &str| io::Error::new(ErrorKind::InvalidData, message);
+        loop {
+            let input = &self.input[..];
+            let consumed = match self.stage {
+                Stage::Version => {
+                    let Some(version) = input.get(..12) else {
+                        return Ok(());
+                    };
+                    if !version.starts_with(b"RFB 003.") {
+                        return Err(invalid("not an RFB viewer"));
+                    }
+                    let minor: u32 = std::str::from_utf8(&version[8..11])
+                        .ok()
+                        .and_then(|minor| minor.parse().ok())
+                        .ok_or_else(|| invalid("invalid RFB version"))?;
+                    self.minor = minor;
+                    if minor >= 7 {
+                        // One security type, None.
+                        self.output.extend_from_slice(&[1, 1]);
+                        self.stage = Stage::Security;
+                    } else {
+                        // Version 3.3, the server picks None.
+                        self.output.extend_from_slice(&1u32.to_be_bytes());
+                        self.stage = Stage::ClientInit;
+                    }
+                    12
+                }
+                Stage::Security => {
+                    let Some(&security) = input.first() else {
+                        return Ok(());
+                    };
+                    if security != 1 {
+                        return Err(invalid("unsupported security type"));
+                    }
+                    // Only 3.8 reports the result of the None type.
+                    if self.minor >= 8 {
+                        self.output.extend_from_slice(&0u32.to_be_bytes());
+                    }
+                    self.stage = Stage::ClientInit;
+                    1
+                }
+                Stage::ClientInit => {
+                    if input.is_empty() {
+                        return Ok(());
+                    }
+                    // Viewers always share the server, the flag is ignored.
+                    let pixmap = &backend.rgb_pixmap;
+                    self.size = (pixmap.width(), pixmap.height());
+                    self.output
+                        .extend_from_slice(&(pixmap.width() as u16).to_be_bytes());
+                    self.output
+                        .extend_from_slice(&(pixmap.height() as u16).to_be_bytes());
+                    PixelFormat::SERVER.write(&mut self.output);
+                    self.output
+                        .extend_from_slice(&(server_name.len() as u32).to_be_bytes());
+                    self.output.extend_from_slice(server_name.as_bytes());
+                    self.stage = Stage::Normal;
+                    1
+                }
+                Stage::Normal => {
+                    let Some(consumed) = self.message(backend, events)? else {
+                        return Ok(());
+                    };
+                    consumed
+                }
+            };
+            self.input.drain(..consumed);
+        }
+    }
+
+    /// Handles a normal message, returning its length or `None` if it's incomplete.
+    fn message(
+        &mut self,
+        backend: &SoftBackend,
+        events: &mut Vec<VncEvent>,
+    ) -> io::Result<Option<usize>> {
+        let invalid = |message: &str| io::Error::new(ErrorKind::InvalidData, message);
+        let input = &self.input[..];
+        let u16_at = |i: usize| u16::from_be_bytes([input[i], input[i + 1]]);
+        let u32_at =
+            |i: usize| u32::from_be_bytes([input[i], input[i + 1], input[i + 2], input[i + 3]]);
+        let Some(&kind) = input.first() else {
+            return Ok(None);
+        };
+        let len = match kind {
+            0 => 20,
+            2 if input.len() >= 4 => 4 + 4 * u16_at(2) as usize,
+            3 => 10,
+            4 => 8,
+            5 => 6,
+            6 if input.len() >= 8 => {
+                let len = u32_at(4) as usize;
+                if len > MAX_CUT_TEXT {
+                    return Err(invalid("clipboard text too long"));
+                }
+                8 + len
+            }
+            2 | 6 => return Ok(None),
+            _ => return Err(invalid("unknown RFB message")),
+        };
+        if input.len() < len {
+            return Ok(None);
+        }
+        match kind {
+            // SetPixelFormat
+            0 => {
+                let format = PixelFormat::parse(&input[4..20]);
+                if !format.true_colour
+                    || !matches!(format.bits_per_pixel, 8 | 16 | 32)
+                    || format
+                        .shift
+                        .iter()
+                        .any(|&shift| shift >= format.bits_per_pixel)
+                {
+                    return Err(invalid("unsupported pixel format"));
+                }
+                self.format = format;
+                self.damage_all();
+            }
+            // SetEncodings
+            2 => {
+                let encodings: Vec<i32> = (0..u16_at(2) as usize)
+                    .map(|i| u32_at(4 + 4 * i) as i32)
+                    .collect();
+                self.desktop_size = encodings.contains(&ENCODING_DESKTOP_SIZE);
+                #[cfg(feature = "zlib")]
+                {
+                    self.use_zlib = encodings.contains(&ENCODING_ZLIB);
+                    if self.use_zlib && self.zlib.is_none() {
+                        use miniz_oxide::deflate::core::{
+                            CompressorOxide, create_comp_flags_from_zip_params,
+                        };
+                        // A single zlib stream lasts for the whole connection.
+                        let flags = create_comp_flags_from_zip_params(6, 15, 0);
+                        self.zlib = Some(std::boxed::Box::new(CompressorOxide::new(flags)));
+                    }
+                }
+            }
+            // FramebufferUpdateRequest
+            3 => {
+                if input[1] == 0 {
+                    self.add_damage(&[PixelRect {
+                        x: u16_at(2) as usize,
+                        y: u16_at(4) as usize,
+                        width: u16_at(6) as usize,
+                        height: u16_at(8) as usize,
+                    }]);
+                }
+                self.requested = true;
+            }
+            // KeyEvent
+            4 => {
+                let (down, keysym) = (input[1] != 0, u32_at(4));
+                match keysym {
+                    0xffe1 | 0xffe2 => self.modifiers.shift = down,
+                    0xffe3 | 0xffe4 => self.modifiers.ctrl = down,
+                    0xffe7..=0xffea => self.modifiers.alt = down,
+                    _ if down => {
+                        if let Some(code) = keysym_to_key(keysym, self.modifiers.shift) {
+                            events.push(VncEvent::Key(KeyEvent::new(code, self.modifiers)));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            // PointerEvent
+            5 => {
+                let (buttons, x, y) = (input[1], u16_at(2), u16_at(4));
+                self.pointer_event(backend, buttons, x, y, events);
+            }
+            // ClientCutText, ignored.
+            _ => {}
+        }
+        Ok(Some(len))
+    }
+
+    fn pointer_event(
+        &mut self,
+        backend: &SoftBackend,
+        buttons: u8,
+        x: u16,
+        y: u16,
+        events: &mut Vec<VncEvent>,
+    ) {
+        let previous = core::mem::replace(&mut self.buttons, buttons);
+        let Some(hit) = backend.cell_at_pixel(x as f32 + 0.5, y as f32 + 0.5) else {
+            return;
+        };
+        let mut event = |kind| {
+            events.push(VncEvent::Mouse(MouseEvent {
+                kind,
+                column: hit.position.x,
+                row: hit.position.y,
+                modifiers: self.modifiers,
+            }))
+        };
+        let mut clicked = false;
+        for (mask, button) in [
+            (1, MouseButton::Left),
+            (2, MouseButton::Middle),
+            (4, MouseButton::Right),
+        ] {
+            if (buttons ^ previous) & mask != 0 {
+                clicked = true;
+                event(if buttons & mask != 0 {
+                    MouseEventKind::Down(button)
+                } else {
+                    MouseEventKind::Up(button)
+                });
+            }
+        }
+        // Wheel "buttons" are pressed and released for each step.
+        for (mask, kind) in [
+            (8, MouseEventKind::ScrollUp),
+            (16, MouseEventKind::ScrollDown),
+            (32, MouseEventKind::ScrollLeft),
+            (64, MouseEventKind::ScrollRight),
+        ] {
+            if buttons & mask != 0 && previous & mask == 0 {
+                clicked = true;
+                event(kind);
+            }
+        }
+        if !clicked && self.pointer != Some(hit.position) {
+            let held = [
+                (1, MouseButton::Left),
+                (2, MouseButton::Middle),
+                (4, MouseButton::Right),
+            ]
+            .into_iter()
+            .find(|(mask, _)| buttons & mask != 0);
+            event(match held {
+                Some((_, button)) => MouseEventKind::Drag(button),
+                None => MouseEventKind::Moved,
+            });
+        }
+        self.pointer = Some(hit.position);
+    }
+
+    /// Adds changed regions, merged into their bounding box past [`MAX_RECTS`] so that they
+    /// don't pile up while the viewer doesn't ask for updates.
+    fn add_damage(&mut self, rects: &[PixelRect]) {
+        self.damage.extend_from_slice(rects);
+        if self.damage.len() > MAX_RECTS {
+            self.damage = self.damage.drain(..).reduce(union).into_iter().collect();
+        }
+    }
+
+    fn damage_all(&mut self) {
+        self.damage = vec![PixelRect {
+            x: 0,
+            y: 0,
+            width: self.size.0,
+            height: self.size.1,
+        }];
+    }
+
+    /// Queues an update if the viewer asked for one and something changed.
+    fn update(&mut self, pixmap: &RgbPixmap) {
+        if self.stage != Stage::Normal || !self.requested || self.output.len() > MAX_PENDING_OUTPUT
+        {
+            return;
+        }
+        let size = (pixmap.width(), pixmap.height());
+        let resized = size != self.size && self.desktop_size;
+        if resized {
+            self.size = size;
+            self.damage_all();
+        }
+        let (width, height) = (self.size.0.min(size.0), self.size.1.min(size.1));
+        let rects: Vec<PixelRect> = self
+            .damage
+            .drain(..)
+            .filter_map(|rect| clip(rect, width, height))
+            .collect();
+        if rects.is_empty() && !resized {
+            return;
+        }
+        self.requested = false;
+        self.output.extend_from_slice(&[0, 0]);
+        self.output
+            .extend_from_slice(&((rects.len() + resized as usize) as u16).to_be_bytes());
+        if resized {
+            self.rect_header(
+                PixelRect {
+                    x: 0,
+                    y: 0,
+                    width: size.0,
+                    height: size.1,
+                },
+                ENCODING_DESKTOP_SIZE,
+            );
+        }
+        for rect in rects {
+            #[cfg(feature = "zlib")]
+            if let Some(compressor) = self.zlib.as_mut().filter(|_| self.use_zlib) {
+                let mut pixels = Vec::new();
+                self.format.encode(pixmap, rect, &mut pixels);
+                let compressed = compress_sync(compressor, &pixels);
+                self.rect_header(rect, ENCODING_ZLIB);
+                self.output
+                    .extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+                self.output.extend_from_slice(&compressed);
+                continue;
+            }
+            self.rect_header(rect, ENCODING_RAW);
+            self.format.encode(pixmap, rect, &mut self.output);
+        }
+    }
+
+    fn rect_header(&mut self, rect: PixelRect, encoding: i32) {
+        for value in [rect.x, rect.y, rect.width, rect.height] {
+            self.output.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        self.output.extend_from_slice(&encoding.to_be_bytes());
+    }
+
+    /// Reads what the viewer sent, returns false when it disconnected.
+    fn read(&mut self) -> io::Result<bool> {
+        let mut chunk = [0; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Ok(false),
+                Ok(len) => self.input.extend_from_slice(&chunk[..len]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => return Ok(true),
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Writes as much of the queued output as the socket takes.
+    fn write(&mut self) -> io::Result<()> {
+        while !self.output.is_empty() {
+            match self.stream.write(&self.output) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(len) => {
+                    self.output.drain(..len);
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compresses data into the zlib stream of a connection, flushed so the viewer can decompress
+/// all of it.
+#[cfg(feature = "zlib")]
+fn compress_sync(
+    compressor: &mut miniz_oxide::deflate::core::CompressorOxide,
+    mut input: &[u8],
+) -> Vec<u8> {
+    use miniz_oxide::MZFlush;
+    use miniz_oxide::deflate::stream::deflate;
+
+    let mut out = Vec::new();
+    let mut chunk = vec![0; 1 << 16];
+    loop {
+        let result = deflate(compressor, input, &mut chunk, MZFlush::Sync);
+        input = &input[result.bytes_consumed..];
+        out.extend_from_slice(&chunk[..result.bytes_written]);
+        if result.status.is_err() || (input.is_empty() && result.bytes_written < chunk.len()) {
+            return out;
+        }
+    }
+}
+
+/// A VNC server sending the pixmap of a [`SoftBackend`] to any number of viewers, and receiving
+/// their key and pointer input.
+///
+/// Poll [`Self::update`] after drawing each frame. It accepts new viewers, sends each one the
+/// regions that changed since its last update, and returns the input received. Viewers that
+/// ask for the zlib encoding get it when the `zlib` feature is enabled, raw pixels otherwise.
+///
+/// # Examples
+/// ```no_run
+/// use soft_ratatui::{BitmapFont, KeyCode, SoftBackend, VncEvent, VncServer};
+///
+/// let mut server = VncServer::bind("0.0.0.0:5900").unwrap();
+/// let mut backend = SoftBackend::new_with_bitmap_font(80, 25, BitmapFont::FIXED_8X13);
+/// loop {
+///     // Draw the frame into the backend, e.g. through a `Terminal`.
+///     for event in server.update(&mut backend).unwrap() {
+///         if matches!(event, VncEvent::Key(key) if key.code == KeyCode::Char('q')) {
+///             return;
+///         }
+///     }
+///     std::thread::sleep(std::time::Duration::from_millis(16));
+/// }
+/// ```
+pub struct VncServer {
+    listener: TcpListener,
+    clients: Vec<Client>,
+    name: String,
+}
+
+impl VncServer {
+    /// Listens for viewers on an address, e.g. `"0.0.0.0:5900"`.
+    pub fn bind(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            clients: Vec::new(),
+            name: String::from("soft_ratatui"),
+        })
+    }
+
+    /// Returns the address listened on, e.g. to find the port picked for port 0.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Sets the desktop name shown by viewers that connect afterwards.
+    pub fn set_name(&mut self, name: &str) {
+        self.name = String::from(name);
+    }
+
+    /// Returns the number of connected viewers.
+    pub fn viewer_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// Accepts new viewers, exchanges messages with all of them and returns the input they sent.
+    ///
+    /// The dirty regions of the backend are taken and cleared. Viewers that disconnect or
+    /// send invalid messages are dropped.
+    pub fn update(&mut self, backend: &mut SoftBackend) -> io::Result<Vec<VncEvent>> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    stream.set_nonblocking(true)?;
+                    let _ = stream.set_nodelay(true);
+                    self.clients.push(Client {
+                        stream,
+                        stage: Stage::Version,
+                        minor: 8,
+                        input: Vec::new(),
+                        output: b"RFB 003.008\n".to_vec(),
+                        format: PixelFormat::SERVER,
+                        #[cfg(feature = "zlib")]
+                        use_zlib: false,
+                        #[cfg(feature = "zlib")]
+                        zlib: None,
+                        desktop_size: false,
+                        damage: Vec::new(),
+                        requested: false,
+                        size: (0, 0),
+                        buttons: 0,
+                        pointer: None,
+                        modifiers: KeyModifiers::NONE,
+                    });
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        let dirty = backend.dirty_regions();
+        backend.clear_dirty();
+        let mut events = Vec::new();
+        let name = &self.name;
+        self.clients.retain_mut(|client| {
+            if client.stage == Stage::Normal {
+                client.add_damage(&dirty);
+            }
+            let connected = client.read().is_ok_and(|open| open)
+                && client.process(name, backend, &mut events).is_ok();
+            if connected {
+                client.update(&backend.rgb_pixmap);
+            }
+            connected && client.write().is_ok()
+        });
+        Ok(events)
+    }
+}
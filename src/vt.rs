@@ -0,0 +1,1434 @@
+//! A VT100/xterm escape sequence parser, for rendering the output of ordinary command line
+//! programs (`ls --color`, compiler diagnostics, logs, full screen apps) with the same
+//! rasterizer as ratatui apps.
+//!
+//! [`VtScreen`] keeps a ratatui [`Buffer`] up to date with the bytes fed to it, and is drawn
+//! like any other widget.
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::layout::{Position, Rect};
+use ratatui::style::{Color, Modifier};
+use ratatui::widgets::Widget;
+use unicode_width::UnicodeWidthChar;
+
+use crate::input::{KeyCode, KeyEvent};
+
+/// The most parameters kept for a single control sequence, as in xterm.
+const MAX_PARAMS: usize = 32;
+/// The longest OSC string kept, longer ones are truncated.
+const MAX_OSC: usize = 4096;
+
+/// The ANSI colors 0 to 15, in the order of their SGR codes.
+const ANSI_COLORS: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// Returns the ratatui color of a 256 color palette index.
+fn indexed_color(index: u16) -> Color {
+    match index {
+        0..=15 => ANSI_COLORS[index as usize],
+        _ => Color::Indexed(index.min(255) as u8),
+    }
+}
+
+/// Maps the DEC special graphics set, used by curses apps for line drawing, to unicode.
+fn dec_special_graphics(c: char) -> char {
+    #[rustfmt::skip]
+    const TABLE: [char; 32] = [
+        '\u{a0}', '◆', '▒', '␉', '␌', '␍', '␊', '°', '±', '␤', '␋', '┘', '┐', '┌', '└', '┼',
+        '⎺', '⎻', '─', '⎼', '⎽', '├', '┤', '┴', '┬', '│', '≤', '≥', 'π', '≠', '£', '·',
+    ];
+    match c {
+        '_'..='~' => TABLE[c as usize - '_' as usize],
+        _ => c,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Charset {
+    Ascii,
+    DecSpecialGraphics,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    EscapeIntermediate,
+    Csi,
+    CsiIgnore,
+    Osc,
+    /// DCS, SOS, PM and APC strings, which are skipped.
+    IgnoredString,
+}
+
+/// The colors and modifiers given to printed and erased cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pen {
+    fg: Color,
+    bg: Color,
+    modifier: Modifier,
+}
+
+impl Default for Pen {
+    fn default() -> Self {
+        Self {
+            fg: Color::Reset,
+            bg: Color::Reset,
+            modifier: Modifier::empty(),
+        }
+    }
+}
+
+/// The state saved by DECSC and restored by DECRC.
+#[derive(Debug, Clone, Copy)]
+struct SavedCursor {
+    x: u16,
+    y: u16,
+    pen: Pen,
+    charsets: [Charset; 2],
+    active_charset: usize,
+    origin_mode: bool,
+}
+
+/// The main screen while the alternate screen is shown.
+#[derive(Debug, Clone)]
+struct MainScreen {
+    buffer: Buffer,
+    saved_cursor: Option<SavedCursor>,
+}
+
+/// A grid of cells driven by a VT100/xterm escape sequence stream.
+///
+/// Covers the subset of xterm that shells, curses apps and tmux emit: UTF-8 text with wide and
+/// combining chars, SGR colors (16, 256 and true color) and modifiers, cursor movement, erase,
+/// insert and delete, scroll regions, tab stops, the DEC line drawing charset and the alternate
+/// screen. Replies to status queries are collected for [`Self::take_responses`].
+///
+/// # Examples
+/// ```rust
+/// use soft_ratatui::VtScreen;
+///
+/// let mut screen = VtScreen::new(20, 2);
+/// screen.feed(b"\x1b[1;31mred\x1b[0m text\r\nline 2");
+/// let cell = &screen.buffer()[(0, 0)];
+/// assert_eq!(cell.symbol(), "r");
+/// assert_eq!(cell.fg, ratatui::style::Color::Red);
+/// assert_eq!(screen.buffer()[(5, 1)].symbol(), "2");
+/// assert_eq!(screen.cursor(), (6, 1).into());
+/// ```
+///
+/// Render it with [`SoftBackend`](crate::SoftBackend) like any widget:
+/// ```rust
+/// use ratatui::Terminal;
+/// use soft_ratatui::{BitmapFont, SoftBackend, VtScreen};
+///
+/// let mut screen = VtScreen::new(20, 4);
+/// screen.set_newline_mode(true);
+/// screen.feed(b"\x1b[34mdrwxr-xr-x\x1b[0m src\n");
+///
+/// let backend = SoftBackend::new_with_bitmap_font(20, 4, BitmapFont::FIXED_8X13);
+/// let mut terminal = Terminal::new(backend).unwrap();
+/// terminal
+///     .draw(|frame| frame.render_widget(&screen, frame.area()))
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct VtScreen {
+    buffer: Buffer,
+    x: u16,
+    y: u16,
+    /// Set after printing in the last column, the next char wraps to a new line first.
+    wrap_pending: bool,
+    pen: Pen,
+    saved_cursor: Option<SavedCursor>,
+    /// The first and last row of the scroll region, inclusive.
+    scroll_top: u16,
+    scroll_bottom: u16,
+    tab_stops: Vec<bool>,
+    charsets: [Charset; 2],
+    active_charset: usize,
+    main_screen: Option<MainScreen>,
+    /// Rows scrolled off the top of the main screen, oldest first.
+    scrollback: VecDeque<Vec<Cell>>,
+    scrollback_limit: usize,
+    /// How many rows of scrollback the view is moved up by.
+    view_offset: usize,
+
+    autowrap: bool,
+    origin_mode: bool,
+    insert_mode: bool,
+    newline_mode: bool,
+    cursor_visible: bool,
+    application_cursor_keys: bool,
+    bracketed_paste: bool,
+
+    /// The last printed cell, where combining chars are added.
+    last_cell: Option<(u16, u16)>,
+    last_char: Option<char>,
+    title: String,
+    responses: Vec<u8>,
+
+    state: State,
+    params: Vec<u16>,
+    /// Whether each parameter was separated from the previous one by a colon.
+    subparams: Vec<bool>,
+    private_marker: Option<u8>,
+    intermediates: Vec<u8>,
+    osc: Vec<u8>,
+    utf8: [u8; 4],
+    utf8_len: usize,
+    utf8_needed: usize,
+}
+
+impl VtScreen {
+    /// Creates a blank screen of `width` columns and `height` rows.
+    pub fn new(width: u16, height: u16) -> Self {
+        let mut screen = Self {
+            buffer: Buffer::empty(Rect::new(0, 0, width, height)),
+            x: 0,
+            y: 0,
+            wrap_pending: false,
+            pen: Pen::default(),
+            saved_cursor: None,
+            scroll_top: 0,
+            scroll_bottom: height.saturating_sub(1),
+            tab_stops: Vec::new(),
+            charsets: [Charset::Ascii; 2],
+            active_charset: 0,
+            main_screen: None,
+            scrollback: VecDeque::new(),
+            scrollback_limit: 0,
+            view_offset: 0,
+
+            autowrap: true,
+            origin_mode: false,
+            insert_mode: false,
+            newline_mode: false,
+            cursor_visible: true,
+            application_cursor_keys: false,
+            bracketed_paste: false,
+
+            last_cell: None,
+            last_char: None,
+            title: String::new(),
+            responses: Vec::new(),
+
+            state: State::Ground,
+            params: Vec::new(),
+            subparams: Vec::new(),
+            private_marker: None,
+            intermediates: Vec::new(),
+            osc: Vec::new(),
+            utf8: [0; 4],
+            utf8_len: 0,
+            utf8_needed: 0,
+        };
+        screen.reset_tab_stops();
+        screen
+    }
+
+    /// Returns the screen contents.
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+    /// Returns the cursor position.
+    pub fn cursor(&self) -> Position {
+        Position::new(self.x, self.y)
+    }
+    /// Returns whether the cursor is shown (DECTCEM).
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+    /// Returns whether the alternate screen, used by full screen apps, is shown.
+    pub fn alternate_screen(&self) -> bool {
+        self.main_screen.is_some()
+    }
+    /// Returns whether cursor keys should send application sequences (DECCKM).
+    pub fn application_cursor_keys(&self) -> bool {
+        self.application_cursor_keys
+    }
+    /// Returns whether pasted text should be wrapped in bracketed paste markers.
+    pub fn bracketed_paste(&self) -> bool {
+        self.bracketed_paste
+    }
+    /// Returns the window title set with OSC 0 or OSC 2.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Sets how many rows scrolled off the top of the screen are kept. Defaults to 0.
+    ///
+    /// Only the main screen feeds the scrollback, full screen apps on the alternate screen don't.
+    pub fn set_scrollback_limit(&mut self, limit: usize) {
+        self.scrollback_limit = limit;
+        while self.scrollback.len() > limit {
+            self.scrollback.pop_front();
+        }
+        self.view_offset = self.view_offset.min(self.scrollback.len());
+    }
+    /// Returns the number of rows in the scrollback.
+    pub fn scrollback_len(&self) -> usize {
+        self.scrollback.len()
+    }
+    /// Returns how many rows of scrollback the view is moved up by, 0 showing the live screen.
+    pub fn view_offset(&self) -> usize {
+        self.view_offset
+    }
+    /// Moves the view up into the scrollback.
+    pub fn scroll_view_up(&mut self, lines: usize) {
+        self.view_offset = (self.view_offset + lines).min(self.scrollback.len());
+    }
+    /// Moves the view down toward the live screen.
+    pub fn scroll_view_down(&mut self, lines: usize) {
+        self.view_offset = self.view_offset.saturating_sub(lines);
+    }
+
+    /// Sets whether a line feed also returns the cursor to the first column (LNM).
+    ///
+    /// Off by default, as programs on a pseudo terminal emit `\r\n`. Turn it on to render text
+    /// captured from a pipe or a file, where lines end with a bare `\n`.
+    pub fn set_newline_mode(&mut self, enabled: bool) {
+        self.newline_mode = enabled;
+    }
+
+    /// Translates a key press into the bytes a terminal sends for it, following the cursor key
+    /// mode set by the program.
+    pub fn encode_key(&self, key: KeyEvent) -> Vec<u8> {
+        let KeyEvent { code, modifiers } = key;
+        // The xterm modifier parameter, 1 meaning none.
+        let parameter =
+            1 + modifiers.shift as u8 + 2 * modifiers.alt as u8 + 4 * modifiers.ctrl as u8;
+        let mut out = Vec::new();
+        let sequence = |out: &mut Vec<u8>, final_byte: u8, ss3: bool| {
+            if parameter > 1 {
+                let _ = write!(Bytes(out), "\x1b[1;{parameter}{}", final_byte as char);
+            } else if ss3 {
+                out.extend_from_slice(&[0x1b, b'O', final_byte]);
+            } else {
+                out.extend_from_slice(&[0x1b, b'[', final_byte]);
+            }
+        };
+        let tilde = |out: &mut Vec<u8>, number: u8| {
+            if parameter > 1 {
+                let _ = write!(Bytes(out), "\x1b[{number};{parameter}~");
+            } else {
+                let _ = write!(Bytes(out), "\x1b[{number}~");
+            }
+        };
+        let cursor_ss3 = self.application_cursor_keys;
+        match code {
+            KeyCode::Char(c) => {
+                if modifiers.alt {
+                    out.push(0x1b);
+                }
+                match c {
+                    '@' | ' ' | 'a'..='z' | 'A'..='Z' | '['..='_' if modifiers.ctrl => {
+                        out.push(c.to_ascii_uppercase() as u8 & 0x1f);
+                    }
+                    '?' if modifiers.ctrl => out.push(0x7f),
+                    _ => {
+                        let mut utf8 = [0; 4];
+                        out.extend_from_slice(c.encode_utf8(&mut utf8).as_bytes());
+                    }
+                }
+            }
+            KeyCode::Enter => {
+                if modifiers.alt {
+                    out.push(0x1b);
+                }
+                out.push(b'\r');
+            }
+            KeyCode::Backspace => {
+                if modifiers.alt {
+                    out.push(0x1b);
+                }
+                out.push(if modifiers.ctrl { 0x08 } else { 0x7f });
+            }
+            KeyCode::Tab if modifiers.shift => out.extend_from_slice(b"\x1b[Z"),
+            KeyCode::Tab => out.push(b'\t'),
+            KeyCode::BackTab => out.extend_from_slice(b"\x1b[Z"),
+            KeyCode::Esc => out.push(0x1b),
+            KeyCode::Up => sequence(&mut out, b'A', cursor_ss3),
+            KeyCode::Down => sequence(&mut out, b'B', cursor_ss3),
+            KeyCode::Right => sequence(&mut out, b'C', cursor_ss3),
+            KeyCode::Left => sequence(&mut out, b'D', cursor_ss3),
+            KeyCode::Home => sequence(&mut out, b'H', cursor_ss3),
+            KeyCode::End => sequence(&mut out, b'F', cursor_ss3),
+            KeyCode::Insert => tilde(&mut out, 2),
+            KeyCode::Delete => tilde(&mut out, 3),
+            KeyCode::PageUp => tilde(&mut out, 5),
+            KeyCode::PageDown => tilde(&mut out, 6),
+            KeyCode::F(n @ 1..=4) => sequence(&mut out, b'P' + n - 1, true),
+            KeyCode::F(n @ 5..=12) => {
+                const NUMBERS: [u8; 8] = [15, 17, 18, 19, 20, 21, 23, 24];
+                tilde(&mut out, NUMBERS[n as usize - 5]);
+            }
+            KeyCode::F(_) => {}
+        }
+        out
+    }
+
+    /// Returns the bytes to send for pasted text, wrapped in bracketed paste markers when the
+    /// program asked for them.
+    pub fn encode_paste(&self, text: &str) -> Vec<u8> {
+        let mut out = Vec::with_capacity(text.len() + 12);
+        if self.bracketed_paste {
+            out.extend_from_slice(b"\x1b[200~");
+            // Stray end markers would let the pasted text run as input.
+            out.extend_from_slice(text.replace("\x1b[201~", "").as_bytes());
+            out.extend_from_slice(b"\x1b[201~");
+        } else {
+            out.extend_from_slice(text.as_bytes());
+        }
+        out
+    }
+
+    /// Takes the replies to status and cursor position queries, which should be written back to
+    /// the program that sent them.
+    pub fn take_responses(&mut self) -> Vec<u8> {
+        core::mem::take(&mut self.responses)
+    }
+
+    /// Parses a chunk of the output stream. Sequences may be split across chunks.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.advance(byte);
+        }
+    }
+
+    /// Resizes the screen, keeping the top left of its contents. When rows are removed the
+    /// contents scroll up so the cursor row stays visible.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        if height > 0 && self.y >= height {
+            let lines = self.y - height + 1;
+            self.scroll_top = 0;
+            self.scroll_bottom = self.buffer.area.height - 1;
+            self.scroll_up(lines);
+            self.y -= lines;
+        }
+        self.buffer = resized(&self.buffer, width, height);
+        if let Some(main) = &mut self.main_screen {
+            main.buffer = resized(&main.buffer, width, height);
+        }
+        self.x = self.x.min(width.saturating_sub(1));
+        self.y = self.y.min(height.saturating_sub(1));
+        self.wrap_pending = false;
+        self.last_cell = None;
+        self.scroll_top = 0;
+        self.scroll_bottom = height.saturating_sub(1);
+        let old_width = self.tab_stops.len();
+        self.tab_stops.resize(width as usize, false);
+        for x in (old_width..width as usize).filter(|x| x % 8 == 0) {
+            self.tab_stops[x] = true;
+        }
+    }
+
+    fn width(&self) -> u16 {
+        self.buffer.area.width
+    }
+    fn height(&self) -> u16 {
+        self.buffer.area.height
+    }
+
+    fn advance(&mut self, byte: u8) {
+        // CAN and SUB abort any sequence, ESC starts a new one, even inside strings.
+        match byte {
+            0x18 | 0x1a => {
+                self.state = State::Ground;
+                return;
+            }
+            0x1b => {
+                if self.state == State::Osc {
+                    self.osc_dispatch();
+                }
+                self.utf8_needed = 0;
+                self.state = State::Escape;
+                self.intermediates.clear();
+                return;
+            }
+            _ => {}
+        }
+
+        match self.state {
+            State::Ground => self.ground(byte),
+            State::Escape => match byte {
+                0x00..=0x1f => self.execute(byte),
+                b'[' => {
+                    self.params.clear();
+                    self.subparams.clear();
+                    self.private_marker = None;
+                    self.state = State::Csi;
+                }
+                b']' => {
+                    self.osc.clear();
+                    self.state = State::Osc;
+                }
+                b'P' | b'X' | b'^' | b'_' => self.state = State::IgnoredString,
+                0x20..=0x2f => {
+                    self.intermediates.push(byte);
+                    self.state = State::EscapeIntermediate;
+                }
+                _ => {
+                    self.state = State::Ground;
+                    self.esc_dispatch(byte);
+                }
+            },
+            State::EscapeIntermediate => match byte {
+                0x00..=0x1f => self.execute(byte),
+                0x20..=0x2f => self.intermediates.push(byte),
+                _ => {
+                    self.state = State::Ground;
+                    self.esc_dispatch(byte);
+                }
+            },
+            State::Csi => match byte {
+                0x00..=0x1f => self.execute(byte),
+                b'0'..=b'9' => {
+                    if self.params.is_empty() {
+                        self.params.push(0);
+                        self.subparams.push(false);
+                    }
+                    let param = self.params.last_mut().unwrap();
+                    *param = param
+                        .saturating_mul(10)
+                        .saturating_add((byte - b'0') as u16);
+                }
+                b';' | b':' => {
+                    if self.params.is_empty() {
+                        self.params.push(0);
+                        self.subparams.push(false);
+                    }
+                    if self.params.len() < MAX_PARAMS {
+                        self.params.push(0);
+                        self.subparams.push(byte == b':');
+                    }
+                }
+                b'<'..=b'?' => {
+                    if self.params.is_empty() && self.private_marker.is_none() {
+                        self.private_marker = Some(byte);
+                    } else {
+                        self.state = State::CsiIgnore;
+                    }
+                }
+                0x20..=0x2f => self.intermediates.push(byte),
+                0x40..=0x7e => {
+                    self.state = State::Ground;
+                    self.csi_dispatch(byte);
+                }
+                _ => {}
+            },
+            State::CsiIgnore => match byte {
+                0x00..=0x1f => self.execute(byte),
+                0x40..=0x7e => self.state = State::Ground,
+                _ => {}
+            },
+            State::Osc => match byte {
+                0x07 => {
+                    self.osc_dispatch();
+                    self.state = State::Ground;
+                }
+                0x00..=0x1f => {}
+                _ => {
+                    if self.osc.len() < MAX_OSC {
+                        self.osc.push(byte);
+                    }
+                }
+            },
+            State::IgnoredString => {}
+        }
+    }
+
+    /// Decodes UTF-8 text and runs C0 controls.
+    fn ground(&mut self, byte: u8) {
+        if self.utf8_needed > 0 {
+            if byte & 0xc0 == 0x80 {
+                self.utf8[self.utf8_len] = byte;
+                self.utf8_len += 1;
+                if self.utf8_len == self.utf8_needed {
+                    self.utf8_needed = 0;
+                    let c = core::str::from_utf8(&self.utf8[..self.utf8_len])
+                        .ok()
+                        .and_then(|s| s.chars().next())
+                        .unwrap_or(char::REPLACEMENT_CHARACTER);
+                    self.print(c);
+                }
+                return;
+            }
+            // A truncated sequence, the byte starts something new.
+            self.utf8_needed = 0;
+            self.print(char::REPLACEMENT_CHARACTER);
+        }
+
+        match byte {
+            0x00..=0x1f => self.execute(byte),
+            0x20..=0x7e => self.print(byte as char),
+            0x7f => {}
+            0xc2..=0xf4 => {
+                self.utf8[0] = byte;
+                self.utf8_len = 1;
+                self.utf8_needed = match byte {
+                    0xc2..=0xdf => 2,
+                    0xe0..=0xef => 3,
+                    _ => 4,
+                };
+            }
+            _ => self.print(char::REPLACEMENT_CHARACTER),
+        }
+    }
+
+    fn execute(&mut self, byte: u8) {
+        if self.buffer.area.is_empty() {
+            return;
+        }
+        match byte {
+            0x08 => {
+                self.x = self.x.saturating_sub(1);
+                self.wrap_pending = false;
+            }
+            0x09 => self.tab_forward(1),
+            0x0a..=0x0c => {
+                self.index();
+                if self.newline_mode {
+                    self.x = 0;
+                }
+            }
+            0x0d => {
+                self.x = 0;
+                self.wrap_pending = false;
+            }
+            0x0e => self.active_charset = 1,
+            0x0f => self.active_charset = 0,
+            _ => {}
+        }
+    }
+
+    fn print(&mut self, c: char) {
+        let c = match self.charsets[self.active_charset] {
+            Charset::DecSpecialGraphics => dec_special_graphics(c),
+            Charset::Ascii => c,
+        };
+        let Some(char_width) = c.width() else {
+            return;
+        };
+        if self.width() == 0 || self.height() == 0 {
+            return;
+        }
+        if char_width == 0 {
+            if let Some(position) = self.last_cell {
+                let cell = &mut self.buffer[position];
+                let mut symbol = String::from(cell.symbol());
+                symbol.push(c);
+                cell.set_symbol(&symbol);
+            }
+            return;
+        }
+        let char_width = (char_width as u16).min(self.width());
+
+        if self.wrap_pending || self.x + char_width > self.width() {
+            if self.autowrap {
+                self.x = 0;
+                self.index();
+            } else {
+                self.x = self.width() - char_width;
+            }
+            self.wrap_pending = false;
+        }
+        if self.insert_mode {
+            self.insert_blanks(char_width);
+        }
+
+        let pen = self.pen;
+        let cell = &mut self.buffer[(self.x, self.y)];
+        cell.reset();
+        cell.set_char(c);
+        (cell.fg, cell.bg, cell.modifier) = (pen.fg, pen.bg, pen.modifier);
+        for x in self.x + 1..self.x + char_width {
+            self.buffer[(x, self.y)] = self.blank();
+        }
+        self.last_cell = Some((self.x, self.y));
+        self.last_char = Some(c);
+
+        if self.x + char_width >= self.width() {
+            self.x = self.width() - 1;
+            self.wrap_pending = self.autowrap;
+        } else {
+            self.x += char_width;
+        }
+    }
+
+    /// An erased cell, which keeps the current background color as in xterm.
+    fn blank(&self) -> Cell {
+        let mut cell = Cell::EMPTY;
+        cell.bg = self.pen.bg;
+        cell
+    }
+
+    fn row_range(&self, y: u16) -> core::ops::Range<usize> {
+        let width = self.width() as usize;
+        y as usize * width..(y as usize + 1) * width
+    }
+
+    fn erase(&mut self, range: core::ops::Range<usize>) {
+        let blank = self.blank();
+        for cell in &mut self.buffer.content[range] {
+            *cell = blank.clone();
+        }
+    }
+
+    /// Scrolls the scroll region up by `lines`, blanking the rows revealed at its bottom.
+    fn scroll_up(&mut self, lines: u16) {
+        let (top, bottom) = (self.scroll_top, self.scroll_bottom);
+        let lines = lines.min(bottom - top + 1);
+        let start = self.row_range(top).start;
+        let end = self.row_range(bottom).end;
+        let shift = lines as usize * self.width() as usize;
+        if top == 0 && self.main_screen.is_none() && self.scrollback_limit > 0 {
+            for row in self.buffer.content[..shift].chunks(self.width() as usize) {
+                if self.scrollback.len() == self.scrollback_limit {
+                    self.scrollback.pop_front();
+                } else if self.view_offset > 0 {
+                    // Keeps the scrolled view on the same rows.
+                    self.view_offset += 1;
+                }
+                self.scrollback.push_back(row.to_vec());
+            }
+        }
+        self.buffer.content[start..end].rotate_left(shift);
+        self.erase(end - shift..end);
+        self.last_cell = None;
+    }
+
+    /// Scrolls the scroll region down by `lines`, blanking the rows revealed at its top.
+    fn scroll_down(&mut self, lines: u16) {
+        let (top, bottom) = (self.scroll_top, self.scroll_bottom);
+        let lines = lines.min(bottom - top + 1);
+        let start = self.row_range(top).start;
+        let end = self.row_range(bottom).end;
+        let shift = lines as usize * self.width() as usize;
+        self.buffer.content[start..end].rotate_right(shift);
+        self.erase(start..start + shift);
+        self.last_cell = None;
+    }
+
+    /// Moves the cursor down, scrolling at the bottom of the scroll region (IND).
+    fn index(&mut self) {
+        self.wrap_pending = false;
+        if self.y == self.scroll_bottom {
+            self.scroll_up(1);
+        } else if self.y + 1 < self.height() {
+            self.y += 1;
+        }
+    }
+
+    /// Moves the cursor up, scrolling at the top of the scroll region (RI).
+    fn reverse_index(&mut self) {
+        self.wrap_pending = false;
+        if self.y == self.scroll_top {
+            self.scroll_down(1);
+        } else {
+            self.y = self.y.saturating_sub(1);
+        }
+    }
+
+    fn insert_blanks(&mut self, count: u16) {
+        let row = self.row_range(self.y);
+        let start = row.start + self.x as usize;
+        let count = (count as usize).min(row.end - start);
+        self.buffer.content[start..row.end].rotate_right(count);
+        self.erase(start..start + count);
+    }
+
+    fn delete_chars(&mut self, count: u16) {
+        let row = self.row_range(self.y);
+        let start = row.start + self.x as usize;
+        let count = (count as usize).min(row.end - start);
+        self.buffer.content[start..row.end].rotate_left(count);
+        self.erase(row.end - count..row.end);
+    }
+
+    fn reset_tab_stops(&mut self) {
+        self.tab_stops = (0..self.width() as usize).map(|x| x % 8 == 0).collect();
+    }
+
+    fn tab_forward(&mut self, count: u16) {
+        for _ in 0..count {
+            let next = (self.x as usize + 1..self.width() as usize).find(|x| self.tab_stops[*x]);
+            self.x = next.unwrap_or(self.width().saturating_sub(1) as usize) as u16;
+        }
+        self.wrap_pending = false;
+    }
+
+    fn tab_backward(&mut self, count: u16) {
+        for _ in 0..count {
+            let previous = (0..self.x as usize).rev().find(|x| self.tab_stops[*x]);
+            self.x = previous.unwrap_or(0) as u16;
+        }
+        self.wrap_pending = false;
+    }
+
+    /// Moves the cursor to a row and column, relative to the scroll region in origin mode.
+    fn move_to(&mut self, x: u16, y: u16) {
+        let (min_y, max_y) = if self.origin_mode {
+            (self.scroll_top, self.scroll_bottom)
+        } else {
+            (0, self.height().saturating_sub(1))
+        };
+        self.x = x.min(self.width().saturating_sub(1));
+        self.y = min_y.saturating_add(y).min(max_y);
+        self.wrap_pending = false;
+    }
+
+    fn save_cursor(&mut self) {
+        self.saved_cursor = Some(SavedCursor {
+            x: self.x,
+            y: self.y,
+            pen: self.pen,
+            charsets: self.charsets,
+            active_charset: self.active_charset,
+            origin_mode: self.origin_mode,
+        });
+    }
+
+    fn restore_cursor(&mut self) {
+        let saved = self.saved_cursor.unwrap_or(SavedCursor {
+            x: 0,
+            y: 0,
+            pen: Pen::default(),
+            charsets: [Charset::Ascii; 2],
+            active_charset: 0,
+            origin_mode: false,
+        });
+        self.x = saved.x.min(self.width().saturating_sub(1));
+        self.y = saved.y.min(self.height().saturating_sub(1));
+        self.pen = saved.pen;
+        self.charsets = saved.charsets;
+        self.active_charset = saved.active_charset;
+        self.origin_mode = saved.origin_mode;
+        self.wrap_pending = false;
+    }
+
+    fn enter_alternate_screen(&mut self, save_cursor: bool) {
+        if self.main_screen.is_some() {
+            return;
+        }
+        if save_cursor {
+            self.save_cursor();
+        }
+        let blank = Buffer::empty(self.buffer.area);
+        self.main_screen = Some(MainScreen {
+            buffer: core::mem::replace(&mut self.buffer, blank),
+            saved_cursor: self.saved_cursor,
+        });
+        self.last_cell = None;
+    }
+
+    fn leave_alternate_screen(&mut self, restore_cursor: bool) {
+        if let Some(main) = self.main_screen.take() {
+            self.buffer = main.buffer;
+            self.saved_cursor = main.saved_cursor;
+            if restore_cursor {
+                self.restore_cursor();
+            }
+            self.last_cell = None;
+        }
+    }
+
+    /// Resets the terminal to its initial state (RIS), keeping its size.
+    fn full_reset(&mut self) {
+        let mut screen = Self::new(self.width(), self.height());
+        screen.responses = core::mem::take(&mut self.responses);
+        screen.scrollback = core::mem::take(&mut self.scrollback);
+        screen.scrollback_limit = self.scrollback_limit;
+        *self = screen;
+    }
+
+    fn esc_dispatch(&mut self, byte: u8) {
+        if self.buffer.area.is_empty() {
+            return;
+        }
+        match (self.intermediates.first().copied(), byte) {
+            (None, b'7') => self.save_cursor(),
+            (None, b'8') => self.restore_cursor(),
+            (None, b'D') => self.index(),
+            (None, b'E') => {
+                self.index();
+                self.x = 0;
+            }
+            (None, b'H') => {
+                if let Some(stop) = self.tab_stops.get_mut(self.x as usize) {
+                    *stop = true;
+                }
+            }
+            (None, b'M') => self.reverse_index(),
+            (None, b'c') => self.full_reset(),
+            (Some(b'('), set) | (Some(b')'), set) => {
+                let g = (self.intermediates[0] == b')') as usize;
+                self.charsets[g] = match set {
+                    b'0' => Charset::DecSpecialGraphics,
+                    _ => Charset::Ascii,
+                };
+            }
+            (Some(b'#'), b'8') => {
+                // DECALN, the screen alignment test.
+                for cell in &mut self.buffer.content {
+                    cell.reset();
+                    cell.set_char('E');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns parameter `index`, or `default` if it is missing or zero.
+    fn param(&self, index: usize, default: u16) -> u16 {
+        match self.params.get(index) {
+            Some(0) | None => default,
+            Some(value) => *value,
+        }
+    }
+
+    fn csi_dispatch(&mut self, byte: u8) {
+        if self.buffer.area.is_empty() {
+            return;
+        }
+        let intermediate = self.intermediates.first().copied();
+        let n = self.param(0, 1);
+        match (self.private_marker, intermediate, byte) {
+            (None, None, b'@') => self.insert_blanks(n),
+            (None, None, b'A') => {
+                let top = if self.y >= self.scroll_top {
+                    self.scroll_top
+                } else {
+                    0
+                };
+                self.y = self.y.saturating_sub(n).max(top);
+                self.wrap_pending = false;
+            }
+            (None, None, b'B') | (None, None, b'e') => {
+                let bottom = if self.y <= self.scroll_bottom {
+                    self.scroll_bottom
+                } else {
+                    self.height() - 1
+                };
+                self.y = self.y.saturating_add(n).min(bottom);
+                self.wrap_pending = false;
+            }
+            (None, None, b'C') | (None, None, b'a') => {
+                self.x = self.x.saturating_add(n).min(self.width() - 1);
+                self.wrap_pending = false;
+            }
+            (None, None, b'D') => {
+                self.x = self.x.saturating_sub(n);
+                self.wrap_pending = false;
+            }
+            (None, None, b'E') => {
+                self.csi_dispatch(b'B');
+                self.x = 0;
+            }
+            (None, None, b'F') => {
+                self.csi_dispatch(b'A');
+                self.x = 0;
+            }
+            (None, None, b'G') | (None, None, b'`') => {
+                self.x = (n - 1).min(self.width() - 1);
+                self.wrap_pending = false;
+            }
+            (None, None, b'H') | (None, None, b'f') => {
+                let row = self.param(0, 1) - 1;
+                let column = self.param(1, 1) - 1;
+                self.move_to(column, row);
+            }
+            (None, None, b'I') => self.tab_forward(n),
+            (None, None, b'Z') => self.tab_backward(n),
+            (None, None, b'J') | (Some(b'?'), None, b'J') => {
+                let cursor = self.row_range(self.y).start + self.x as usize;
+                match self.param(0, 0) {
+                    0 => self.erase(cursor..self.buffer.content.len()),
+                    1 => self.erase(0..cursor + 1),
+                    2 => self.erase(0..self.buffer.content.len()),
+                    // Only the scrollback, as in xterm: `clear` sends `CSI 2 J` then `CSI 3 J`.
+                    3 => {
+                        self.scrollback.clear();
+                        self.view_offset = 0;
+                    }
+                    _ => {}
+                }
+            }
+            (None, None, b'K') | (Some(b'?'), None, b'K') => {
+                let row = self.row_range(self.y);
+                let cursor = row.start + self.x as usize;
+                match self.param(0, 0) {
+                    0 => self.erase(cursor..row.end),
+                    1 => self.erase(row.start..cursor + 1),
+                    2 => self.erase(row),
+                    _ => {}
+                }
+            }
+            (None, None, b'L') | (None, None, b'M')
+                if (self.scroll_top..=self.scroll_bottom).contains(&self.y) =>
+            {
+                let top = self.scroll_top;
+                self.scroll_top = self.y;
+                if byte == b'L' {
+                    self.scroll_down(n);
+                } else {
+                    self.scroll_up(n);
+                }
+                self.scroll_top = top;
+                self.x = 0;
+                self.wrap_pending = false;
+            }
+            (None, None, b'P') => self.delete_chars(n),
+            (None, None, b'S') => self.scroll_up(n),
+            (None, None, b'T') => self.scroll_down(n),
+            (None, None, b'X') => {
+                let row = self.row_range(self.y);
+                let cursor = row.start + self.x as usize;
+                self.erase(cursor..(cursor + n as usize).min(row.end));
+            }
+            (None, None, b'b') => {
+                if let Some(c) = self.last_char {
+                    for _ in 0..n {
+                        self.print(c);
+                    }
+                }
+            }
+            (None, None, b'c') => self.responses.extend_from_slice(b"\x1b[?62;22c"),
+            (Some(b'>'), None, b'c') => self.responses.extend_from_slice(b"\x1b[>1;10;0c"),
+            (None, None, b'd') => {
+                let row = n - 1;
+                let column = self.x;
+                self.move_to(column, row);
+            }
+            (None, None, b'g') => match self.param(0, 0) {
+                0 => {
+                    if let Some(stop) = self.tab_stops.get_mut(self.x as usize) {
+                        *stop = false;
+                    }
+                }
+                3 => self.tab_stops.fill(false),
+                _ => {}
+            },
+            (marker, None, b'h') | (marker, None, b'l') => {
+                let enabled = byte == b'h';
+                for index in 0..self.params.len() {
+                    self.set_mode(marker == Some(b'?'), self.params[index], enabled);
+                }
+            }
+            (None, None, b'm') => self.select_graphic_rendition(),
+            (None, None, b'n') => match self.param(0, 0) {
+                5 => self.responses.extend_from_slice(b"\x1b[0n"),
+                6 => {
+                    let row = if self.origin_mode {
+                        // The cursor may sit above a region set after it was saved.
+                        self.y.saturating_sub(self.scroll_top)
+                    } else {
+                        self.y
+                    };
+                    let mut reply = String::new();
+                    let _ = write!(reply, "\x1b[{};{}R", row + 1, self.x + 1);
+                    self.responses.extend_from_slice(reply.as_bytes());
+                }
+                _ => {}
+            },
+            (None, None, b'r') => {
+                let top = self.param(0, 1) - 1;
+                let bottom = self.param(1, self.height()).min(self.height()) - 1;
+                if top < bottom {
+                    self.scroll_top = top;
+                    self.scroll_bottom = bottom;
+                    self.move_to(0, 0);
+                }
+            }
+            (None, None, b's') => self.save_cursor(),
+            (None, None, b'u') => self.restore_cursor(),
+            (None, Some(b'!'), b'p') => {
+                // DECSTR, a soft reset that keeps the screen contents.
+                self.pen = Pen::default();
+                self.scroll_top = 0;
+                self.scroll_bottom = self.height().saturating_sub(1);
+                self.autowrap = true;
+                self.origin_mode = false;
+                self.insert_mode = false;
+                self.cursor_visible = true;
+                self.application_cursor_keys = false;
+                self.charsets = [Charset::Ascii; 2];
+                self.active_charset = 0;
+                self.saved_cursor = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn set_mode(&mut self, private: bool, mode: u16, enabled: bool) {
+        match (private, mode) {
+            (false, 4) => self.insert_mode = enabled,
+            (false, 20) => self.newline_mode = enabled,
+            (true, 1) => self.application_cursor_keys = enabled,
+            (true, 6) => {
+                self.origin_mode = enabled;
+                self.move_to(0, 0);
+            }
+            (true, 7) => self.autowrap = enabled,
+            (true, 25) => self.cursor_visible = enabled,
+            (true, 47) | (true, 1047) | (true, 1049) => {
+                if enabled {
+                    self.enter_alternate_screen(mode == 1049);
+                    if mode != 47 {
+                        self.erase(0..self.buffer.content.len());
+                    }
+                } else {
+                    self.leave_alternate_screen(mode == 1049);
+                }
+            }
+            (true, 1048) => {
+                if enabled {
+                    self.save_cursor();
+                } else {
+                    self.restore_cursor();
+                }
+            }
+            (true, 2004) => self.bracketed_paste = enabled,
+            _ => {}
+        }
+    }
+
+    /// Applies SGR parameters, in both the `38;2;r;g;b` and the `38:2::r:g:b` forms.
+    fn select_graphic_rendition(&mut self) {
+        if self.params.is_empty() {
+            self.pen = Pen::default();
+            return;
+        }
+        let mut index = 0;
+        while index < self.params.len() {
+            // A parameter and its colon separated sub-parameters.
+            let group_end = (index + 1..self.params.len())
+                .find(|i| !self.subparams[*i])
+                .unwrap_or(self.params.len());
+            let group = &self.params[index..group_end];
+            let mut next = group_end;
+
+            let pen = &mut self.pen;
+            match group[0] {
+                0 => *pen = Pen::default(),
+                1 => pen.modifier.insert(Modifier::BOLD),
+                2 => pen.modifier.insert(Modifier::DIM),
+                3 => pen.modifier.insert(Modifier::ITALIC),
+                4 => match group.get(1) {
+                    Some(0) => pen.modifier.remove(Modifier::UNDERLINED),
+                    _ => pen.modifier.insert(Modifier::UNDERLINED),
+                },
+                5 => pen.modifier.insert(Modifier::SLOW_BLINK),
+                6 => pen.modifier.insert(Modifier::RAPID_BLINK),
+                7 => pen.modifier.insert(Modifier::REVERSED),
+                8 => pen.modifier.insert(Modifier::HIDDEN),
+                9 => pen.modifier.insert(Modifier::CROSSED_OUT),
+                21 => pen.modifier.insert(Modifier::UNDERLINED),
+                22 => pen.modifier.remove(Modifier::BOLD | Modifier::DIM),
+                23 => pen.modifier.remove(Modifier::ITALIC),
+                24 => pen.modifier.remove(Modifier::UNDERLINED),
+                25 => pen
+                    .modifier
+                    .remove(Modifier::SLOW_BLINK | Modifier::RAPID_BLINK),
+                27 => pen.modifier.remove(Modifier::REVERSED),
+                28 => pen.modifier.remove(Modifier::HIDDEN),
+                29 => pen.modifier.remove(Modifier::CROSSED_OUT),
+                code @ 30..=37 => pen.fg = ANSI_COLORS[code as usize - 30],
+                code @ 40..=47 => pen.bg = ANSI_COLORS[code as usize - 40],
+                code @ 90..=97 => pen.fg = ANSI_COLORS[code as usize - 90 + 8],
+                code @ 100..=107 => pen.bg = ANSI_COLORS[code as usize - 100 + 8],
+                39 => pen.fg = Color::Reset,
+                49 => pen.bg = Color::Reset,
+                code @ (38 | 48 | 58) => {
+                    let color = if group.len() > 1 {
+                        extended_color(&group[1..])
+                    } else {
+                        let (color, used) = extended_color_semicolon(&self.params[index + 1..]);
+                        next = index + 1 + used;
+                        color
+                    };
+                    let pen = &mut self.pen;
+                    match (code, color) {
+                        (38, Some(color)) => pen.fg = color,
+                        (48, Some(color)) => pen.bg = color,
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+            index = next;
+        }
+    }
+
+    fn osc_dispatch(&mut self) {
+        let text = String::from_utf8_lossy(&self.osc);
+        if let Some((command, value)) = text.split_once(';')
+            && matches!(command, "0" | "2")
+        {
+            self.title = String::from(value);
+        }
+        self.osc.clear();
+    }
+}
+
+/// Parses the color of a colon separated `38:5:n`, `38:2:r:g:b` or `38:2::r:g:b` group,
+/// without its leading code.
+fn extended_color(params: &[u16]) -> Option<Color> {
+    match params {
+        [5, index, ..] => Some(indexed_color(*index)),
+        [2, _, r, g, b, ..] => Some(rgb_color(*r, *g, *b)),
+        [2, r, g, b, ..] => Some(rgb_color(*r, *g, *b)),
+        _ => None,
+    }
+}
+
+/// Parses the color of a semicolon separated `38;5;n` or `38;2;r;g;b` sequence, returning it
+/// with the number of parameters it used.
+fn extended_color_semicolon(params: &[u16]) -> (Option<Color>, usize) {
+    match params {
+        [5, index, ..] => (Some(indexed_color(*index)), 2),
+        [2, r, g, b, ..] => (Some(rgb_color(*r, *g, *b)), 4),
+        [5] | [2, ..] => (None, params.len()),
+        _ => (None, 0),
+    }
+}
+
+fn rgb_color(r: u16, g: u16, b: u16) -> Color {
+    Color::Rgb(r.min(255) as u8, g.min(255) as u8, b.min(255) as u8)
+}
+
+/// Lets `write!` format into a byte vector.
+struct Bytes<'a>(&'a mut Vec<u8>);
+
+impl Write for Bytes<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Copies the top left of `buffer` into a buffer of the new size.
+fn resized(buffer: &Buffer, width: u16, height: u16) -> Buffer {
+    let mut resized = Buffer::empty(Rect::new(0, 0, width, height));
+    for y in 0..height.min(buffer.area.height) {
+        for x in 0..width.min(buffer.area.width) {
+            resized[(x, y)] = buffer[(x, y)].clone();
+        }
+    }
+    resized
+}
+
+/// Copies the screen, scrolled by the view offset, into the area, clipped to its size.
+impl Widget for &VtScreen {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.intersection(buf.area);
+        let first_row = self.scrollback.len() - self.view_offset;
+        for y in 0..area.height.min(self.height()) {
+            let row = first_row + y as usize;
+            let cells = match self.scrollback.get(row) {
+                Some(line) => &line[..],
+                None => {
+                    let range = self.row_range((row - self.scrollback.len()) as u16);
+                    &self.buffer.content[range]
+                }
+            };
+            for x in 0..area.width {
+                buf[(area.x + x, area.y + y)] = match cells.get(x as usize) {
+                    Some(cell) => cell.clone(),
+                    None => Cell::EMPTY,
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(width: u16, height: u16, bytes: &[u8]) -> VtScreen {
+        let mut screen = VtScreen::new(width, height);
+        screen.feed(bytes);
+        screen
+    }
+
+    /// Returns the symbols of a row without trailing blanks.
+    fn row(screen: &VtScreen, y: u16) -> String {
+        let mut row: String = (0..screen.width())
+            .map(|x| screen.buffer()[(x, y)].symbol())
+            .collect();
+        row.truncate(row.trim_end().len());
+        row
+    }
+
+    fn cell(screen: &VtScreen, x: u16, y: u16) -> &Cell {
+        &screen.buffer()[(x, y)]
+    }
+
+    #[test]
+    fn wraps_at_the_last_column() {
+        let screen = screen(5, 3, b"abcdefg");
+        assert_eq!(row(&screen, 0), "abcde");
+        assert_eq!(row(&screen, 1), "fg");
+        assert_eq!(screen.cursor(), Position::new(2, 1));
+    }
+
+    #[test]
+    fn wrap_is_pending_until_the_next_char() {
+        // Filling the row keeps the cursor on it, so a CR LF doesn't leave a blank row.
+        let screen = screen(5, 3, b"abcde\r\nf");
+        assert_eq!(row(&screen, 0), "abcde");
+        assert_eq!(row(&screen, 1), "f");
+        assert_eq!(screen.cursor(), Position::new(1, 1));
+    }
+
+    #[test]
+    fn overwrites_the_last_column_without_autowrap() {
+        let screen = screen(5, 2, b"\x1b[?7labcdefg");
+        assert_eq!(row(&screen, 0), "abcdg");
+        assert_eq!(row(&screen, 1), "");
+    }
+
+    #[test]
+    fn scrolls_only_the_scroll_region() {
+        let mut screen = screen(10, 5, b"0\r\n1\r\n2\r\n3\r\n4");
+        screen.feed(b"\x1b[2;4r\x1b[4;1H\n");
+        let rows: Vec<String> = (0..5).map(|y| row(&screen, y)).collect();
+        assert_eq!(rows, ["0", "2", "3", "", "4"]);
+
+        // Reverse index at the top of the region scrolls it down.
+        screen.feed(b"\x1b[2;1H\x1bM");
+        let rows: Vec<String> = (0..5).map(|y| row(&screen, y)).collect();
+        assert_eq!(rows, ["0", "", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn inserts_and_deletes_lines_inside_the_scroll_region() {
+        let mut screen = screen(10, 5, b"0\r\n1\r\n2\r\n3\r\n4\x1b[1;4r\x1b[2;1H\x1b[L");
+        let rows: Vec<String> = (0..5).map(|y| row(&screen, y)).collect();
+        assert_eq!(rows, ["0", "", "1", "2", "4"]);
+        screen.feed(b"\x1b[2M");
+        let rows: Vec<String> = (0..5).map(|y| row(&screen, y)).collect();
+        assert_eq!(rows, ["0", "2", "", "", "4"]);
+    }
+
+    #[test]
+    fn wide_chars_take_two_cells() {
+        let screen = screen(10, 2, "a中b".as_bytes());
+        assert_eq!(cell(&screen, 1, 0).symbol(), "中");
+        assert_eq!(cell(&screen, 2, 0).symbol(), " ");
+        assert_eq!(cell(&screen, 3, 0).symbol(), "b");
+        assert_eq!(screen.cursor(), Position::new(4, 0));
+    }
+
+    #[test]
+    fn wide_chars_wrap_when_one_cell_is_left() {
+        let screen = screen(3, 2, "ab中".as_bytes());
+        assert_eq!(row(&screen, 0), "ab");
+        assert_eq!(cell(&screen, 0, 1).symbol(), "中");
+        assert_eq!(screen.cursor(), Position::new(2, 1));
+    }
+
+    #[test]
+    fn combining_chars_join_the_previous_cell() {
+        let screen = screen(5, 1, "e\u{301}x".as_bytes());
+        assert_eq!(cell(&screen, 0, 0).symbol(), "e\u{301}");
+        assert_eq!(cell(&screen, 1, 0).symbol(), "x");
+        assert_eq!(screen.cursor(), Position::new(2, 0));
+    }
+
+    #[test]
+    fn sgr_colon_parameters() {
+        let screen = screen(
+            5,
+            1,
+            b"\x1b[38:2::10:20:30;48:5:196ma\x1b[38:2:1:2:3;4:3mb\x1b[4:0mc",
+        );
+        assert_eq!(cell(&screen, 0, 0).fg, Color::Rgb(10, 20, 30));
+        assert_eq!(cell(&screen, 0, 0).bg, Color::Indexed(196));
+        assert_eq!(cell(&screen, 1, 0).fg, Color::Rgb(1, 2, 3));
+        assert!(cell(&screen, 1, 0).modifier.contains(Modifier::UNDERLINED));
+        assert!(!cell(&screen, 2, 0).modifier.contains(Modifier::UNDERLINED));
+    }
+
+    #[test]
+    fn sgr_semicolon_colors_consume_their_parameters() {
+        let screen = screen(5, 1, b"\x1b[38;2;1;2;3;1;48;5;2ma");
+        let cell = cell(&screen, 0, 0);
+        assert_eq!(cell.fg, Color::Rgb(1, 2, 3));
+        assert_eq!(cell.bg, Color::Green);
+        assert!(cell.modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn utf8_split_across_chunks() {
+        let mut screen = VtScreen::new(5, 1);
+        let text = "é🦀".as_bytes();
+        for byte in text {
+            screen.feed(&[*byte]);
+        }
+        assert_eq!(cell(&screen, 0, 0).symbol(), "é");
+        assert_eq!(cell(&screen, 1, 0).symbol(), "🦀");
+    }
+
+    #[test]
+    fn truncated_utf8_is_replaced() {
+        let screen = screen(5, 1, b"\xc3a\xff");
+        assert_eq!(row(&screen, 0), "\u{fffd}a\u{fffd}");
+    }
+
+    #[test]
+    fn erase_saved_lines_keeps_the_screen() {
+        let mut screen = VtScreen::new(5, 2);
+        screen.set_scrollback_limit(10);
+        screen.feed(b"1\r\n2\r\n3\x1b[3J");
+        assert_eq!(screen.scrollback_len(), 0);
+        assert_eq!((row(&screen, 0), row(&screen, 1)), ("2".into(), "3".into()));
+
+        // What `clear` sends.
+        screen.feed(b"\r\n4\x1b[H\x1b[2J\x1b[3J");
+        assert_eq!(screen.scrollback_len(), 0);
+        assert_eq!((row(&screen, 0), row(&screen, 1)), ("".into(), "".into()));
+    }
+
+    #[test]
+    fn escape_sequences_split_across_chunks() {
+        let mut screen = VtScreen::new(5, 1);
+        screen.feed(b"\x1b");
+        screen.feed(b"[3");
+        screen.feed(b"1mX");
+        assert_eq!(cell(&screen, 0, 0).fg, Color::Red);
+        assert_eq!(row(&screen, 0), "X");
+    }
+    #[test]
+    fn cursor_report_above_the_scroll_region_in_origin_mode() {
+        // The cursor is saved at the top of one region and restored above the next one.
+        let mut screen = screen(
+            20,
+            30,
+            b"\x1b[5;25r\x1b[?6h\x1b7\x1b[r\x1b[10;20r\x1b8\x1b[6n",
+        );
+        assert_eq!(screen.take_responses(), b"\x1b[1;1R");
+    }
+}
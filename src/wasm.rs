@@ -0,0 +1,627 @@
+//! A wasm-bindgen wrapper for web pages, drawing a terminal into RGBA pixels for an HTML canvas
+//! and converting DOM input events to terminal input.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::{Deref, DerefMut};
+
+use js_sys::{Array, Object, Reflect, Uint8ClampedArray};
+use ratatui::Terminal;
+use wasm_bindgen::JsValue;
+use wasm_bindgen::prelude::{JsError, wasm_bindgen};
+
+use crate::bitmap_font::BitmapFont;
+use crate::input::{
+    InputEvent, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use crate::pixmap::PixelRect;
+use crate::soft_backend::SoftBackend;
+use crate::vt::VtScreen;
+
+/// The number of dirty rectangles kept before they are merged into the whole pixmap.
+const MAX_DIRTY_RECTS: usize = 64;
+
+/// A terminal for a web page, exported to JavaScript as `WasmTerminal`.
+///
+/// JavaScript feeds it ANSI text, or a Rust app draws into the terminal it dereferences to, then
+/// `draw` copies the redrawn cells into the RGBA pixels that `pixels` views without copying, for
+/// `putImageData` on a canvas, with the rectangles redrawn since the last frame from
+/// `takeDirtyRects`.
+///
+/// DOM keyboard and mouse events passed to it are collected, for `takeEvents` in JavaScript or
+/// [`Self::take_events`] in Rust, or as the bytes a program reads from its terminal for
+/// `takeInput`. Their methods return whether the event was one for the terminal, for
+/// `preventDefault`.
+///
+/// ```js
+/// const terminal = WasmTerminal.withBitmapFont(80, 24);
+/// terminal.feed(new TextEncoder().encode("\x1b[1;32mHello\x1b[0m"));
+/// terminal.draw();
+/// canvas.width = terminal.width();
+/// canvas.height = terminal.height();
+/// const image = new ImageData(terminal.pixels(), terminal.width());
+/// const rects = terminal.takeDirtyRects();
+/// for (let i = 0; i < rects.length; i += 4) {
+///     context.putImageData(image, 0, 0, rects[i], rects[i + 1], rects[i + 2], rects[i + 3]);
+/// }
+/// ```
+#[wasm_bindgen]
+pub struct WasmTerminal {
+    terminal: Terminal<SoftBackend>,
+    screen: VtScreen,
+    /// Whether text was fed to the screen since the last draw.
+    screen_changed: bool,
+    rgba: Vec<u8>,
+    /// The regions of the pixels redrawn since the last `takeDirtyRects`.
+    dirty: Vec<PixelRect>,
+    /// The cell under the mouse, to report moves once per cell.
+    mouse_cell: Option<(u16, u16)>,
+    events: Vec<InputEvent>,
+}
+
+#[wasm_bindgen]
+impl WasmTerminal {
+    /// Creates a terminal of `cols` by `rows` cells drawn with a TTF/OTF font of `font_size`
+    /// pixels. Throws if the font can't be loaded.
+    #[wasm_bindgen(constructor)]
+    pub fn new(font: &[u8], font_size: i32, cols: u16, rows: u16) -> Result<Self, JsError> {
+        let backend = SoftBackend::try_new_with_font(cols, rows, font_size, font)
+            .ok_or_else(|| JsError::new("invalid font data or size"))?;
+        Ok(Self::with_backend(backend))
+    }
+
+    /// Creates a terminal of `cols` by `rows` cells drawn with the built-in 8x13 bitmap font.
+    #[wasm_bindgen(js_name = withBitmapFont)]
+    pub fn with_bitmap_font(cols: u16, rows: u16) -> Self {
+        Self::with_backend(SoftBackend::new_with_bitmap_font(
+            cols,
+            rows,
+            BitmapFont::FIXED_8X13,
+        ))
+    }
+
+    /// Parses ANSI text, e.g. the output of a program, into a screen that replaces the cells at
+    /// the next draw. Sequences may be split across calls.
+    pub fn feed(&mut self, data: &[u8]) {
+        self.screen.feed(data);
+        self.screen_changed = true;
+    }
+
+    /// Draws the text fed since the last draw, and copies the cells redrawn since the last draw
+    /// into the pixels.
+    pub fn draw(&mut self) {
+        if self.screen_changed {
+            let screen = &self.screen;
+            self.terminal
+                .draw(|frame| {
+                    frame.render_widget(screen, frame.area());
+                    if screen.cursor_visible() {
+                        frame.set_cursor_position(screen.cursor());
+                    }
+                })
+                .unwrap_or_else(|error| match error {});
+            self.screen_changed = false;
+        }
+        let backend = self.terminal.backend_mut();
+        if self.rgba.len() == backend.get_pixmap_width() * backend.get_pixmap_height() * 4 {
+            let regions = backend.update_rgba(&mut self.rgba);
+            self.dirty.extend_from_slice(&regions);
+            if self.dirty.len() > MAX_DIRTY_RECTS {
+                self.dirty = vec![backend.rgb_pixmap.bounds()];
+            }
+        } else {
+            // Resized.
+            self.rgba = backend.get_pixmap_data_as_rgba();
+            self.dirty = vec![backend.rgb_pixmap.bounds()];
+            backend.clear_dirty();
+        }
+    }
+
+    /// Resizes the grid to `cols` by `rows` cells. The pixels are resized at the next draw.
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        self.terminal.backend_mut().resize(cols, rows);
+        self.screen.resize(cols, rows);
+    }
+
+    /// Resizes the grid to as many cells as fit in `width` by `height` pixels, e.g. the size of
+    /// the canvas element in device pixels. Returns whether the grid changed.
+    pub fn fit(&mut self, width: u32, height: u32) -> bool {
+        let backend = self.terminal.backend_mut();
+        if !backend.fit_to_pixels(width as usize, height as usize) {
+            return false;
+        }
+        let area = backend.buffer.area;
+        self.screen.resize(area.width, area.height);
+        true
+    }
+
+    /// Returns the width of the pixels.
+    pub fn width(&self) -> u32 {
+        self.terminal.backend().get_pixmap_width() as u32
+    }
+
+    /// Returns the height of the pixels.
+    pub fn height(&self) -> u32 {
+        self.terminal.backend().get_pixmap_height() as u32
+    }
+
+    /// Returns a view of the RGBA pixels as of the last draw, for `new ImageData(pixels, width)`,
+    /// without copying them.
+    ///
+    /// The view is only valid until the next call to the terminal, which may move the pixels or
+    /// grow the WebAssembly memory, so take a new one for every frame instead of keeping it.
+    pub fn pixels(&self) -> Uint8ClampedArray {
+        // SAFETY: The view is documented to be dropped before the next call, which is the
+        // first thing that could move or free the pixels.
+        unsafe { Uint8ClampedArray::view(&self.rgba) }
+    }
+
+    /// Returns the rectangles of the pixels redrawn since the last call, as `x`, `y`, `width`
+    /// and `height` of each, and forgets them.
+    #[wasm_bindgen(js_name = takeDirtyRects)]
+    pub fn take_dirty_rects(&mut self) -> Vec<u32> {
+        self.dirty
+            .drain(..)
+            .flat_map(|rect| [rect.x, rect.y, rect.width, rect.height])
+            .map(|value| value as u32)
+            .collect()
+    }
+
+    /// Returns the input received since the last call, in order, as objects with a `type` of
+    /// `"key"`, `"down"`, `"up"`, `"drag"`, `"move"`, `"scrollUp"`, `"scrollDown"`,
+    /// `"scrollLeft"` or `"scrollRight"`, and `shift`, `ctrl` and `alt`. Keys have the DOM `key`
+    /// name of their code, mouse events a `column` and `row`, and a DOM `button` number for
+    /// `"down"`, `"up"` and `"drag"`.
+    #[wasm_bindgen(js_name = takeEvents)]
+    pub fn take_event_objects(&mut self) -> Array {
+        self.take_events()
+            .into_iter()
+            .map(|event| {
+                let object = Object::new();
+                for (name, value) in event_fields(event) {
+                    let value = match value {
+                        Field::Str(value) => JsValue::from_str(&value),
+                        Field::Number(value) => JsValue::from(value),
+                        Field::Bool(value) => JsValue::from_bool(value),
+                    };
+                    // Setting a property on a plain object can't fail.
+                    let _ = Reflect::set(&object, &JsValue::from_str(name), &value);
+                }
+                JsValue::from(object)
+            })
+            .collect()
+    }
+
+    /// Returns the replies to the queries of the fed program and the keys received since the
+    /// last call, encoded as the program reads them from its terminal, for hosts that feed the
+    /// output of a program, e.g. from a pseudo terminal behind a WebSocket. Mouse input is
+    /// dropped.
+    #[wasm_bindgen(js_name = takeInput)]
+    pub fn take_input(&mut self) -> Vec<u8> {
+        let mut input = self.screen.take_responses();
+        for event in self.take_events() {
+            if let InputEvent::Key(key) = event {
+                input.extend_from_slice(&self.screen.encode_key(key));
+            }
+        }
+        input
+    }
+
+    /// Converts a `keydown` event, from its `key`, `shiftKey`, `ctrlKey` and `altKey`, see
+    /// [`KeyEvent::from_dom`].
+    #[wasm_bindgen(js_name = keyDown)]
+    pub fn key_down(&mut self, key: &str, shift: bool, ctrl: bool, alt: bool) -> bool {
+        let Some(event) = KeyEvent::from_dom(key, shift, ctrl, alt) else {
+            return false;
+        };
+        self.events.push(InputEvent::Key(event));
+        true
+    }
+
+    /// Converts a `mousedown` event, from its `button`, its position in pixels of the pixmap,
+    /// i.e. `offsetX` and `offsetY` scaled by the canvas size over its client size, and its
+    /// modifier keys.
+    #[wasm_bindgen(js_name = mouseDown)]
+    pub fn mouse_down(
+        &mut self,
+        button: i16,
+        x: f32,
+        y: f32,
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+    ) -> bool {
+        let Some(button) = MouseButton::from_dom(button) else {
+            return false;
+        };
+        self.mouse(MouseEventKind::Down(button), x, y, [shift, ctrl, alt])
+    }
+
+    /// Converts a `mouseup` event, like [`Self::mouse_down`].
+    #[wasm_bindgen(js_name = mouseUp)]
+    pub fn mouse_up(
+        &mut self,
+        button: i16,
+        x: f32,
+        y: f32,
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+    ) -> bool {
+        let Some(button) = MouseButton::from_dom(button) else {
+            return false;
+        };
+        self.mouse(MouseEventKind::Up(button), x, y, [shift, ctrl, alt])
+    }
+
+    /// Converts a `mousemove` event, from its `buttons` and, like [`Self::mouse_down`], its
+    /// position and modifier keys, to a move, or a drag of the first held button, once per cell.
+    #[wasm_bindgen(js_name = mouseMove)]
+    pub fn mouse_move(
+        &mut self,
+        buttons: u16,
+        x: f32,
+        y: f32,
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+    ) -> bool {
+        let cell = self.cell(x, y);
+        if cell == self.mouse_cell {
+            return false;
+        }
+        self.mouse_cell = cell;
+        // The bits of `buttons` are the primary, secondary and auxiliary buttons.
+        let kind = [
+            (1, MouseButton::Left),
+            (4, MouseButton::Middle),
+            (2, MouseButton::Right),
+        ]
+        .into_iter()
+        .find(|(bit, _)| buttons & bit != 0)
+        .map_or(MouseEventKind::Moved, |(_, button)| {
+            MouseEventKind::Drag(button)
+        });
+        self.mouse(kind, x, y, [shift, ctrl, alt])
+    }
+
+    /// Converts a `wheel` event, from its `deltaX` and `deltaY` and, like
+    /// [`Self::mouse_down`], its position and modifier keys, to a scroll along its main axis.
+    #[allow(clippy::too_many_arguments)]
+    pub fn wheel(
+        &mut self,
+        delta_x: f64,
+        delta_y: f64,
+        x: f32,
+        y: f32,
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+    ) -> bool {
+        let kind = if delta_y.abs() >= delta_x.abs() {
+            if delta_y > 0.0 {
+                MouseEventKind::ScrollDown
+            } else if delta_y < 0.0 {
+                MouseEventKind::ScrollUp
+            } else {
+                return false;
+            }
+        } else if delta_x > 0.0 {
+            MouseEventKind::ScrollRight
+        } else {
+            MouseEventKind::ScrollLeft
+        };
+        self.mouse(kind, x, y, [shift, ctrl, alt])
+    }
+}
+
+impl WasmTerminal {
+    /// Creates a terminal drawing into a backend, for Rust apps that draw into it.
+    pub fn with_backend(backend: SoftBackend) -> Self {
+        let area = backend.buffer.area;
+        Self {
+            terminal: Terminal::new(backend).unwrap_or_else(|error| match error {}),
+            screen: VtScreen::new(area.width, area.height),
+            screen_changed: false,
+            rgba: Vec::new(),
+            dirty: Vec::new(),
+            mouse_cell: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// Returns the input received since the last call, in order, like `takeEvents` in
+    /// JavaScript.
+    pub fn take_events(&mut self) -> Vec<InputEvent> {
+        core::mem::take(&mut self.events)
+    }
+
+    fn cell(&self, x: f32, y: f32) -> Option<(u16, u16)> {
+        let hit = self.terminal.backend().cell_at_pixel(x, y)?;
+        Some((hit.position.x, hit.position.y))
+    }
+
+    /// Collects a mouse event on the cell under a pixel, returning whether there is one.
+    fn mouse(
+        &mut self,
+        kind: MouseEventKind,
+        x: f32,
+        y: f32,
+        [shift, ctrl, alt]: [bool; 3],
+    ) -> bool {
+        let Some((column, row)) = self.cell(x, y) else {
+            return false;
+        };
+        self.events.push(InputEvent::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers { shift, ctrl, alt },
+        }));
+        true
+    }
+}
+
+/// A property value of an event object for JavaScript.
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    Str(String),
+    Number(u16),
+    Bool(bool),
+}
+
+/// Returns the properties of the object given to JavaScript for an event.
+fn event_fields(event: InputEvent) -> Vec<(&'static str, Field)> {
+    let (modifiers, mut fields) = match event {
+        InputEvent::Key(key) => {
+            let name = match key.code {
+                KeyCode::Char(c) => String::from(c),
+                KeyCode::F(n) => format!("F{n}"),
+                KeyCode::Enter => "Enter".into(),
+                KeyCode::Backspace => "Backspace".into(),
+                // With shift, which is held for a back tab.
+                KeyCode::Tab | KeyCode::BackTab => "Tab".into(),
+                KeyCode::Esc => "Escape".into(),
+                KeyCode::Left => "ArrowLeft".into(),
+                KeyCode::Right => "ArrowRight".into(),
+                KeyCode::Up => "ArrowUp".into(),
+                KeyCode::Down => "ArrowDown".into(),
+                KeyCode::Home => "Home".into(),
+                KeyCode::End => "End".into(),
+                KeyCode::PageUp => "PageUp".into(),
+                KeyCode::PageDown => "PageDown".into(),
+                KeyCode::Insert => "Insert".into(),
+                KeyCode::Delete => "Delete".into(),
+            };
+            let shift = key.modifiers.shift || key.code == KeyCode::BackTab;
+            let modifiers = KeyModifiers {
+                shift,
+                ..key.modifiers
+            };
+            let fields = vec![
+                ("type", Field::Str("key".into())),
+                ("key", Field::Str(name)),
+            ];
+            (modifiers, fields)
+        }
+        InputEvent::Mouse(mouse) => {
+            let (kind, button) = match mouse.kind {
+                MouseEventKind::Down(button) => ("down", Some(button)),
+                MouseEventKind::Up(button) => ("up", Some(button)),
+                MouseEventKind::Drag(button) => ("drag", Some(button)),
+                MouseEventKind::Moved => ("move", None),
+                MouseEventKind::ScrollUp => ("scrollUp", None),
+                MouseEventKind::ScrollDown => ("scrollDown", None),
+                MouseEventKind::ScrollLeft => ("scrollLeft", None),
+                MouseEventKind::ScrollRight => ("scrollRight", None),
+            };
+            let mut fields = vec![
+                ("type", Field::Str(kind.into())),
+                ("column", Field::Number(mouse.column)),
+                ("row", Field::Number(mouse.row)),
+            ];
+            if let Some(button) = button {
+                // The numbering of `MouseButton::from_dom`.
+                let number = match button {
+                    MouseButton::Left => 0,
+                    MouseButton::Middle => 1,
+                    MouseButton::Right => 2,
+                };
+                fields.push(("button", Field::Number(number)));
+            }
+            (mouse.modifiers, fields)
+        }
+    };
+    fields.extend([
+        ("shift", Field::Bool(modifiers.shift)),
+        ("ctrl", Field::Bool(modifiers.ctrl)),
+        ("alt", Field::Bool(modifiers.alt)),
+    ]);
+    fields
+}
+
+impl Deref for WasmTerminal {
+    type Target = Terminal<SoftBackend>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl DerefMut for WasmTerminal {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_fed_text_into_the_pixels() {
+        // 4x2 cells of 8x13 pixels.
+        let mut terminal = WasmTerminal::with_bitmap_font(4, 2);
+        terminal.draw();
+        assert_eq!((terminal.width(), terminal.height()), (32, 26));
+        assert_eq!(terminal.rgba.len(), 32 * 26 * 4);
+        assert_eq!(terminal.take_dirty_rects(), [0, 0, 32, 26]);
+
+        // A red background on the first two cells.
+        terminal.feed(b"\x1b[41m");
+        terminal.feed(b"  ");
+        terminal.draw();
+        let rects = terminal.take_dirty_rects();
+        assert!(!rects.is_empty());
+        assert_eq!(&rects[..2], [0, 0]);
+        assert_eq!(terminal.rgba[15 * 4 + 1..15 * 4 + 4], [0, 0, 255]);
+        assert!(terminal.rgba[15 * 4] > 0x80);
+        terminal.draw();
+        assert_eq!(terminal.take_dirty_rects(), []);
+
+        // Redrawn whole after a resize.
+        assert!(terminal.fit(80, 13));
+        assert!(!terminal.fit(87, 25));
+        terminal.draw();
+        assert_eq!(terminal.rgba.len(), 80 * 13 * 4);
+        assert_eq!(terminal.take_dirty_rects(), [0, 0, 80, 13]);
+    }
+
+    #[test]
+    fn converts_dom_events() {
+        let mut terminal = WasmTerminal::with_bitmap_font(4, 2);
+        assert!(terminal.key_down("ArrowUp", false, true, false));
+        assert!(!terminal.key_down("Shift", true, false, false));
+        assert!(terminal.mouse_move(0, 9.0, 14.0, false, false, false));
+        // Moves inside the same cell are dropped.
+        assert!(!terminal.mouse_move(0, 10.0, 15.0, false, false, false));
+        assert!(terminal.mouse_down(0, 10.0, 15.0, false, false, false));
+        assert!(terminal.mouse_move(1, 17.0, 15.0, false, false, false));
+        assert!(terminal.mouse_up(0, 17.0, 15.0, true, false, false));
+        assert!(terminal.wheel(0.0, 100.0, 17.0, 15.0, false, false, false));
+        assert!(terminal.wheel(-3.0, 1.0, 17.0, 15.0, false, false, false));
+        // Outside of the pixmap, and the back button.
+        assert!(!terminal.mouse_down(0, 40.0, 15.0, false, false, false));
+        assert!(!terminal.mouse_down(3, 17.0, 15.0, false, false, false));
+
+        let events = terminal.take_events();
+        let ctrl = KeyModifiers {
+            ctrl: true,
+            ..KeyModifiers::NONE
+        };
+        assert_eq!(events[0], InputEvent::Key(KeyEvent::new(KeyCode::Up, ctrl)));
+        let mice: Vec<_> = events[1..]
+            .iter()
+            .map(|event| match event {
+                InputEvent::Mouse(mouse) => (mouse.kind, mouse.column, mouse.row),
+                InputEvent::Key(_) => panic!("{event:?}"),
+            })
+            .collect();
+        let left = MouseButton::Left;
+        assert_eq!(
+            mice,
+            [
+                (MouseEventKind::Moved, 1, 1),
+                (MouseEventKind::Down(left), 1, 1),
+                (MouseEventKind::Drag(left), 2, 1),
+                (MouseEventKind::Up(left), 2, 1),
+                (MouseEventKind::ScrollDown, 2, 1),
+                (MouseEventKind::ScrollLeft, 2, 1),
+            ]
+        );
+        let InputEvent::Mouse(up) = events[4] else {
+            unreachable!()
+        };
+        assert!(up.modifiers.shift);
+        assert!(terminal.take_events().is_empty());
+    }
+    /// Returns the value of a field of an event object.
+    fn field(fields: &[(&str, Field)], name: &str) -> Field {
+        fields.iter().find(|(n, _)| *n == name).unwrap().1.clone()
+    }
+
+    #[test]
+    fn key_objects_name_dom_keys() {
+        let codes = [
+            KeyCode::Char('é'),
+            KeyCode::F(12),
+            KeyCode::Enter,
+            KeyCode::Backspace,
+            KeyCode::Tab,
+            KeyCode::BackTab,
+            KeyCode::Esc,
+            KeyCode::Left,
+            KeyCode::Right,
+            KeyCode::Up,
+            KeyCode::Down,
+            KeyCode::Home,
+            KeyCode::End,
+            KeyCode::PageUp,
+            KeyCode::PageDown,
+            KeyCode::Insert,
+            KeyCode::Delete,
+        ];
+        for code in codes {
+            let key = KeyEvent::new(
+                code,
+                KeyModifiers {
+                    shift: code == KeyCode::BackTab,
+                    ..KeyModifiers::NONE
+                },
+            );
+            let fields = event_fields(InputEvent::Key(key));
+            assert_eq!(field(&fields, "type"), Field::Str("key".into()));
+            let (Field::Str(name), Field::Bool(shift)) =
+                (field(&fields, "key"), field(&fields, "shift"))
+            else {
+                panic!("{fields:?}");
+            };
+            // The names read back as the same key.
+            assert_eq!(KeyEvent::from_dom(&name, shift, false, false), Some(key));
+        }
+    }
+
+    #[test]
+    fn mouse_objects_have_cells_and_dom_buttons() {
+        let event = |kind| {
+            event_fields(InputEvent::Mouse(MouseEvent {
+                kind,
+                column: 3,
+                row: 1,
+                modifiers: KeyModifiers {
+                    alt: true,
+                    ..KeyModifiers::NONE
+                },
+            }))
+        };
+        assert_eq!(
+            event(MouseEventKind::Down(MouseButton::Right)),
+            [
+                ("type", Field::Str("down".into())),
+                ("column", Field::Number(3)),
+                ("row", Field::Number(1)),
+                ("button", Field::Number(2)),
+                ("shift", Field::Bool(false)),
+                ("ctrl", Field::Bool(false)),
+                ("alt", Field::Bool(true)),
+            ]
+        );
+        let fields = event(MouseEventKind::ScrollUp);
+        assert_eq!(field(&fields, "type"), Field::Str("scrollUp".into()));
+        assert!(fields.iter().all(|(name, _)| *name != "button"));
+    }
+
+    #[test]
+    fn input_is_encoded_for_the_fed_program() {
+        let mut terminal = WasmTerminal::with_bitmap_font(4, 2);
+        // The program asks for application cursor keys and the cursor position.
+        terminal.feed(b"\x1b[?1h\x1b[6n");
+        terminal.key_down("ArrowUp", false, false, false);
+        terminal.mouse_down(0, 1.0, 1.0, false, false, false);
+        terminal.key_down("c", false, true, false);
+        assert_eq!(terminal.take_input(), b"\x1b[1;1R\x1bOA\x03");
+        assert_eq!(terminal.take_input(), b"");
+    }
+}
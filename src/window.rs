@@ -0,0 +1,708 @@
+//! A desktop window showing a terminal, driven by winit and presented with softbuffer.
+
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::num::NonZeroU32;
+use core::ops::ControlFlow;
+
+use ratatui::Terminal;
+use softbuffer::{Context, SoftBufferError, Surface};
+use winit::application::ApplicationHandler;
+use winit::dpi::LogicalSize;
+use winit::error::{EventLoopError, OsError};
+use winit::event::{ElementState, MouseScrollDelta, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use winit::window::{Window, WindowId};
+
+use crate::input::{
+    InputEvent, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use crate::pixmap::{PixelRect, RgbPixmap};
+use crate::soft_backend::SoftBackend;
+
+/// What [`run_app`] tells the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AppEvent {
+    /// The window opened, or changed its size or scale factor, and the terminal was resized to
+    /// fill it.
+    Resized,
+    /// Key or mouse input to the window.
+    Input(InputEvent),
+}
+
+/// An error of [`run_app`].
+#[derive(Debug)]
+pub enum WindowError {
+    /// The event loop couldn't be created or failed.
+    EventLoop(EventLoopError),
+    /// The window couldn't be created.
+    Os(OsError),
+    /// The window surface couldn't be created, resized or presented.
+    Surface(SoftBufferError),
+}
+
+impl fmt::Display for WindowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EventLoop(error) => write!(f, "event loop failed: {error}"),
+            Self::Os(error) => write!(f, "failed to create the window: {error}"),
+            Self::Surface(error) => write!(f, "failed to present the window: {error}"),
+        }
+    }
+}
+
+impl core::error::Error for WindowError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::EventLoop(error) => Some(error),
+            Self::Os(error) => Some(error),
+            Self::Surface(error) => Some(error),
+        }
+    }
+}
+
+/// Runs an app in a window titled `title`, showing a terminal drawing into `backend`, until the
+/// window is closed or the app returns [`ControlFlow::Break`].
+///
+/// The app is called with the terminal when the window opens and after it's resized, and with
+/// every key press and every mouse event over the terminal, and draws into the terminal as it
+/// sees fit. The terminal fills the window with as many cells as fit, centered, and the regions
+/// it redraws are presented in the native `0x00RRGGBB` format of the window surface.
+///
+/// On displays with a scale factor above 1, fonts are rendered at the scaled size, and the
+/// glyphs of a fixed size of a [`BitmapFont`](crate::BitmapFont) or a
+/// [`Tileset`](crate::Tileset) are drawn with each pixel as a square of the rounded factor.
+///
+/// # Examples
+/// ```rust,no_run
+/// use std::ops::ControlFlow;
+///
+/// use ratatui::widgets::Paragraph;
+/// use soft_ratatui::{AppEvent, BitmapFont, InputEvent, KeyCode, SoftBackend, run_app};
+///
+/// let backend = SoftBackend::new_with_bitmap_font(40, 10, BitmapFont::FIXED_8X13);
+/// let mut text = String::from("Press q to quit");
+/// run_app("Hello", backend, |terminal, event| {
+///     if let AppEvent::Input(input) = event {
+///         if let InputEvent::Key(key) = input
+///             && key.code == KeyCode::Char('q')
+///         {
+///             return ControlFlow::Break(());
+///         }
+///         text = format!("{input:?}");
+///     }
+///     terminal
+///         .draw(|frame| frame.render_widget(Paragraph::new(text.as_str()), frame.area()))
+///         .unwrap();
+///     ControlFlow::Continue(())
+/// })
+/// .unwrap();
+/// ```
+pub fn run_app<F>(title: &str, backend: SoftBackend, on_event: F) -> Result<(), WindowError>
+where
+    F: FnMut(&mut Terminal<SoftBackend>, AppEvent) -> ControlFlow<()>,
+{
+    let event_loop = EventLoop::new().map_err(WindowError::EventLoop)?;
+    let mut runner = Runner {
+        title,
+        font_size: backend.font_size(),
+        terminal: Terminal::new(backend).unwrap_or_else(|error| match error {}),
+        on_event,
+        shown: None,
+        input: Input::default(),
+        error: None,
+    };
+    event_loop
+        .run_app(&mut runner)
+        .map_err(WindowError::EventLoop)?;
+    runner.error.map_or(Ok(()), Err)
+}
+
+/// The window and the state of its surface.
+struct Shown {
+    window: Rc<Window>,
+    surface: Surface<Rc<Window>, Rc<Window>>,
+    layout: Layout,
+    /// Whether the whole surface has to be drawn, after it was resized.
+    full: bool,
+    /// The regions of the pixmap presented in the last frame, for double buffered surfaces.
+    previous: Vec<PixelRect>,
+}
+
+struct Runner<'a, F> {
+    title: &'a str,
+    /// The font size at a scale factor of 1, `None` for glyphs of a fixed size.
+    font_size: Option<f32>,
+    terminal: Terminal<SoftBackend>,
+    on_event: F,
+    shown: Option<Shown>,
+    input: Input,
+    error: Option<WindowError>,
+}
+
+impl<F> Runner<'_, F>
+where
+    F: FnMut(&mut Terminal<SoftBackend>, AppEvent) -> ControlFlow<()>,
+{
+    fn open(&mut self, event_loop: &ActiveEventLoop) -> Result<(), WindowError> {
+        let backend = self.terminal.backend();
+        let attributes = Window::default_attributes()
+            .with_title(self.title)
+            .with_inner_size(LogicalSize::new(
+                backend.get_pixmap_width() as u32,
+                backend.get_pixmap_height() as u32,
+            ));
+        let window = Rc::new(
+            event_loop
+                .create_window(attributes)
+                .map_err(WindowError::Os)?,
+        );
+        let context = Context::new(window.clone()).map_err(WindowError::Surface)?;
+        let surface = Surface::new(&context, window.clone()).map_err(WindowError::Surface)?;
+        self.shown = Some(Shown {
+            window,
+            surface,
+            layout: Layout::centered((0, 0), (0, 0), 1),
+            full: true,
+            previous: Vec::new(),
+        });
+        self.resize(event_loop)
+    }
+
+    /// Fits the terminal and the surface to the size and the scale factor of the window.
+    fn resize(&mut self, event_loop: &ActiveEventLoop) -> Result<(), WindowError> {
+        let Some(shown) = &mut self.shown else {
+            return Ok(());
+        };
+        let size = shown.window.inner_size();
+        let (Some(width), Some(height)) =
+            (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
+        else {
+            // Minimized.
+            return Ok(());
+        };
+        shown
+            .surface
+            .resize(width, height)
+            .map_err(WindowError::Surface)?;
+        let backend = self.terminal.backend_mut();
+        let scale = pixel_scale(backend, self.font_size, shown.window.scale_factor());
+        let surface = (size.width as usize, size.height as usize);
+        backend.fit_to_pixels(surface.0 / scale, surface.1 / scale);
+        let pixmap = (backend.get_pixmap_width(), backend.get_pixmap_height());
+        shown.layout = Layout::centered(pixmap, surface, scale);
+        shown.full = true;
+        self.call(event_loop, AppEvent::Resized);
+        Ok(())
+    }
+
+    /// Calls the app, then asks for a frame if the terminal was redrawn.
+    fn call(&mut self, event_loop: &ActiveEventLoop, event: AppEvent) {
+        if (self.on_event)(&mut self.terminal, event).is_break() {
+            event_loop.exit();
+        }
+        if let Some(shown) = &self.shown
+            && (shown.full || !self.terminal.backend().dirty_regions().is_empty())
+        {
+            shown.window.request_redraw();
+        }
+    }
+
+    /// Copies the regions of the pixmap redrawn since the last frame to the surface, or the
+    /// whole pixmap when the surface doesn't hold the previous frames, and presents them.
+    fn present(&mut self) -> Result<(), WindowError> {
+        let Some(shown) = &mut self.shown else {
+            return Ok(());
+        };
+        let backend = self.terminal.backend_mut();
+        let regions = backend.dirty_regions();
+        let mut buffer = shown.surface.buffer_mut().map_err(WindowError::Surface)?;
+        let width = buffer.width().get() as usize;
+        let damage = match buffer.age() {
+            1 | 2 if !shown.full => {
+                let mut redrawn = regions.clone();
+                if buffer.age() == 2 {
+                    redrawn.extend_from_slice(&shown.previous);
+                }
+                redrawn
+                    .into_iter()
+                    .map(|region| {
+                        shown
+                            .layout
+                            .blit(&backend.rgb_pixmap, region, &mut buffer, width)
+                    })
+                    .collect()
+            }
+            _ => {
+                buffer.fill(0);
+                let bounds = backend.rgb_pixmap.bounds();
+                shown
+                    .layout
+                    .blit(&backend.rgb_pixmap, bounds, &mut buffer, width);
+                vec![PixelRect {
+                    x: 0,
+                    y: 0,
+                    width,
+                    height: buffer.height().get() as usize,
+                }]
+            }
+        };
+        shown.previous = if shown.full {
+            vec![backend.rgb_pixmap.bounds()]
+        } else {
+            regions
+        };
+        shown.full = false;
+        backend.clear_dirty();
+        let damage: Vec<softbuffer::Rect> = damage
+            .into_iter()
+            .filter_map(|rect| {
+                Some(softbuffer::Rect {
+                    x: rect.x as u32,
+                    y: rect.y as u32,
+                    width: NonZeroU32::new(rect.width as u32)?,
+                    height: NonZeroU32::new(rect.height as u32)?,
+                })
+            })
+            .collect();
+        shown.window.pre_present_notify();
+        buffer
+            .present_with_damage(&damage)
+            .map_err(WindowError::Surface)
+    }
+
+    /// Converts a window event to terminal input, `None` for events with no terminal
+    /// equivalent or outside of the terminal.
+    fn input_event(&mut self, event: &WindowEvent) -> Option<InputEvent> {
+        match event {
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.input.modifiers = key_modifiers(modifiers.state());
+                None
+            }
+            WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+                key_event(&event.logical_key, self.input.modifiers).map(InputEvent::Key)
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let layout = self.shown.as_ref()?.layout;
+                let cell = layout.cell(self.terminal.backend(), position.x, position.y);
+                self.input.moved(cell)
+            }
+            WindowEvent::CursorLeft { .. } => self.input.moved(None),
+            WindowEvent::MouseInput { state, button, .. } => self
+                .input
+                .button(mouse_button(*button)?, *state == ElementState::Pressed),
+            WindowEvent::MouseWheel { delta, .. } => self.input.wheel(*delta),
+            _ => None,
+        }
+    }
+}
+
+impl<F> ApplicationHandler for Runner<'_, F>
+where
+    F: FnMut(&mut Terminal<SoftBackend>, AppEvent) -> ControlFlow<()>,
+{
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.shown.is_none()
+            && let Err(error) = self.open(event_loop)
+        {
+            self.error = Some(error);
+            event_loop.exit();
+        }
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+        let result = match event {
+            WindowEvent::CloseRequested => {
+                event_loop.exit();
+                Ok(())
+            }
+            WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => {
+                self.resize(event_loop)
+            }
+            WindowEvent::RedrawRequested => self.present(),
+            event => {
+                if let Some(input) = self.input_event(&event) {
+                    self.call(event_loop, AppEvent::Input(input));
+                }
+                Ok(())
+            }
+        };
+        if let Err(error) = result {
+            self.error = Some(error);
+            event_loop.exit();
+        }
+    }
+}
+
+/// Sets the font of a backend to its size at a scale factor, and returns the size of the
+/// square of the surface each pixel of the pixmap is drawn as: 1 for fonts, and the rounded
+/// factor for glyphs of a fixed size.
+fn pixel_scale(backend: &mut SoftBackend, font_size: Option<f32>, scale_factor: f64) -> usize {
+    match font_size {
+        Some(font_size) => {
+            let scaled = (font_size as f64 * scale_factor).round().max(1.0) as i32;
+            // Setting the size recreates the pixmap.
+            if backend.font_size() != Some(scaled as f32) {
+                backend.set_font_size(scaled);
+            }
+            1
+        }
+        None => (scale_factor.round() as usize).max(1),
+    }
+}
+
+/// Where the pixmap is drawn in the surface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Layout {
+    /// The surface pixel of the top left pixel of the pixmap.
+    x: usize,
+    y: usize,
+    /// The size of the square of surface pixels each pixel of the pixmap is drawn as.
+    scale: usize,
+}
+
+impl Layout {
+    /// Centers a pixmap in a surface, both sized in pixels.
+    fn centered(pixmap: (usize, usize), surface: (usize, usize), scale: usize) -> Self {
+        Self {
+            x: surface.0.saturating_sub(pixmap.0 * scale) / 2,
+            y: surface.1.saturating_sub(pixmap.1 * scale) / 2,
+            scale,
+        }
+    }
+
+    /// Returns the cell under a pixel of the surface.
+    fn cell(&self, backend: &SoftBackend, x: f64, y: f64) -> Option<(u16, u16)> {
+        let pixel = |surface: f64, offset: usize| (surface - offset as f64) / self.scale as f64;
+        let hit = backend.cell_at_pixel(pixel(x, self.x) as f32, pixel(y, self.y) as f32)?;
+        Some((hit.position.x, hit.position.y))
+    }
+
+    /// Copies a region of the pixmap as `0x00RRGGBB` into a surface `width` pixels wide,
+    /// clipped to the surface, and returns the region of the surface written to.
+    fn blit(
+        &self,
+        pixmap: &RgbPixmap,
+        region: PixelRect,
+        surface: &mut [u32],
+        width: usize,
+    ) -> PixelRect {
+        let height = surface.len().checked_div(width).unwrap_or(0);
+        let x_start = self.x + region.x * self.scale;
+        let y_start = self.y + region.y * self.scale;
+        let x_end =
+            (self.x + (region.x + region.width).min(pixmap.width()) * self.scale).min(width);
+        let y_end =
+            (self.y + (region.y + region.height).min(pixmap.height()) * self.scale).min(height);
+        if x_start >= x_end || y_start >= y_end {
+            return PixelRect::default();
+        }
+        let data = pixmap.data();
+        for y in y_start..y_end {
+            let row = (y - self.y) / self.scale * pixmap.width();
+            for (x, pixel) in (x_start..x_end).zip(&mut surface[y * width + x_start..]) {
+                let i = 3 * (row + (x - self.x) / self.scale);
+                *pixel = (data[i] as u32) << 16 | (data[i + 1] as u32) << 8 | data[i + 2] as u32;
+            }
+        }
+        PixelRect {
+            x: x_start,
+            y: y_start,
+            width: x_end - x_start,
+            height: y_end - y_start,
+        }
+    }
+}
+
+/// The state of the keyboard and the mouse that their events are converted with.
+#[derive(Debug, Default)]
+struct Input {
+    modifiers: KeyModifiers,
+    /// The cell under the cursor, `None` outside of the terminal.
+    cell: Option<(u16, u16)>,
+    /// The buttons held, in the order they were pressed.
+    held: Vec<MouseButton>,
+}
+
+impl Input {
+    fn mouse(&self, kind: MouseEventKind) -> Option<InputEvent> {
+        let (column, row) = self.cell?;
+        Some(InputEvent::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: self.modifiers,
+        }))
+    }
+
+    /// Moves the cursor to a cell, returning a move, or a drag of the first held button, when
+    /// the cell changed.
+    fn moved(&mut self, cell: Option<(u16, u16)>) -> Option<InputEvent> {
+        if cell == self.cell {
+            return None;
+        }
+        self.cell = cell;
+        let kind = self.held.first().map_or(MouseEventKind::Moved, |button| {
+            MouseEventKind::Drag(*button)
+        });
+        self.mouse(kind)
+    }
+
+    fn button(&mut self, button: MouseButton, pressed: bool) -> Option<InputEvent> {
+        self.held.retain(|held| *held != button);
+        if pressed {
+            self.held.push(button);
+            self.mouse(MouseEventKind::Down(button))
+        } else {
+            self.mouse(MouseEventKind::Up(button))
+        }
+    }
+
+    /// Converts a wheel turn to a scroll along its main axis. Positive deltas move the content
+    /// right or down, i.e. scroll left or up.
+    fn wheel(&self, delta: MouseScrollDelta) -> Option<InputEvent> {
+        let (x, y) = match delta {
+            MouseScrollDelta::LineDelta(x, y) => (x as f64, y as f64),
+            MouseScrollDelta::PixelDelta(position) => (position.x, position.y),
+        };
+        let kind = if y.abs() >= x.abs() {
+            match y.partial_cmp(&0.0)? {
+                core::cmp::Ordering::Greater => MouseEventKind::ScrollUp,
+                core::cmp::Ordering::Less => MouseEventKind::ScrollDown,
+                core::cmp::Ordering::Equal => return None,
+            }
+        } else if x > 0.0 {
+            MouseEventKind::ScrollLeft
+        } else {
+            MouseEventKind::ScrollRight
+        };
+        self.mouse(kind)
+    }
+}
+
+fn key_modifiers(state: ModifiersState) -> KeyModifiers {
+    KeyModifiers {
+        shift: state.shift_key(),
+        ctrl: state.control_key(),
+        alt: state.alt_key(),
+    }
+}
+
+/// Converts a mouse button, `None` for the buttons a terminal doesn't have.
+fn mouse_button(button: winit::event::MouseButton) -> Option<MouseButton> {
+    match button {
+        winit::event::MouseButton::Left => Some(MouseButton::Left),
+        winit::event::MouseButton::Right => Some(MouseButton::Right),
+        winit::event::MouseButton::Middle => Some(MouseButton::Middle),
+        _ => None,
+    }
+}
+
+/// Converts a pressed key, `None` for keys with no terminal equivalent, such as modifier keys
+/// on their own.
+fn key_event(key: &Key, modifiers: KeyModifiers) -> Option<KeyEvent> {
+    let code = match key {
+        Key::Character(text) => {
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => KeyCode::Char(c),
+                _ => return None,
+            }
+        }
+        Key::Named(named) => match named {
+            NamedKey::Space => KeyCode::Char(' '),
+            NamedKey::Enter => KeyCode::Enter,
+            NamedKey::Backspace => KeyCode::Backspace,
+            NamedKey::Tab if modifiers.shift => KeyCode::BackTab,
+            NamedKey::Tab => KeyCode::Tab,
+            NamedKey::Escape => KeyCode::Esc,
+            NamedKey::ArrowLeft => KeyCode::Left,
+            NamedKey::ArrowRight => KeyCode::Right,
+            NamedKey::ArrowUp => KeyCode::Up,
+            NamedKey::ArrowDown => KeyCode::Down,
+            NamedKey::Home => KeyCode::Home,
+            NamedKey::End => KeyCode::End,
+            NamedKey::PageUp => KeyCode::PageUp,
+            NamedKey::PageDown => KeyCode::PageDown,
+            NamedKey::Insert => KeyCode::Insert,
+            NamedKey::Delete => KeyCode::Delete,
+            NamedKey::F1 => KeyCode::F(1),
+            NamedKey::F2 => KeyCode::F(2),
+            NamedKey::F3 => KeyCode::F(3),
+            NamedKey::F4 => KeyCode::F(4),
+            NamedKey::F5 => KeyCode::F(5),
+            NamedKey::F6 => KeyCode::F(6),
+            NamedKey::F7 => KeyCode::F(7),
+            NamedKey::F8 => KeyCode::F(8),
+            NamedKey::F9 => KeyCode::F(9),
+            NamedKey::F10 => KeyCode::F(10),
+            NamedKey::F11 => KeyCode::F(11),
+            NamedKey::F12 => KeyCode::F(12),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(KeyEvent::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::dpi::PhysicalPosition;
+
+    use super::*;
+    use crate::BitmapFont;
+
+    #[test]
+    fn blits_scaled_and_clipped() {
+        let mut pixmap = RgbPixmap::new(2, 2);
+        pixmap.put_pixel(0, 0, [0x12, 0x34, 0x56]);
+        pixmap.put_pixel(1, 1, [0xff, 0, 0]);
+        // A 2x2 pixmap drawn at twice the size from (1, 1) of a 4x4 surface, which clips it.
+        let layout = Layout::centered((2, 2), (6, 6), 2);
+        assert_eq!(
+            layout,
+            Layout {
+                x: 1,
+                y: 1,
+                scale: 2
+            }
+        );
+        let mut surface = [0xffff_ffff; 16];
+        let written = layout.blit(&pixmap, pixmap.bounds(), &mut surface, 4);
+        assert_eq!(
+            written,
+            PixelRect {
+                x: 1,
+                y: 1,
+                width: 3,
+                height: 3
+            }
+        );
+        let (a, r, w) = (0x0012_3456, 0x00ff_0000, 0xffff_ffff);
+        #[rustfmt::skip]
+        assert_eq!(surface, [
+            w, w, w, w,
+            w, a, a, 0,
+            w, a, a, 0,
+            w, 0, 0, r,
+        ]);
+
+        // Only the region, and nothing for regions off the surface.
+        let mut surface = [0xffff_ffff; 16];
+        let region = PixelRect {
+            x: 1,
+            y: 0,
+            width: 1,
+            height: 1,
+        };
+        let written = layout.blit(&pixmap, region, &mut surface, 4);
+        assert_eq!(
+            (written.x, written.y, written.width, written.height),
+            (3, 1, 1, 2)
+        );
+        assert_eq!(surface.iter().filter(|pixel| **pixel == 0).count(), 2);
+        let layout = Layout { x: 4, ..layout };
+        assert_eq!(
+            layout.blit(&pixmap, pixmap.bounds(), &mut surface, 4),
+            PixelRect::default()
+        );
+    }
+
+    #[test]
+    fn finds_cells_in_the_centered_pixmap() {
+        // 10x4 cells of 8x13 pixels, at twice the size in the middle of a 200x120 surface.
+        let backend = SoftBackend::new_with_bitmap_font(10, 4, BitmapFont::FIXED_8X13);
+        let layout = Layout::centered((80, 52), (200, 120), 2);
+        assert_eq!((layout.x, layout.y), (20, 8));
+        assert_eq!(layout.cell(&backend, 20.0, 8.0), Some((0, 0)));
+        assert_eq!(layout.cell(&backend, 20.0 + 17.0, 8.0 + 27.0), Some((1, 1)));
+        assert_eq!(layout.cell(&backend, 179.0, 111.0), Some((9, 3)));
+        assert_eq!(layout.cell(&backend, 19.0, 50.0), None);
+        assert_eq!(layout.cell(&backend, 100.0, 113.0), None);
+    }
+
+    #[test]
+    fn scales_fixed_glyphs_by_whole_pixels() {
+        let mut backend = SoftBackend::new_with_bitmap_font(10, 4, BitmapFont::FIXED_8X13);
+        assert_eq!(pixel_scale(&mut backend, None, 1.0), 1);
+        assert_eq!(pixel_scale(&mut backend, None, 1.25), 1);
+        assert_eq!(pixel_scale(&mut backend, None, 1.5), 2);
+        assert_eq!(pixel_scale(&mut backend, None, 3.0), 3);
+        assert_eq!(pixel_scale(&mut backend, None, 0.5), 1);
+    }
+
+    #[test]
+    fn converts_mouse_events() {
+        let mut input = Input::default();
+        let kinds = |events: &[Option<InputEvent>]| -> Vec<_> {
+            events
+                .iter()
+                .map(|event| match event {
+                    Some(InputEvent::Mouse(mouse)) => Some((mouse.kind, mouse.column, mouse.row)),
+                    _ => None,
+                })
+                .collect()
+        };
+        let left = MouseButton::Left;
+        let events = [
+            input.moved(Some((1, 2))),
+            // Moves inside the same cell are dropped.
+            input.moved(Some((1, 2))),
+            input.button(left, true),
+            input.moved(Some((2, 2))),
+            input.button(left, false),
+            input.wheel(MouseScrollDelta::LineDelta(0.0, -1.0)),
+            input.wheel(MouseScrollDelta::PixelDelta(PhysicalPosition::new(
+                8.0, 2.0,
+            ))),
+            input.moved(None),
+            // Outside of the terminal.
+            input.button(MouseButton::Right, true),
+        ];
+        assert_eq!(
+            kinds(&events),
+            [
+                Some((MouseEventKind::Moved, 1, 2)),
+                None,
+                Some((MouseEventKind::Down(left), 1, 2)),
+                Some((MouseEventKind::Drag(left), 2, 2)),
+                Some((MouseEventKind::Up(left), 2, 2)),
+                Some((MouseEventKind::ScrollDown, 2, 2)),
+                Some((MouseEventKind::ScrollLeft, 2, 2)),
+                None,
+                None,
+            ]
+        );
+        // The button pressed outside is still held when the cursor comes back.
+        assert_eq!(
+            kinds(&[input.moved(Some((0, 0)))]),
+            [Some((MouseEventKind::Drag(MouseButton::Right), 0, 0))]
+        );
+    }
+
+    #[test]
+    fn converts_keys() {
+        let code = |key| key_event(&key, KeyModifiers::NONE).map(|event| event.code);
+        assert_eq!(code(Key::Character("é".into())), Some(KeyCode::Char('é')));
+        assert_eq!(code(Key::Named(NamedKey::Space)), Some(KeyCode::Char(' ')));
+        assert_eq!(code(Key::Named(NamedKey::ArrowDown)), Some(KeyCode::Down));
+        assert_eq!(code(Key::Named(NamedKey::F12)), Some(KeyCode::F(12)));
+        assert_eq!(code(Key::Character("ab".into())), None);
+        assert_eq!(code(Key::Named(NamedKey::Shift)), None);
+
+        let modifiers = key_modifiers(ModifiersState::CONTROL | ModifiersState::SHIFT);
+        let expected = KeyModifiers {
+            shift: true,
+            ctrl: true,
+            alt: false,
+        };
+        assert_eq!(modifiers, expected);
+        assert_eq!(
+            key_event(&Key::Named(NamedKey::Tab), modifiers),
+            Some(KeyEvent::new(KeyCode::BackTab, expected))
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! Runs `SoftRatatuiPlugin` in a headless app, with a window entity but no window backend or
+//! renderer.
+#![cfg(feature = "bevy")]
+
+use bevy::asset::AssetEvent;
+use bevy::ecs::message::Messages;
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::mouse::MouseButtonInput;
+use bevy::math::DVec2;
+use bevy::prelude::*;
+use bevy::window::{CursorMoved, ExitCondition, PrimaryWindow, WindowResolution};
+use ratatui::style::Stylize;
+use ratatui::widgets::Paragraph;
+use soft_ratatui::{
+    BitmapFont, InputEvent, KeyModifiers, MouseEventKind, SoftBackend, SoftRatatuiPlugin,
+    SoftTerminal, TerminalFit, TerminalFocus, TerminalImage, TerminalInput,
+};
+
+/// An app with a 200x100 primary window.
+fn app(scale_factor: f32) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        ImagePlugin::default(),
+        bevy::input::InputPlugin,
+        WindowPlugin {
+            primary_window: Some(Window {
+                resolution: WindowResolution::new(200, 100)
+                    .with_scale_factor_override(scale_factor),
+                ..default()
+            }),
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+            ..default()
+        },
+        SoftRatatuiPlugin,
+    ));
+    app
+}
+
+fn terminal(width: u16, height: u16) -> SoftTerminal {
+    SoftTerminal::new(SoftBackend::new_with_bitmap_font(
+        width,
+        height,
+        BitmapFont::FIXED_8X13,
+    ))
+}
+
+fn grid(app: &App, entity: Entity) -> (u16, u16) {
+    let area = app
+        .world()
+        .get::<SoftTerminal>(entity)
+        .unwrap()
+        .backend()
+        .buffer
+        .area;
+    (area.width, area.height)
+}
+
+fn image(app: &App, entity: Entity) -> &Image {
+    let handle = &app.world().get::<TerminalImage>(entity).unwrap().0;
+    app.world().resource::<Assets<Image>>().get(handle).unwrap()
+}
+
+fn inputs(app: &mut App) -> Vec<TerminalInput> {
+    app.world_mut()
+        .resource_mut::<Messages<TerminalInput>>()
+        .drain()
+        .collect()
+}
+
+fn primary_window(app: &mut App) -> Entity {
+    app.world_mut()
+        .query_filtered::<Entity, With<PrimaryWindow>>()
+        .single(app.world())
+        .unwrap()
+}
+
+#[test]
+fn image_follows_the_pixmap() {
+    let mut app = app(1.0);
+    let entity = app
+        .world_mut()
+        .spawn((terminal(4, 2), Sprite::default()))
+        .id();
+    app.update();
+
+    let handle = app.world().get::<TerminalImage>(entity).unwrap().0.clone();
+    assert_eq!(app.world().get::<Sprite>(entity).unwrap().image, handle);
+    assert_eq!(image(&app, entity).size(), UVec2::new(32, 26));
+
+    app.world_mut()
+        .get_mut::<SoftTerminal>(entity)
+        .unwrap()
+        .draw(|frame| frame.render_widget(Paragraph::new("Hi").red().on_blue(), frame.area()))
+        .unwrap();
+    app.update();
+    let expected = app
+        .world()
+        .get::<SoftTerminal>(entity)
+        .unwrap()
+        .backend()
+        .get_pixmap_data_as_rgba();
+    assert_eq!(image(&app, entity).data.as_deref(), Some(&expected[..]));
+
+    // Frames that draw nothing leave the image alone.
+    app.update();
+    let mut modified = app
+        .world()
+        .resource::<Messages<AssetEvent<Image>>>()
+        .get_cursor_current();
+    for _ in 0..3 {
+        app.update();
+    }
+    let messages = app.world().resource::<Messages<AssetEvent<Image>>>();
+    assert_eq!(
+        modified
+            .read(messages)
+            .filter(|event| event.is_modified(&handle))
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn terminals_fit_the_window_and_sprites() {
+    let mut app = app(2.0);
+    let window = app
+        .world_mut()
+        .spawn((terminal(1, 1), Sprite::default(), TerminalFit::Window))
+        .id();
+    let sprite = app
+        .world_mut()
+        .spawn((
+            terminal(1, 1),
+            Sprite::sized(Vec2::new(80.0, 30.0)),
+            TerminalFit::Sprite,
+        ))
+        .id();
+    app.update();
+    // The window is 200x100 physical pixels.
+    assert_eq!(grid(&app, window), (25, 7));
+    assert_eq!(image(&app, window).size(), UVec2::new(200, 91));
+    assert_eq!(
+        app.world().get::<Sprite>(window).unwrap().custom_size,
+        Some(Vec2::new(100.0, 45.5))
+    );
+    assert_eq!(grid(&app, sprite), (10, 2));
+
+    let primary = primary_window(&mut app);
+    app.world_mut()
+        .get_mut::<Window>(primary)
+        .unwrap()
+        .resolution
+        .set_physical_resolution(400, 100);
+    app.update();
+    assert_eq!(grid(&app, window), (50, 7));
+    assert_eq!(image(&app, window).size(), UVec2::new(400, 91));
+}
+
+#[test]
+fn keys_go_to_focused_terminals() {
+    let mut app = app(1.0);
+    let focused = app.world_mut().spawn((terminal(4, 2), TerminalFocus)).id();
+    app.world_mut().spawn(terminal(4, 2));
+    app.update();
+
+    let window = primary_window(&mut app);
+    let key = |key_code, logical_key, state| KeyboardInput {
+        key_code,
+        logical_key,
+        state,
+        text: None,
+        repeat: false,
+        window,
+    };
+    app.world_mut()
+        .write_message(key(KeyCode::ShiftLeft, Key::Shift, ButtonState::Pressed));
+    app.world_mut()
+        .write_message(key(KeyCode::Tab, Key::Tab, ButtonState::Pressed));
+    app.world_mut()
+        .write_message(key(KeyCode::Tab, Key::Tab, ButtonState::Released));
+    app.update();
+
+    let shift = KeyModifiers {
+        shift: true,
+        ..KeyModifiers::NONE
+    };
+    assert_eq!(
+        inputs(&mut app),
+        [TerminalInput {
+            terminal: focused,
+            event: InputEvent::Key(soft_ratatui::KeyEvent::new(
+                soft_ratatui::KeyCode::BackTab,
+                shift
+            )),
+        }]
+    );
+}
+
+#[test]
+fn mouse_events_are_mapped_to_cells() {
+    let mut app = app(1.0);
+    let entity = app
+        .world_mut()
+        .spawn((terminal(1, 1), Sprite::default(), TerminalFit::Window))
+        .id();
+    app.update();
+    inputs(&mut app);
+
+    // The 200x91 pixmap is centered in the 200x100 window.
+    let window = primary_window(&mut app);
+    app.world_mut()
+        .get_mut::<Window>(window)
+        .unwrap()
+        .set_physical_cursor_position(Some(DVec2::new(20.0, 30.0)));
+    app.world_mut().write_message(MouseButtonInput {
+        button: bevy::input::mouse::MouseButton::Left,
+        state: ButtonState::Pressed,
+        window,
+    });
+    app.update();
+    app.world_mut().write_message(CursorMoved {
+        window,
+        position: Vec2::new(20.0, 30.0),
+        delta: None,
+    });
+    app.update();
+
+    let events: Vec<_> = inputs(&mut app)
+        .into_iter()
+        .map(|input| {
+            assert_eq!(input.terminal, entity);
+            let InputEvent::Mouse(event) = input.event else {
+                panic!("{input:?}")
+            };
+            (event.kind, event.column, event.row)
+        })
+        .collect();
+    let left = soft_ratatui::MouseButton::Left;
+    assert_eq!(
+        events,
+        [
+            (MouseEventKind::Down(left), 2, 1),
+            (MouseEventKind::Drag(left), 2, 1)
+        ]
+    );
+}
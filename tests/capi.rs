@@ -0,0 +1,64 @@
+//! Compiles `tests/data/capi.c` against `include/soft_ratatui.h` with the C compiler (`$CC`, or
+//! `cc`), links it to the cdylib built for the tests and runs it, so that the header can't drift
+//! from `src/capi.rs`.
+#![cfg(all(feature = "capi", target_os = "linux"))]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A directory in the temporary directory, unique to the process, removed when dropped even if
+/// the test fails.
+struct TempDir(PathBuf);
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn c_program_runs_against_the_header() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // The cdylib is built into the directory of the test executables.
+    let exe = std::env::current_exe().unwrap();
+    let deps = exe.parent().unwrap();
+    assert!(
+        deps.join("libsoft_ratatui.so").exists(),
+        "no cdylib in {}",
+        deps.display()
+    );
+    let dir =
+        TempDir(std::env::temp_dir().join(format!("soft_ratatui_capi_{}", std::process::id())));
+    std::fs::create_dir_all(&dir.0).unwrap();
+    let program = dir.0.join("capi");
+
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".into());
+    let output = Command::new(&compiler)
+        .args(["-std=c11", "-Wall", "-Wextra", "-Werror", "-pedantic", "-I"])
+        .arg(manifest.join("include"))
+        .arg(manifest.join("tests/data/capi.c"))
+        .arg("-L")
+        .arg(deps)
+        .arg(format!("-Wl,-rpath,{}", deps.display()))
+        .args(["-lsoft_ratatui", "-o"])
+        .arg(&program)
+        .output()
+        .unwrap_or_else(|error| panic!("failed to run {compiler}: {error}"));
+    assert!(
+        output.status.success(),
+        "compiling failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Cargo's library path may hold a cdylib of other features, which would take precedence over
+    // the run path.
+    let output = Command::new(&program)
+        .env_remove("LD_LIBRARY_PATH")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "the program failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
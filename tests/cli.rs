@@ -0,0 +1,97 @@
+//! Runs the `soft_ratatui` command line tool on ANSI text and checks the images it writes.
+#![cfg(feature = "cli")]
+
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+/// A file in the temporary directory, unique to the process and the test, removed when dropped
+/// even if the test fails.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str) -> Self {
+        let name = format!("soft_ratatui_cli_{}_{name}", std::process::id());
+        Self(std::env::temp_dir().join(name))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Runs the tool with `args`, writing `input` to its stdin.
+fn run(args: &[&str], input: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_soft_ratatui"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Renders ANSI text to a PNG, returning its width, height and RGB pixels.
+fn render_png(args: &[&str], input: &[u8]) -> (u32, u32, Vec<u8>) {
+    let file = TempFile::new(&format!("{}.png", args.len() + input.len()));
+    let path = file.0.to_str().unwrap();
+    let output = run(&[&["-o", path], args].concat(), input);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let data = std::fs::read(&file.0).unwrap();
+    let mut reader = png::Decoder::new(Cursor::new(data)).read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!(info.color_type, png::ColorType::Rgb);
+    (info.width, info.height, pixels)
+}
+
+/// Returns whether a row of 8x13 cells of an image has any pixel other than the background.
+fn has_text(width: u32, pixels: &[u8], row: usize) -> bool {
+    let row_bytes = width as usize * 3;
+    pixels[row * 13 * row_bytes..(row + 1) * 13 * row_bytes]
+        .chunks_exact(3)
+        .any(|pixel| pixel != [0, 0, 0])
+}
+
+#[test]
+fn sizes_text_to_its_lines() {
+    let (width, height, pixels) = render_png(&["--cols", "5"], b"line1\nline2\nline3\n");
+    assert_eq!((width, height), (40, 39));
+    for row in 0..3 {
+        assert!(has_text(width, &pixels, row), "row {row} is blank");
+    }
+    // The final newline makes no difference.
+    assert_eq!(
+        render_png(&["--cols", "5"], b"line1\nline2\nline3").2,
+        pixels
+    );
+}
+
+#[test]
+fn crops_trailing_blank_rows() {
+    let (width, height, pixels) = render_png(&["--cols", "5"], b"one\n\x1b[31mtwo\x1b[0m\n\n\n");
+    assert_eq!((width, height), (40, 26));
+    assert!(has_text(width, &pixels, 0));
+    assert!(has_text(width, &pixels, 1));
+}
+
+#[test]
+fn keeps_the_rows_asked_for() {
+    let (_, height, _) = render_png(&["--cols", "5", "--rows", "4"], b"one\n");
+    assert_eq!(height, 52);
+}
+
+#[test]
+fn rejects_a_missing_output() {
+    let output = run(&[], b"text");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing --output"));
+}
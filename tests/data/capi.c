@@ -0,0 +1,76 @@
+/*
+ * Calls every function of include/soft_ratatui.h, linked to the cdylib by tests/capi.rs.
+ * Exits with 1 and prints the failed check if the library doesn't behave as declared.
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "soft_ratatui.h"
+
+#define CHECK(condition)                                                   \
+    do {                                                                   \
+        if (!(condition)) {                                                \
+            fprintf(stderr, "%s:%d: %s\n", __FILE__, __LINE__, #condition); \
+            exit(1);                                                       \
+        }                                                                  \
+    } while (0)
+
+/* The layouts of the #[repr(C)] structs of src/capi.rs. */
+_Static_assert(sizeof(SoftRatatuiRect) == 16, "SoftRatatuiRect is four uint32_t");
+_Static_assert(sizeof(SoftRatatuiPixmap) == 4 * sizeof(size_t) + sizeof(void *),
+               "SoftRatatuiPixmap is a pointer, three size_t and a padded uint32_t");
+
+static const uint8_t *pixel(SoftRatatuiPixmap pixmap, size_t x, size_t y) {
+    return pixmap.data + y * pixmap.stride + x * 3;
+}
+
+int main(void) {
+    /* 4x2 cells of the 8x13 bitmap font. */
+    SoftRatatui *handle = soft_ratatui_new_bitmap(4, 2);
+    CHECK(handle != NULL);
+    SoftRatatuiPixmap pixmap = soft_ratatui_pixmap(handle);
+    CHECK(pixmap.width == 32 && pixmap.height == 26);
+    CHECK(pixmap.stride == 32 * 3);
+    CHECK(pixmap.format == SOFT_RATATUI_FORMAT_RGB888);
+    CHECK(soft_ratatui_clear_dirty(handle));
+    CHECK(soft_ratatui_dirty_rects(handle, NULL, 0) == 0);
+
+    /* A space on a colored background fills the cell with the color. */
+    CHECK(soft_ratatui_set_cell(handle, 1, 1, " ", SOFT_RATATUI_RESET,
+                                SOFT_RATATUI_RGB(0x12, 0x34, 0x56), SOFT_RATATUI_BOLD));
+    CHECK(!soft_ratatui_set_cell(handle, 4, 0, "x", SOFT_RATATUI_RESET, SOFT_RATATUI_RESET, 0));
+    CHECK(!soft_ratatui_set_cell(handle, 0, 0, "\xff", SOFT_RATATUI_RESET, SOFT_RATATUI_RESET, 0));
+    CHECK(soft_ratatui_render(handle));
+    SoftRatatuiRect rects[4];
+    CHECK(soft_ratatui_dirty_rects(handle, rects, 4) == 1);
+    CHECK(rects[0].x == 8 && rects[0].y == 13 && rects[0].width == 8 && rects[0].height == 13);
+    pixmap = soft_ratatui_pixmap(handle);
+    const uint8_t *p = pixel(pixmap, 8, 13);
+    CHECK(p[0] == 0x12 && p[1] == 0x34 && p[2] == 0x56);
+    CHECK(soft_ratatui_clear_dirty(handle));
+    CHECK(soft_ratatui_dirty_rects(handle, rects, 4) == 0);
+
+    /* ANSI text replaces the cells: a red background on the first two cells. */
+    const char *text = "\x1b[41m  ";
+    CHECK(soft_ratatui_feed(handle, (const uint8_t *)text, 3));
+    CHECK(soft_ratatui_feed(handle, (const uint8_t *)text + 3, 4));
+    CHECK(!soft_ratatui_feed(handle, NULL, 0));
+    CHECK(soft_ratatui_render(handle));
+    CHECK(soft_ratatui_dirty_rects(handle, NULL, 0) > 0);
+    pixmap = soft_ratatui_pixmap(handle);
+    p = pixel(pixmap, 15, 0);
+    CHECK(p[0] > 0x80 && p[1] == 0 && p[2] == 0);
+
+    CHECK(soft_ratatui_clear(handle));
+    CHECK(soft_ratatui_resize(handle, 2, 1));
+    pixmap = soft_ratatui_pixmap(handle);
+    CHECK(pixmap.width == 16 && pixmap.height == 13);
+    soft_ratatui_free(handle);
+    soft_ratatui_free(NULL);
+
+    /* Data that isn't a font. */
+    const uint8_t not_a_font[4] = {0, 1, 2, 3};
+    CHECK(soft_ratatui_new_with_font(4, 2, 16, not_a_font, sizeof not_a_font) == NULL);
+    return 0;
+}
@@ -0,0 +1,157 @@
+//! Runs `EguiTerminal` in a headless egui context, checking the texture uploads in its output.
+#![cfg(feature = "egui")]
+
+use egui::epaint::ImageDelta;
+use egui::{
+    CentralPanel, Context, Event, Frame, FullOutput, Modifiers, PointerButton, Pos2, RawInput,
+    Rect, TextureId, vec2,
+};
+use ratatui::layout::Size;
+use ratatui::widgets::Paragraph;
+use soft_ratatui::{
+    BitmapFont, EguiTerminal, InputEvent, KeyCode, KeyEvent, KeyModifiers, MouseButton,
+    MouseEventKind, SoftBackend,
+};
+
+/// Runs a frame of a 200x100 screen filled by the terminal.
+fn frame(ctx: &Context, terminal: &mut EguiTerminal, events: Vec<Event>) -> FullOutput {
+    let input = RawInput {
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, vec2(200.0, 100.0))),
+        events,
+        ..Default::default()
+    };
+    ctx.run(input, |ctx| {
+        CentralPanel::default()
+            .frame(Frame::NONE)
+            .show(ctx, |ui| ui.add(&mut *terminal));
+    })
+}
+
+fn uploads(output: &FullOutput) -> Vec<(Option<[usize; 2]>, [usize; 2])> {
+    output
+        .textures_delta
+        .set
+        .iter()
+        // Not the font atlas.
+        .filter(|(id, _)| *id != TextureId::default())
+        .map(|(_, ImageDelta { pos, image, .. })| (*pos, image.size()))
+        .collect()
+}
+
+fn terminal() -> EguiTerminal {
+    EguiTerminal::new(SoftBackend::new_with_bitmap_font(
+        1,
+        1,
+        BitmapFont::FIXED_8X13,
+    ))
+}
+
+#[test]
+fn uploads_the_texture_once_then_dirty_regions() {
+    let ctx = Context::default();
+    let mut terminal = terminal();
+    let output = frame(&ctx, &mut terminal, vec![]);
+    assert_eq!(terminal.size().unwrap(), Size::new(25, 7));
+    assert_eq!(uploads(&output), [(None, [200, 91])]);
+
+    // The first draw after the fit clears the whole screen.
+    terminal.draw(|_| {}).unwrap();
+    let output = frame(&ctx, &mut terminal, vec![]);
+    assert_eq!(uploads(&output), [(Some([0, 0]), [200, 91])]);
+    terminal
+        .draw(|frame| frame.render_widget(Paragraph::new("Hi"), frame.area()))
+        .unwrap();
+    let output = frame(&ctx, &mut terminal, vec![]);
+    assert_eq!(uploads(&output), [(Some([0, 0]), [16, 13])]);
+    let output = frame(&ctx, &mut terminal, vec![]);
+    assert_eq!(uploads(&output), []);
+
+    // A smaller screen takes a new texture of the new size.
+    let input = RawInput {
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, vec2(100.0, 100.0))),
+        ..Default::default()
+    };
+    let output = ctx.run(input, |ctx| {
+        CentralPanel::default()
+            .frame(Frame::NONE)
+            .show(ctx, |ui| ui.add(&mut terminal));
+    });
+    assert_eq!(terminal.size().unwrap(), Size::new(12, 7));
+    assert_eq!(uploads(&output), [(None, [96, 91])]);
+}
+
+#[test]
+fn sends_input_once_focused() {
+    let ctx = Context::default();
+    let mut terminal = terminal();
+    frame(&ctx, &mut terminal, vec![]);
+
+    // Typing before the terminal is clicked goes nowhere.
+    frame(
+        &ctx,
+        &mut terminal,
+        vec![Event::Text("x".into()), Event::Copy],
+    );
+    assert_eq!(terminal.take_events(), []);
+
+    let click = |pressed| Event::PointerButton {
+        pos: Pos2::new(20.0, 30.0),
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Modifiers::NONE,
+    };
+    frame(
+        &ctx,
+        &mut terminal,
+        vec![Event::PointerMoved(Pos2::new(20.0, 30.0)), click(true)],
+    );
+    frame(&ctx, &mut terminal, vec![click(false)]);
+    // egui keeps Tab for the terminal from its second frame with the focus.
+    frame(&ctx, &mut terminal, vec![]);
+    let tab = Event::Key {
+        key: egui::Key::Tab,
+        physical_key: None,
+        pressed: true,
+        repeat: false,
+        modifiers: Modifiers::NONE,
+    };
+    frame(&ctx, &mut terminal, vec![Event::Text("a".into()), tab]);
+    // Ctrl+C arrives as a copy command.
+    frame(
+        &ctx,
+        &mut terminal,
+        vec![Event::Text("b".into()), Event::Copy],
+    );
+
+    let left = MouseButton::Left;
+    let events: Vec<_> = terminal
+        .take_events()
+        .into_iter()
+        .map(|event| match event {
+            InputEvent::Mouse(mouse) => {
+                assert_eq!((mouse.column, mouse.row), (2, 2));
+                Err(mouse.kind)
+            }
+            InputEvent::Key(key) => Ok(key),
+        })
+        .collect();
+    assert_eq!(
+        events,
+        [
+            Err(MouseEventKind::Moved),
+            Err(MouseEventKind::Down(left)),
+            Err(MouseEventKind::Up(left)),
+            Ok(KeyEvent::from(KeyCode::Char('a'))),
+            // Tab stays in the terminal instead of moving the focus.
+            Ok(KeyEvent::from(KeyCode::Tab)),
+            Ok(KeyEvent::from(KeyCode::Char('b'))),
+            Ok(KeyEvent::new(
+                KeyCode::Char('c'),
+                KeyModifiers {
+                    ctrl: true,
+                    ..KeyModifiers::NONE
+                }
+            )),
+        ]
+    );
+}
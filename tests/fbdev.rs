@@ -0,0 +1,96 @@
+//! Presents terminals to plain files standing in for framebuffer devices.
+#![cfg(all(feature = "fbdev", target_os = "linux"))]
+
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use ratatui::Terminal;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Block;
+use soft_ratatui::{BitmapFont, Channel, Framebuffer, FramebufferLayout, SoftBackend};
+
+/// A file in the temporary directory, unique to the process and the test, removed when dropped
+/// even if the test fails.
+struct FakeFramebuffer(PathBuf);
+
+impl FakeFramebuffer {
+    fn new(test: &str) -> Self {
+        let name = format!("soft_ratatui_{test}_{}", std::process::id());
+        Self(std::env::temp_dir().join(name))
+    }
+}
+
+impl Drop for FakeFramebuffer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Draws a 2x1 terminal of 8x13 cells filled with a background color.
+fn filled_terminal(color: Color) -> Terminal<SoftBackend> {
+    let backend = SoftBackend::new_with_bitmap_font(2, 1, BitmapFont::FIXED_8X13);
+    let mut terminal = Terminal::new(backend).unwrap();
+    terminal
+        .draw(|frame| frame.render_widget(Block::new().style(Style::new().bg(color)), frame.area()))
+        .unwrap();
+    terminal
+}
+
+#[test]
+fn presents_rgb565_with_padded_rows() {
+    let file = FakeFramebuffer::new("rgb565");
+    // 20x15 pixels with rows padded from 40 to 48 bytes.
+    let layout = FramebufferLayout {
+        line_length: 48,
+        ..FramebufferLayout::rgb565(20, 15)
+    };
+    let mut framebuffer = Framebuffer::open_with_layout(&file.0, layout).unwrap();
+    let mut terminal = filled_terminal(Color::Rgb(0xff, 0x80, 0x08));
+    framebuffer.present(terminal.backend_mut());
+    assert!(terminal.backend().dirty_regions().is_empty());
+    drop(framebuffer);
+
+    let data = std::fs::read(&file.0).unwrap();
+    assert_eq!(data.len(), 48 * 15);
+    // 5 bits of red, 6 of green and 5 of blue, little endian.
+    let pixel = (0b11111 << 11 | 0b100000 << 5 | 0b00001u16).to_le_bytes();
+    for (y, row) in data.chunks_exact(48).enumerate() {
+        for (x, value) in row.chunks_exact(2).enumerate() {
+            // The 16x13 pixmap, and nothing in the rest of the rows or their padding.
+            let expected = if x < 16 && y < 13 { pixel } else { [0, 0] };
+            assert_eq!(value, expected, "pixel ({x}, {y})");
+        }
+    }
+}
+
+#[test]
+fn clips_the_pixmap_to_the_framebuffer() {
+    let file = FakeFramebuffer::new("clipped");
+    let layout = FramebufferLayout::xrgb8888(10, 5);
+    let mut framebuffer = Framebuffer::open_with_layout(&file.0, layout).unwrap();
+    let mut terminal = filled_terminal(Color::Rgb(0x12, 0x34, 0x56));
+    framebuffer.present(terminal.backend_mut());
+    drop(framebuffer);
+
+    let data = std::fs::read(&file.0).unwrap();
+    assert_eq!(data.len(), 10 * 5 * 4);
+    assert!(
+        data.chunks_exact(4)
+            .all(|pixel| pixel == [0x56, 0x34, 0x12, 0])
+    );
+}
+
+#[test]
+fn rejects_channels_past_the_pixel() {
+    let file = FakeFramebuffer::new("channels");
+    for (offset, length) in [(12, 5), (30, 8), (0, 48), (u32::MAX, 2)] {
+        let layout = FramebufferLayout {
+            red: Channel { offset, length },
+            ..FramebufferLayout::rgb565(4, 4)
+        };
+        let error = Framebuffer::open_with_layout(&file.0, layout)
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput, "{offset} {length}");
+    }
+}
@@ -0,0 +1,76 @@
+//! Runs programs on pseudo terminals that don't read their input or ignore the hangup.
+#![cfg(all(feature = "pty", target_os = "linux"))]
+
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use soft_ratatui::PtyTerminal;
+
+fn sh(script: &str) -> PtyTerminal {
+    let mut command = Command::new("/bin/sh");
+    command.args(["-c", script]);
+    PtyTerminal::spawn(command, 20, 5).unwrap()
+}
+
+/// Returns whether a process exists, including as a zombie waiting to be reaped.
+fn exists(pid: u32) -> bool {
+    // SAFETY: Signal 0 only checks that the process exists.
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+/// Reads the output until the first row shows `expected`, for up to 5 seconds.
+fn wait_for_first_row(terminal: &mut PtyTerminal, expected: &str) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        terminal.update().unwrap();
+        let buffer = terminal.screen().buffer();
+        let row: String = (0..buffer.area.width)
+            .map(|x| buffer[(x, 0)].symbol())
+            .collect();
+        if row.trim_end() == expected {
+            return;
+        }
+        assert!(Instant::now() < deadline, "first row: {row:?}");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn input_beyond_the_queue_does_not_block() {
+    // Raw mode without echo, so the input only fills the queue of the program.
+    let mut terminal = sh("stty raw -echo; echo ready; sleep 10");
+    wait_for_first_row(&mut terminal, "ready");
+    let start = Instant::now();
+    // Far more than the input queue of a terminal, and never read.
+    terminal.send_bytes(&vec![b'a'; 1 << 20]).unwrap();
+    terminal.update().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn drop_reaps_the_program() {
+    let mut terminal = sh("sleep 10");
+    let pid = terminal.child().id();
+    drop(terminal);
+    assert!(!exists(pid));
+
+    // Killed once it ignores the hangup.
+    let mut terminal = sh("trap '' HUP; sleep 10");
+    let pid = terminal.child().id();
+    let start = Instant::now();
+    drop(terminal);
+    assert!(!exists(pid));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn drop_after_exit_does_not_signal() {
+    let mut terminal = sh("exit 3");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while terminal.is_running() {
+        assert!(Instant::now() < deadline, "still running");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(terminal.child().wait().unwrap().code(), Some(3));
+    drop(terminal);
+}
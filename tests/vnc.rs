@@ -0,0 +1,194 @@
+//! Drives a `VncServer` with scripted RFB viewers over loopback connections.
+#![cfg(feature = "vnc")]
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use soft_ratatui::{BitmapFont, KeyCode, SoftBackend, VncEvent, VncServer};
+
+/// How long a viewer waits for the server before the test fails.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A 10x2 terminal of 8x13 cells, 80x26 pixels.
+fn backend() -> SoftBackend {
+    SoftBackend::new_with_bitmap_font(10, 2, BitmapFont::FIXED_8X13)
+}
+
+/// A viewer connected to a server, which it polls while waiting for it.
+struct Viewer {
+    stream: TcpStream,
+}
+
+impl Viewer {
+    /// Connects with RFB 3.8 and goes through the handshake, checking the server's answers.
+    fn connect(server: &mut VncServer, backend: &mut SoftBackend) -> Self {
+        Self::connect_with_version(server, backend, 8)
+    }
+
+    /// Connects with RFB 3.3, 3.7 or 3.8 and goes through the handshake.
+    fn connect_with_version(server: &mut VncServer, backend: &mut SoftBackend, minor: u8) -> Self {
+        let stream = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        stream.set_nonblocking(true).unwrap();
+        let mut viewer = Self { stream };
+        assert_eq!(viewer.receive(server, backend, 12), b"RFB 003.008\n");
+        viewer.send(format!("RFB 003.00{minor}\n").as_bytes());
+        if minor >= 7 {
+            // One security type, None, chosen by the viewer.
+            assert_eq!(viewer.receive(server, backend, 2), [1, 1]);
+            viewer.send(&[1]);
+            // Only 3.8 gets a SecurityResult.
+            if minor >= 8 {
+                assert_eq!(viewer.receive(server, backend, 4), [0; 4]);
+            }
+        } else {
+            // The None security type, picked by the server.
+            assert_eq!(viewer.receive(server, backend, 4), [0, 0, 0, 1]);
+        }
+        // ClientInit, and ServerInit with the size, pixel format and name.
+        viewer.send(&[1]);
+        let init = viewer.receive(server, backend, 24 + 12);
+        assert_eq!(init[..4], [0, 80, 0, 26]);
+        assert_eq!(&init[24..], b"soft_ratatui");
+        viewer
+    }
+
+    fn send(&mut self, data: &[u8]) {
+        self.stream.write_all(data).unwrap();
+    }
+
+    /// Sends a non-incremental FramebufferUpdateRequest for the whole pixmap.
+    fn request_all(&mut self) {
+        self.send(&[3, 0, 0, 0, 0, 0, 0, 80, 0, 26]);
+    }
+
+    /// Polls the server until `len` bytes are received.
+    fn receive(
+        &mut self,
+        server: &mut VncServer,
+        backend: &mut SoftBackend,
+        len: usize,
+    ) -> Vec<u8> {
+        let deadline = Instant::now() + TIMEOUT;
+        let mut data = vec![0; len];
+        let mut read = 0;
+        while read < len {
+            assert!(Instant::now() < deadline, "received {read} of {len} bytes");
+            server.update(backend).unwrap();
+            match self.stream.read(&mut data[read..]) {
+                Ok(0) => panic!("disconnected after {read} of {len} bytes"),
+                Ok(n) => read += n,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                Err(error) => panic!("{error}"),
+            }
+        }
+        data
+    }
+
+    /// Polls the server until it closes the connection.
+    fn wait_closed(&mut self, server: &mut VncServer, backend: &mut SoftBackend) {
+        let deadline = Instant::now() + TIMEOUT;
+        let mut data = [0; 256];
+        loop {
+            assert!(Instant::now() < deadline, "still connected");
+            server.update(backend).unwrap();
+            match self.stream.read(&mut data) {
+                Ok(0) => return,
+                Ok(_) => {}
+                Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                Err(error) if error.kind() == ErrorKind::ConnectionReset => return,
+                Err(error) => panic!("{error}"),
+            }
+        }
+    }
+}
+
+/// Polls the server until it returns input.
+fn receive_events(server: &mut VncServer, backend: &mut SoftBackend) -> Vec<VncEvent> {
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        assert!(Instant::now() < deadline, "no input received");
+        let events = server.update(backend).unwrap();
+        if !events.is_empty() {
+            return events;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn sends_raw_pixels_and_receives_keys() {
+    let mut server = VncServer::bind("127.0.0.1:0").unwrap();
+    let mut backend = backend();
+    let mut viewer = Viewer::connect(&mut server, &mut backend);
+    assert_eq!(server.viewer_count(), 1);
+
+    viewer.request_all();
+    let update = viewer.receive(&mut server, &mut backend, 4 + 12 + 80 * 26 * 4);
+    // One rectangle of the whole pixmap, raw encoded.
+    assert_eq!(update[..4], [0, 0, 0, 1]);
+    assert_eq!(update[4..16], [0, 0, 0, 0, 0, 80, 0, 26, 0, 0, 0, 0]);
+    let [r, g, b] = backend.rgb_pixmap.get_pixel(0, 0);
+    assert_eq!(update[16..20], [b, g, r, 0]);
+
+    viewer.send(&[4, 1, 0, 0, 0, 0, 0, b'q']);
+    let events = receive_events(&mut server, &mut backend);
+    assert!(matches!(events[..], [VncEvent::Key(key)] if key.code == KeyCode::Char('q')));
+}
+
+#[test]
+fn handshakes_with_older_versions() {
+    let mut server = VncServer::bind("127.0.0.1:0").unwrap();
+    let mut backend = backend();
+    let viewers: Vec<Viewer> = [3, 7]
+        .into_iter()
+        .map(|minor| {
+            let mut viewer = Viewer::connect_with_version(&mut server, &mut backend, minor);
+            // The update comes right after ServerInit, with nothing left over from the handshake.
+            viewer.request_all();
+            let update = viewer.receive(&mut server, &mut backend, 16);
+            assert_eq!(update[..4], [0, 0, 0, 1], "RFB 3.{minor}");
+            viewer
+        })
+        .collect();
+    assert_eq!(server.viewer_count(), viewers.len());
+}
+
+#[test]
+fn rejects_shifts_past_the_pixel() {
+    let mut server = VncServer::bind("127.0.0.1:0").unwrap();
+    let mut backend = backend();
+    let mut viewer = Viewer::connect(&mut server, &mut backend);
+    // 16 bits per pixel with red shifted by 16.
+    viewer.send(&[
+        0, 0, 0, 0, 16, 16, 0, 1, 0, 31, 0, 63, 0, 31, 16, 5, 0, 0, 0, 0,
+    ]);
+    viewer.wait_closed(&mut server, &mut backend);
+    assert_eq!(server.viewer_count(), 0);
+}
+
+#[cfg(feature = "zlib")]
+#[test]
+fn stops_compressing_when_zlib_is_no_longer_asked_for() {
+    let mut server = VncServer::bind("127.0.0.1:0").unwrap();
+    let mut backend = backend();
+    let mut viewer = Viewer::connect(&mut server, &mut backend);
+
+    // SetEncodings with zlib.
+    viewer.send(&[2, 0, 0, 1, 0, 0, 0, 6]);
+    viewer.request_all();
+    let update = viewer.receive(&mut server, &mut backend, 4 + 12 + 4);
+    assert_eq!(update[12..16], [0, 0, 0, 6]);
+    let len = u32::from_be_bytes([update[16], update[17], update[18], update[19]]);
+    viewer.receive(&mut server, &mut backend, len as usize);
+
+    // SetEncodings with raw only.
+    viewer.send(&[2, 0, 0, 1, 0, 0, 0, 0]);
+    viewer.request_all();
+    let update = viewer.receive(&mut server, &mut backend, 4 + 12 + 80 * 26 * 4);
+    assert_eq!(update[12..16], [0, 0, 0, 0]);
+}
@@ -0,0 +1,93 @@
+//! Replays byte streams recorded from real programs through `VtScreen`.
+//!
+//! The streams in `tests/data` were recorded with:
+//! - `ls_color.ansi`: `LS_COLORS='di=01;34:ex=01;32:ln=01;36' ls --color=always` in a directory
+//!   holding `bin/`, `link -> notes.txt`, `notes.txt` and an executable `run.sh`.
+//! - `gcc_error.ansi`: `gcc -fdiagnostics-color=always -c bad.c` in a UTF-8 locale, with `bad.c`
+//!   returning an undeclared `answer` from `main`.
+//! - `curses_scroll_region.ansi`: `script` running an 8x30 ncurses program for
+//!   `TERM=xterm-256color`, which draws a header and a footer, then prints ten lines into a
+//!   scroll region between them and exits without `endwin`.
+
+use ratatui::layout::Position;
+use ratatui::style::{Color, Modifier};
+use soft_ratatui::VtScreen;
+
+fn replay(width: u16, height: u16, stream: &[u8]) -> VtScreen {
+    let mut screen = VtScreen::new(width, height);
+    // LF without CR, as the pseudo terminal's output processing would add it.
+    for line in stream.split_inclusive(|byte| *byte == b'\n') {
+        screen.feed(line);
+        if line.ends_with(b"\n") {
+            screen.feed(b"\r");
+        }
+    }
+    screen
+}
+
+fn row(screen: &VtScreen, y: u16) -> String {
+    let buffer = screen.buffer();
+    let row: String = (0..buffer.area.width)
+        .map(|x| buffer[(x, y)].symbol())
+        .collect();
+    row.trim_end().to_string()
+}
+
+#[test]
+fn ls_color() {
+    let screen = replay(20, 5, include_bytes!("data/ls_color.ansi"));
+    let rows: Vec<String> = (0..5).map(|y| row(&screen, y)).collect();
+    assert_eq!(rows, ["bin", "link", "notes.txt", "run.sh", ""]);
+
+    let buffer = screen.buffer();
+    assert_eq!(buffer[(0, 0)].fg, Color::Blue);
+    assert_eq!(buffer[(0, 1)].fg, Color::Cyan);
+    assert_eq!(buffer[(0, 2)].fg, Color::Reset);
+    assert_eq!(buffer[(0, 3)].fg, Color::Green);
+    assert!(buffer[(0, 3)].modifier.contains(Modifier::BOLD));
+    assert!(!buffer[(0, 2)].modifier.contains(Modifier::BOLD));
+}
+
+#[test]
+fn gcc_error() {
+    let screen = replay(80, 8, include_bytes!("data/gcc_error.ansi"));
+    assert_eq!(row(&screen, 0), "bad.c: In function ‘main’:");
+    assert_eq!(
+        row(&screen, 1),
+        "bad.c:2:12: error: ‘answer’ undeclared (first use in this function)"
+    );
+    assert_eq!(row(&screen, 2), "    2 |     return answer;");
+    assert_eq!(row(&screen, 3), "      |            ^~~~~~");
+
+    let buffer = screen.buffer();
+    // "error: " is bold red, the source line highlights the identifier.
+    assert_eq!(buffer[(12, 1)].fg, Color::Red);
+    assert!(buffer[(12, 1)].modifier.contains(Modifier::BOLD));
+    assert_eq!(buffer[(19, 2)].fg, Color::Red);
+    assert_eq!(buffer[(18, 2)].fg, Color::Reset);
+    // The long note wraps onto the next row.
+    assert_eq!(row(&screen, 5), "tion it appears in");
+}
+
+#[test]
+fn curses_scroll_region() {
+    let screen = replay(30, 8, include_bytes!("data/curses_scroll_region.ansi"));
+    assert!(screen.alternate_screen());
+    let rows: Vec<String> = (0..8).map(|y| row(&screen, y)).collect();
+    assert_eq!(
+        rows,
+        [
+            "Header", "line 4", "line 5", "line 6", "line 7", "line 8", "line 9", "Footer"
+        ]
+    );
+
+    let buffer = screen.buffer();
+    assert_eq!(buffer[(0, 0)].fg, Color::Red);
+    assert!(buffer[(0, 0)].modifier.contains(Modifier::BOLD));
+    assert_eq!(
+        (buffer[(0, 7)].fg, buffer[(0, 7)].bg),
+        (Color::Black, Color::Cyan)
+    );
+    assert_eq!(buffer[(0, 3)].fg, Color::Reset);
+    assert_eq!(screen.cursor(), Position::new(6, 6));
+}